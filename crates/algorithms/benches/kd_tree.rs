@@ -0,0 +1,125 @@
+// SPDX-License-Identifier: MIT
+/*
+ * Copyright (c) [2023 - Present] Emily Matheys <emilymatt96@gmail.com>
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+//! Compares the build and query times of the boxed [`KDTree`] and the arena-backed [`FlatKDTree`],
+//! on the nearest-neighbour loop performed by each ICP iteration.
+//! Run with `cargo bench --bench kd_tree`.
+
+use std::{hint::black_box, time::Instant};
+
+use mapping_algorithms::{
+    kd_tree::{flat::FlatKDTree, KDTree, SearchMode},
+    utils::point_cloud::{generate_point_cloud, transform_point_cloud},
+};
+use nalgebra::{Isometry3, Point3, Vector3};
+
+const REPETITIONS: u32 = 10;
+
+fn measure<R, F: FnMut() -> R>(name: &str, num_points: usize, mut func: F) {
+    // Warm up caches before measuring
+    black_box(func());
+
+    let start = Instant::now();
+    for _ in 0..REPETITIONS {
+        black_box(func());
+    }
+    println!(
+        "{name:<32} {num_points:>8} points: {:>12.3?}",
+        start.elapsed() / REPETITIONS
+    );
+}
+
+fn nearest_neighbour_loop<F: Fn(&Point3<f64>) -> Option<Point3<f64>>>(
+    source_points: &[Point3<f64>],
+    nearest: F,
+) -> Vec<Point3<f64>> {
+    source_points.iter().filter_map(nearest).collect()
+}
+
+fn main() {
+    for num_points in [1_000, 10_000, 100_000] {
+        let points_b = generate_point_cloud(num_points, [-50.0..=50.0, -50.0..=50.0, -5.0..=5.0]);
+        let points_a = transform_point_cloud(
+            &points_b,
+            Isometry3::new(Vector3::new(0.5, -0.3, 0.1), Vector3::new(0.0, 0.0, 0.05)),
+        );
+
+        measure("KDTree build", num_points, || {
+            KDTree::from(points_b.as_slice())
+        });
+        measure("FlatKDTree build", num_points, || {
+            FlatKDTree::from(points_b.as_slice())
+        });
+
+        let kd_tree = KDTree::from(points_b.as_slice());
+        let flat_tree = FlatKDTree::from(points_b.as_slice());
+        measure("KDTree nearest neighbours", num_points, || {
+            nearest_neighbour_loop(&points_a, |point| kd_tree.nearest(point))
+        });
+        measure("FlatKDTree nearest neighbours", num_points, || {
+            nearest_neighbour_loop(&points_a, |point| flat_tree.nearest(point))
+        });
+        for (name, search_mode) in [
+            (
+                "KDTree (1+0.5) approximate",
+                SearchMode::Approximate { epsilon: 0.5 },
+            ),
+            ("KDTree 32 leaf checks", SearchMode::MaxLeafChecks(32)),
+        ] {
+            measure(name, num_points, || {
+                nearest_neighbour_loop(&points_a, |point| {
+                    kd_tree
+                        .search_nearest(point, search_mode)
+                        .map(|(nearest, _)| nearest)
+                })
+            });
+        }
+        for (name, search_mode) in [
+            (
+                "FlatKDTree (1+0.5) approximate",
+                SearchMode::Approximate { epsilon: 0.5 },
+            ),
+            ("FlatKDTree 4 leaf checks", SearchMode::MaxLeafChecks(4)),
+        ] {
+            measure(name, num_points, || {
+                nearest_neighbour_loop(&points_a, |point| {
+                    flat_tree
+                        .search_nearest(point, search_mode)
+                        .map(|(nearest, _)| nearest)
+                })
+            });
+        }
+        measure("KDTree 8 nearest neighbours", num_points, || {
+            points_a
+                .iter()
+                .map(|point| kd_tree.k_nearest(point, 8))
+                .collect::<Vec<_>>()
+        });
+        measure("FlatKDTree 8 nearest neighbours", num_points, || {
+            points_a
+                .iter()
+                .map(|point| flat_tree.k_nearest(point, 8))
+                .collect::<Vec<_>>()
+        });
+    }
+}
@@ -23,7 +23,10 @@
 
 use eframe::{egui, epaint};
 use mapping_algorithms::{
-    icp::{icp_iteration, types::ICPConfiguration},
+    icp::{
+        icp_iteration,
+        types::{ICPConfiguration, ICPIterationStatus},
+    },
     kd_tree::KDTree,
     utils::point_cloud::{
         calculate_point_cloud_center, generate_point_cloud, transform_point_cloud,
@@ -116,16 +119,25 @@ fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
                     &mut self.current_mse,
                     &self.config.into(),
                 ) {
-                    Ok(mse) => {
+                    Ok(ICPIterationStatus::Converged { mse, .. }) => {
                         log::info!(
                             "Successfully converged after {} iterations! Last MSE: {mse}",
                             self.current_iteration
                         );
                         self.converged = true;
                     }
-                    Err(means) => {
-                        log::info!("MSE {}", self.current_mse);
-                        self.current_means = means;
+                    Ok(ICPIterationStatus::NotConverged {
+                        mse,
+                        source_centeroid,
+                        target_centeroid,
+                        ..
+                    }) => {
+                        log::info!("MSE {mse}");
+                        self.current_means = (source_centeroid, target_centeroid);
+                    }
+                    Err(err) => {
+                        log::error!("{err}");
+                        self.converged = true;
                     }
                 }
 
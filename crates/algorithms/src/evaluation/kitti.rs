@@ -0,0 +1,453 @@
+// SPDX-License-Identifier: MIT
+/*
+ * Copyright (c) [2023 - Present] Emily Matheys <emilymatt96@gmail.com>
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+use nalgebra::{Isometry3, RealField};
+use num_traits::AsPrimitive;
+
+use super::{types::EvaluationError, types::SegmentError, validate_trajectories};
+use crate::Vec;
+
+#[cfg(feature = "io")]
+use {
+    crate::io::{
+        helpers::parse_value,
+        types::{IOError, PointCloudData},
+    },
+    core::fmt::Display,
+    nalgebra::{Matrix3, Rotation3, Translation3, UnitQuaternion},
+    std::{
+        fs::File,
+        io::{BufRead, BufReader, Read, Write},
+        path::Path,
+    },
+};
+
+/// The segment lengths used by the official KITTI odometry benchmark, in meters.
+pub const KITTI_SEGMENT_LENGTHS: [f64; 8] =
+    [100.0, 200.0, 300.0, 400.0, 500.0, 600.0, 700.0, 800.0];
+
+/// The amount of frames between the first frames of consecutive segments in the official KITTI odometry benchmark.
+pub const KITTI_STEP_SIZE: usize = 10;
+
+/// The size of a single point in a velodyne scan, four little-endian [`f32`]s.
+#[cfg(feature = "io")]
+const VELODYNE_POINT_SIZE: usize = 16;
+
+/// Reads a KITTI velodyne scan, stored as consecutive little-endian `x`, `y`, `z` and reflectance [`f32`] values.
+///
+/// # Arguments
+/// * `reader`: a [`Read`], from which to read the scan, usually a `.bin` file.
+///
+/// # Returns
+/// A [`PointCloudData`] containing the points of the scan and their reflectance in an `intensity` field,
+/// or an [`IOError`] if the scan could not be read.
+#[cfg(feature = "io")]
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument("Read KITTI Velodyne Scan", skip_all, level = "info")
+)]
+pub fn read_velodyne_scan<R: Read>(mut reader: R) -> Result<PointCloudData<f32, 3>, IOError> {
+    let mut data = Vec::new();
+    reader.read_to_end(&mut data)?;
+    if data.len() % VELODYNE_POINT_SIZE != 0 {
+        return Err(IOError::InvalidData {
+            reason: format!(
+                "scan size of {} bytes is not a multiple of {VELODYNE_POINT_SIZE}",
+                data.len()
+            ),
+        });
+    }
+
+    let values = data
+        .chunks_exact(4)
+        .map(|bytes| f32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+        .collect::<Vec<_>>();
+    let points = values
+        .chunks_exact(4)
+        .map(|point| nalgebra::Point3::new(point[0], point[1], point[2]))
+        .collect();
+    let intensities = values.chunks_exact(4).map(|point| point[3]).collect();
+    Ok(PointCloudData::new(points).with_field("intensity", intensities))
+}
+
+/// Reads a KITTI velodyne scan from a file, see [`read_velodyne_scan`].
+///
+/// # Arguments
+/// * `path`: the path of the scan's `.bin` file.
+///
+/// # Returns
+/// A [`PointCloudData`] containing the points of the scan and their reflectance in an `intensity` field,
+/// or an [`IOError`] if the scan could not be read.
+#[cfg(feature = "io")]
+pub fn read_velodyne_scan_file(path: impl AsRef<Path>) -> Result<PointCloudData<f32, 3>, IOError> {
+    read_velodyne_scan(BufReader::new(File::open(path)?))
+}
+
+/// Reads a KITTI pose file, where each line contains the first three rows of a 4x4 homogeneous transform, in row-major order.
+///
+/// # Arguments
+/// * `reader`: a [`BufRead`], from which to read the poses.
+///
+/// # Generics
+/// * `T`: Either an [`f32`] or [`f64`].
+///
+/// # Returns
+/// A [`Vec`] of [`Isometry3`], one for each line, or an [`IOError`] if the poses could not be read.
+///
+/// # Warnings
+/// * The rotation part of each transform is projected onto the closest rotation, to account for rounding in the file.
+#[cfg(feature = "io")]
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument("Read KITTI Poses", skip_all, level = "info")
+)]
+pub fn read_poses<T, R>(reader: R) -> Result<Vec<Isometry3<T>>, IOError>
+where
+    T: Copy + RealField,
+    f64: AsPrimitive<T>,
+    R: BufRead,
+{
+    let mut poses = Vec::new();
+    for (line_idx, line) in reader.lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let values = line
+            .split_whitespace()
+            .map(|token| parse_value(token, line_idx).map(AsPrimitive::as_))
+            .collect::<Result<Vec<T>, _>>()?;
+        if values.len() != 12 {
+            return Err(IOError::InvalidData {
+                reason: format!(
+                    "line {} has {} values, expected 12",
+                    line_idx + 1,
+                    values.len()
+                ),
+            });
+        }
+
+        let rotation =
+            Rotation3::from_matrix(&Matrix3::from_fn(|row, column| values[row * 4 + column]));
+        poses.push(Isometry3::from_parts(
+            Translation3::new(values[3], values[7], values[11]),
+            UnitQuaternion::from_rotation_matrix(&rotation),
+        ));
+    }
+
+    Ok(poses)
+}
+
+/// Reads a KITTI pose file, see [`read_poses`].
+///
+/// # Arguments
+/// * `path`: the path of the pose file.
+///
+/// # Generics
+/// * `T`: Either an [`f32`] or [`f64`].
+///
+/// # Returns
+/// A [`Vec`] of [`Isometry3`], one for each line, or an [`IOError`] if the poses could not be read.
+#[cfg(feature = "io")]
+pub fn read_poses_file<T>(path: impl AsRef<Path>) -> Result<Vec<Isometry3<T>>, IOError>
+where
+    T: Copy + RealField,
+    f64: AsPrimitive<T>,
+{
+    read_poses(BufReader::new(File::open(path)?))
+}
+
+/// Writes poses in the KITTI pose format, so that they can be evaluated by the official development kit.
+///
+/// # Arguments
+/// * `writer`: a [`Write`], to which to write the poses.
+/// * `poses`: a slice of [`Isometry3`], one for each frame.
+///
+/// # Generics
+/// * `T`: Either an [`f32`] or [`f64`].
+///
+/// # Returns
+/// An [`IOError`] if the poses could not be written.
+#[cfg(feature = "io")]
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument("Write KITTI Poses", skip_all, level = "info")
+)]
+pub fn write_poses<T, W>(mut writer: W, poses: &[Isometry3<T>]) -> Result<(), IOError>
+where
+    T: Copy + Display + RealField,
+    W: Write,
+{
+    for pose in poses {
+        let matrix = pose.to_homogeneous();
+        let line = (0..3)
+            .flat_map(|row| (0..4).map(move |column| (row, column)))
+            .map(|(row, column)| matrix[(row, column)].to_string())
+            .collect::<Vec<_>>()
+            .join(" ");
+        writeln!(writer, "{line}")?;
+    }
+    Ok(())
+}
+
+/// Calculates the distance travelled along a trajectory up to each of its poses.
+///
+/// # Arguments
+/// * `poses`: a slice of [`Isometry3`], representing the trajectory.
+///
+/// # Generics
+/// * `T`: Either an [`f32`] or [`f64`].
+///
+/// # Returns
+/// A [`Vec`] with the same size as `poses`, starting at `0`.
+pub fn trajectory_distances<T>(poses: &[Isometry3<T>]) -> Vec<T>
+where
+    T: Copy + RealField,
+{
+    let mut distance = T::zero();
+    poses
+        .iter()
+        .enumerate()
+        .map(|(idx, pose)| {
+            if idx > 0 {
+                distance += (pose.translation.vector - poses[idx - 1].translation.vector).norm();
+            }
+            distance
+        })
+        .collect()
+}
+
+/// Calculates the drift of an estimated trajectory over segments of the ground truth trajectory, as in the KITTI odometry benchmark.
+/// A segment starts every `step_size` frames for each of the `segment_lengths`, and ends at the first frame at which the ground truth
+/// trajectory has travelled farther than the segment's length, segments which would end beyond the last frame are skipped.
+/// The error of each segment is the relative transform between the estimated and ground truth motions over the segment.
+///
+/// # Arguments
+/// * `ground_truth`: a slice of [`Isometry3`], representing the ground truth trajectory.
+/// * `estimated`: a slice of [`Isometry3`], representing the estimated trajectory, where each pose matches the ground truth pose at the same index.
+/// * `segment_lengths`: the lengths of the segments to evaluate, see [`KITTI_SEGMENT_LENGTHS`] for the official ones.
+/// * `step_size`: the amount of frames between the first frames of consecutive segments, see [`KITTI_STEP_SIZE`] for the official one.
+///
+/// # Generics
+/// * `T`: Either an [`f32`] or [`f64`].
+///
+/// # Returns
+/// A [`Vec`] with a [`SegmentError`] for each evaluated segment, or an [`EvaluationError`] if the trajectories cannot be compared.
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument("Calculate KITTI Segment Errors", skip_all, level = "info")
+)]
+pub fn segment_errors<T>(
+    ground_truth: &[Isometry3<T>],
+    estimated: &[Isometry3<T>],
+    segment_lengths: &[T],
+    step_size: usize,
+) -> Result<Vec<SegmentError<T>>, EvaluationError>
+where
+    T: Copy + RealField,
+{
+    validate_trajectories(ground_truth, estimated)?;
+
+    let distances = trajectory_distances(ground_truth);
+    let mut errors = Vec::new();
+    for first_frame in (0..ground_truth.len()).step_by(step_size.max(1)) {
+        for segment_length in segment_lengths.iter().copied() {
+            let Some(last_frame) = (first_frame..ground_truth.len())
+                .find(|&frame| distances[frame] > distances[first_frame] + segment_length)
+            else {
+                continue;
+            };
+
+            let ground_truth_delta = ground_truth[first_frame].inv_mul(&ground_truth[last_frame]);
+            let estimated_delta = estimated[first_frame].inv_mul(&estimated[last_frame]);
+            let pose_error = estimated_delta.inv_mul(&ground_truth_delta);
+            errors.push(SegmentError {
+                first_frame,
+                segment_length,
+                translation_error: pose_error.translation.vector.norm() / segment_length,
+                rotation_error: pose_error.rotation.angle() / segment_length,
+            });
+        }
+    }
+
+    Ok(errors)
+}
+
+/// Averages the errors of all segments, producing the translation and rotation errors reported by the KITTI odometry benchmark.
+///
+/// # Arguments
+/// * `errors`: a slice of [`SegmentError`], see [`segment_errors`].
+///
+/// # Generics
+/// * `T`: Either an [`f32`] or [`f64`].
+///
+/// # Returns
+/// A tuple of the mean translation error (multiply by `100` for a percentage) and the mean rotation error in radians per unit of length,
+/// or [`None`] if there are no segments.
+pub fn mean_segment_errors<T>(errors: &[SegmentError<T>]) -> Option<(T, T)>
+where
+    T: Copy + RealField,
+    usize: AsPrimitive<T>,
+{
+    if errors.is_empty() {
+        return None;
+    }
+
+    let num_errors: T = errors.len().as_();
+    let (translation_error_sum, rotation_error_sum) = errors.iter().fold(
+        (T::zero(), T::zero()),
+        |(translation_acc, rotation_acc), error| {
+            (
+                translation_acc + error.translation_error,
+                rotation_acc + error.rotation_error,
+            )
+        },
+    );
+    Some((
+        translation_error_sum / num_errors,
+        rotation_error_sum / num_errors,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use nalgebra::{Translation3, UnitQuaternion, Vector3};
+
+    use super::*;
+
+    #[cfg(feature = "io")]
+    fn test_data_path(file_name: &str) -> std::path::PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("tests/data/kitti")
+            .join(file_name)
+    }
+
+    fn generate_ground_truth() -> Vec<Isometry3<f64>> {
+        // Driving forward one meter per frame, while turning slightly
+        (0..30)
+            .scan(Isometry3::identity(), |pose, _| {
+                let current_pose = *pose;
+                *pose *= Isometry3::from_parts(
+                    Translation3::new(0.0, 0.0, 1.0),
+                    UnitQuaternion::from_axis_angle(&Vector3::y_axis(), 0.02),
+                );
+                Some(current_pose)
+            })
+            .collect()
+    }
+
+    #[cfg(feature = "io")]
+    #[test]
+    fn test_read_velodyne_scan() {
+        let scan = read_velodyne_scan_file(test_data_path("velodyne/000000.bin")).unwrap();
+        assert_eq!(
+            scan.points,
+            [
+                nalgebra::Point3::new(1.5, -2.0, 0.25),
+                nalgebra::Point3::new(10.0, 0.0, -1.75),
+                nalgebra::Point3::new(-3.25, 4.5, 1.0),
+                nalgebra::Point3::new(0.0, 0.0, 0.0)
+            ]
+        );
+        assert_eq!(
+            scan.field("intensity"),
+            Some([0.5, 0.0, 1.0, 0.125].as_slice())
+        );
+
+        assert!(matches!(
+            read_velodyne_scan(std::io::Cursor::new([0u8; 17])),
+            Err(IOError::InvalidData { .. })
+        ));
+    }
+
+    #[cfg(feature = "io")]
+    #[test]
+    fn test_read_poses() {
+        let poses = read_poses_file::<f64>(test_data_path("poses/00.txt")).unwrap();
+        let ground_truth = generate_ground_truth();
+        assert_eq!(poses.len(), ground_truth.len());
+        for (pose, ground_truth_pose) in poses.iter().zip(ground_truth.iter()) {
+            assert!((pose.translation.vector - ground_truth_pose.translation.vector).norm() < 1e-5);
+            assert!(pose.rotation.angle_to(&ground_truth_pose.rotation) < 1e-5);
+        }
+
+        let mut buffer = Vec::new();
+        write_poses(&mut buffer, &ground_truth).unwrap();
+        let written_poses = read_poses::<f64, _>(std::io::Cursor::new(buffer)).unwrap();
+        for (pose, ground_truth_pose) in written_poses.iter().zip(ground_truth.iter()) {
+            assert!((pose.to_homogeneous() - ground_truth_pose.to_homogeneous()).norm() < 1e-12);
+        }
+
+        assert!(matches!(
+            read_poses::<f64, _>(std::io::Cursor::new("1 0 0 0 0 1 0 0 0 0 1\n")),
+            Err(IOError::InvalidData { .. })
+        ));
+    }
+
+    #[test]
+    fn test_trajectory_distances() {
+        let distances = trajectory_distances(&generate_ground_truth());
+        assert_eq!(distances.len(), 30);
+        assert_eq!(distances[0], 0.0);
+        assert!((distances[29] - 29.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_segment_errors() {
+        let ground_truth = generate_ground_truth();
+        let errors = segment_errors(&ground_truth, &ground_truth, &[5.0, 10.0], 5).unwrap();
+        // Segments of length 5 can start at every step but the last, and segments of length 10 at the first 4 steps
+        assert_eq!(errors.len(), 5 + 4);
+        assert!(errors
+            .iter()
+            .all(|error| error.translation_error < 1e-9 && error.rotation_error < 1e-9));
+        assert!(segment_errors(&ground_truth, &ground_truth, &[50.0], 1)
+            .unwrap()
+            .is_empty());
+
+        // An estimate which overshoots every motion by 2% drifts by 2% of each segment's length, without rotating
+        let estimated = ground_truth
+            .iter()
+            .map(|pose| {
+                Isometry3::from_parts(
+                    Translation3::from(pose.translation.vector * 1.02),
+                    pose.rotation,
+                )
+            })
+            .collect::<Vec<_>>();
+        let errors = segment_errors(&ground_truth, &estimated, &[5.0, 10.0], 5).unwrap();
+        let (translation_error, rotation_error) = mean_segment_errors(&errors).unwrap();
+        assert!((translation_error - 0.02).abs() < 5e-3);
+        assert!(rotation_error < 1e-9);
+
+        assert_eq!(
+            segment_errors(&ground_truth, &estimated[1..], &[5.0], 1),
+            Err(EvaluationError::MismatchedLengths {
+                ground_truth: 30,
+                estimated: 29
+            })
+        );
+        assert!(mean_segment_errors::<f64>(&[]).is_none());
+    }
+}
@@ -0,0 +1,548 @@
+// SPDX-License-Identifier: MIT
+/*
+ * Copyright (c) [2023 - Present] Emily Matheys <emilymatt96@gmail.com>
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+use crate::{
+    types::{AbstractIsometry, IsometryAbstractor},
+    Vec,
+};
+use nalgebra::{
+    AbstractRotation, Isometry, Point, RealField, SMatrix, SVector, Similarity, Translation,
+};
+use num_traits::AsPrimitive;
+use types::{EvaluationError, RelativePoseError, TrajectoryAlignment};
+
+/// Loaders and odometry metrics for the KITTI odometry benchmark.
+pub mod kitti;
+
+/// Loaders and timestamp association for trajectories in the TUM RGB-D benchmark format.
+pub mod tum;
+
+/// Structs in use as part of the public API of the evaluation metrics.
+pub mod types;
+
+/// A shorthand for an [`Isometry`] in `N` dimensions, using the rotation type matching that dimension.
+type Pose<T, const N: usize> =
+    Isometry<T, <IsometryAbstractor<T, N> as AbstractIsometry<T, N>>::RotType, N>;
+
+/// Makes sure that two trajectories can be compared pose by pose.
+pub(crate) fn validate_trajectories<A, B>(
+    ground_truth: &[A],
+    estimated: &[B],
+) -> Result<(), EvaluationError> {
+    if ground_truth.len() != estimated.len() {
+        return Err(EvaluationError::MismatchedLengths {
+            ground_truth: ground_truth.len(),
+            estimated: estimated.len(),
+        });
+    }
+
+    if ground_truth.is_empty() {
+        return Err(EvaluationError::EmptyTrajectory);
+    }
+
+    Ok(())
+}
+
+/// Calculates the Absolute Trajectory Error, the Root Mean Squared distance between the positions of matching poses.
+/// The trajectories are compared as-is, so they must already be expressed in the same frame.
+///
+/// # Arguments
+/// * `ground_truth`: a slice of [`Isometry`], representing the ground truth trajectory.
+/// * `estimated`: a slice of [`Isometry`], representing the estimated trajectory, where each pose matches the ground truth pose at the same index.
+///
+/// # Generics
+/// * `T`: Either an [`f32`] or [`f64`].
+/// * `R`: An SO(N) rotation, either a [`UnitComplex`](nalgebra::UnitComplex) or a [`UnitQuaternion`](nalgebra::UnitQuaternion).
+/// * `N`: A const usize, either `2` or `3`.
+///
+/// # Returns
+/// The Absolute Trajectory Error, or an [`EvaluationError`] if the trajectories cannot be compared.
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument("Calculate Absolute Trajectory Error", skip_all)
+)]
+pub fn absolute_trajectory_error<T, R, const N: usize>(
+    ground_truth: &[Isometry<T, R, N>],
+    estimated: &[Isometry<T, R, N>],
+) -> Result<T, EvaluationError>
+where
+    T: Copy + RealField,
+    R: AbstractRotation<T, N>,
+    usize: AsPrimitive<T>,
+{
+    validate_trajectories(ground_truth, estimated)?;
+
+    let squared_error_sum = ground_truth.iter().zip(estimated.iter()).fold(
+        T::zero(),
+        |acc, (ground_truth_pose, estimated_pose)| {
+            acc + (ground_truth_pose.translation.vector - estimated_pose.translation.vector)
+                .norm_squared()
+        },
+    );
+    Ok((squared_error_sum / ground_truth.len().as_()).sqrt())
+}
+
+/// Calculates the rotation angle of an SO(N) rotation from the trace of its matrix, for `N` of either `2` or `3`.
+fn rotation_angle<T, R, const N: usize>(rotation: &R) -> T
+where
+    T: Copy + RealField,
+    R: AbstractRotation<T, N>,
+    usize: AsPrimitive<T>,
+{
+    let trace = (0..N).fold(T::zero(), |acc, idx| {
+        let axis = SVector::<T, N>::from_fn(|row, _| if row == idx { T::one() } else { T::zero() });
+        acc + axis.dot(&rotation.transform_vector(&axis))
+    });
+    let two = T::one() + T::one();
+    ((trace - (N - 2).as_()) / two)
+        .clamp(T::one().neg(), T::one())
+        .acos()
+}
+
+/// Estimates the transform which best maps a set of source points onto a set of target points, in the least squares sense,
+/// using the closed-form method of Umeyama.
+///
+/// # Arguments
+/// * `source`: a slice of [`Point`], representing the points to be transformed.
+/// * `target`: a slice of [`Point`], where each point matches the source point at the same index.
+/// * `with_scale`: whether to also estimate a uniform scale, or only a rotation and a translation.
+///
+/// # Generics
+/// * `T`: Either an [`f32`] or [`f64`].
+/// * `N`: A const usize, either `2` or `3`.
+///
+/// # Returns
+/// A [`Similarity`] which maps `source` onto `target`, its scaling is `1` when `with_scale` is `false`.
+/// Returns an [`EvaluationError`] if the point sets cannot be matched, or if either of them is concentrated in a single position.
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument("Estimate Umeyama Alignment", skip_all, level = "info")
+)]
+pub fn umeyama_alignment<T, const N: usize>(
+    source: &[Point<T, N>],
+    target: &[Point<T, N>],
+    with_scale: bool,
+) -> Result<
+    Similarity<T, <IsometryAbstractor<T, N> as AbstractIsometry<T, N>>::RotType, N>,
+    EvaluationError,
+>
+where
+    T: Copy + RealField,
+    usize: AsPrimitive<T>,
+    IsometryAbstractor<T, N>: AbstractIsometry<T, N>,
+{
+    validate_trajectories(target, source)?;
+
+    let num_points: T = source.len().as_();
+    let mean_source = Point::from(
+        source
+            .iter()
+            .fold(SVector::<T, N>::zeros(), |acc, point| acc + point.coords)
+            / num_points,
+    );
+    let mean_target = Point::from(
+        target
+            .iter()
+            .fold(SVector::<T, N>::zeros(), |acc, point| acc + point.coords)
+            / num_points,
+    );
+
+    let (covariance, source_variance) = source.iter().zip(target.iter()).fold(
+        (SMatrix::<T, N, N>::zeros(), T::zero()),
+        |(covariance, variance), (source_point, target_point)| {
+            let source_distance = source_point - mean_source;
+            (
+                covariance + (target_point - mean_target) * source_distance.transpose(),
+                variance + source_distance.norm_squared(),
+            )
+        },
+    );
+    if source_variance <= T::default_epsilon() {
+        return Err(EvaluationError::DegenerateTrajectory);
+    }
+
+    let rigid = IsometryAbstractor::<T, N>::update_transform(
+        &Isometry::identity(),
+        mean_source,
+        mean_target,
+        &covariance,
+    );
+    if !with_scale {
+        return Ok(Similarity::from_isometry(rigid, T::one()));
+    }
+
+    // The trace of the rotated covariance matrix is the sum of the (sign-corrected) singular values
+    let scale =
+        source
+            .iter()
+            .zip(target.iter())
+            .fold(T::zero(), |acc, (source_point, target_point)| {
+                acc + (target_point - mean_target).dot(
+                    &rigid
+                        .rotation
+                        .transform_vector(&(source_point - mean_source)),
+                )
+            })
+            / source_variance;
+    if scale <= T::default_epsilon() {
+        return Err(EvaluationError::DegenerateTrajectory);
+    }
+
+    let translation =
+        mean_target.coords - rigid.rotation.transform_vector(&mean_source.coords) * scale;
+    Ok(Similarity::from_parts(
+        Translation::from(translation),
+        rigid.rotation,
+        scale,
+    ))
+}
+
+/// Aligns an estimated trajectory to the ground truth trajectory, using the positions of matching poses.
+///
+/// # Arguments
+/// * `ground_truth`: a slice of [`Isometry`], representing the ground truth trajectory.
+/// * `estimated`: a slice of [`Isometry`], representing the estimated trajectory, where each pose matches the ground truth pose at the same index.
+/// * `alignment`: a [`TrajectoryAlignment`], specifying which transform to estimate.
+///
+/// # Generics
+/// * `T`: Either an [`f32`] or [`f64`].
+/// * `N`: A const usize, either `2` or `3`.
+///
+/// # Returns
+/// A [`Vec`] containing the aligned estimated trajectory, or an [`EvaluationError`] if the trajectories cannot be aligned.
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument("Align Trajectory", skip_all, level = "info")
+)]
+pub fn align_trajectory<T, const N: usize>(
+    ground_truth: &[Pose<T, N>],
+    estimated: &[Pose<T, N>],
+    alignment: TrajectoryAlignment,
+) -> Result<Vec<Pose<T, N>>, EvaluationError>
+where
+    T: Copy + RealField,
+    usize: AsPrimitive<T>,
+    IsometryAbstractor<T, N>: AbstractIsometry<T, N>,
+{
+    validate_trajectories(ground_truth, estimated)?;
+
+    let with_scale = match alignment {
+        TrajectoryAlignment::None => return Ok(estimated.to_vec()),
+        TrajectoryAlignment::Rigid => false,
+        TrajectoryAlignment::Similarity => true,
+    };
+
+    let ground_truth_positions = ground_truth
+        .iter()
+        .map(|pose| Point::from(pose.translation.vector))
+        .collect::<Vec<_>>();
+    let estimated_positions = estimated
+        .iter()
+        .map(|pose| Point::from(pose.translation.vector))
+        .collect::<Vec<_>>();
+    let similarity = umeyama_alignment(&estimated_positions, &ground_truth_positions, with_scale)?;
+
+    Ok(estimated
+        .iter()
+        .map(|pose| {
+            similarity.isometry
+                * Isometry::from_parts(
+                    Translation::from(pose.translation.vector * similarity.scaling()),
+                    pose.rotation,
+                )
+        })
+        .collect())
+}
+
+/// Calculates the Absolute Trajectory Error after aligning the estimated trajectory to the ground truth trajectory,
+/// see [`align_trajectory`] and [`absolute_trajectory_error`].
+///
+/// # Arguments
+/// * `ground_truth`: a slice of [`Isometry`], representing the ground truth trajectory.
+/// * `estimated`: a slice of [`Isometry`], representing the estimated trajectory, where each pose matches the ground truth pose at the same index.
+/// * `alignment`: a [`TrajectoryAlignment`], specifying which transform to estimate.
+///
+/// # Generics
+/// * `T`: Either an [`f32`] or [`f64`].
+/// * `N`: A const usize, either `2` or `3`.
+///
+/// # Returns
+/// The Absolute Trajectory Error, or an [`EvaluationError`] if the trajectories cannot be aligned or compared.
+pub fn aligned_absolute_trajectory_error<T, const N: usize>(
+    ground_truth: &[Pose<T, N>],
+    estimated: &[Pose<T, N>],
+    alignment: TrajectoryAlignment,
+) -> Result<T, EvaluationError>
+where
+    T: Copy + RealField,
+    usize: AsPrimitive<T>,
+    IsometryAbstractor<T, N>: AbstractIsometry<T, N>,
+{
+    absolute_trajectory_error(
+        ground_truth,
+        &align_trajectory(ground_truth, estimated, alignment)?,
+    )
+}
+
+/// Calculates the Relative Pose Error, comparing the motion between every pair of poses `delta` frames apart.
+/// Since only relative motions are compared, the trajectories do not need to be aligned beforehand.
+///
+/// # Arguments
+/// * `ground_truth`: a slice of [`Isometry`], representing the ground truth trajectory.
+/// * `estimated`: a slice of [`Isometry`], representing the estimated trajectory, where each pose matches the ground truth pose at the same index.
+/// * `delta`: the amount of frames between the poses of each pair, `1` measures the drift between consecutive frames.
+///
+/// # Generics
+/// * `T`: Either an [`f32`] or [`f64`].
+/// * `R`: An SO(N) rotation, either a [`UnitComplex`](nalgebra::UnitComplex) or a [`UnitQuaternion`](nalgebra::UnitQuaternion).
+/// * `N`: A const usize, either `2` or `3`.
+///
+/// # Returns
+/// A [`RelativePoseError`], or an [`EvaluationError`] if the trajectories cannot be compared.
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument("Calculate Relative Pose Error", skip_all)
+)]
+pub fn relative_pose_error<T, R, const N: usize>(
+    ground_truth: &[Isometry<T, R, N>],
+    estimated: &[Isometry<T, R, N>],
+    delta: usize,
+) -> Result<RelativePoseError<T>, EvaluationError>
+where
+    T: Copy + RealField,
+    R: AbstractRotation<T, N>,
+    usize: AsPrimitive<T>,
+{
+    validate_trajectories(ground_truth, estimated)?;
+    if delta == 0 || delta >= ground_truth.len() {
+        return Err(EvaluationError::InvalidFrameDelta {
+            delta,
+            num_poses: ground_truth.len(),
+        });
+    }
+
+    let num_pairs = ground_truth.len() - delta;
+    let (translation_error_sum, rotation_error_sum) = (0..num_pairs).fold(
+        (T::zero(), T::zero()),
+        |(translation_acc, rotation_acc), first_frame| {
+            let last_frame = first_frame + delta;
+            let ground_truth_delta = ground_truth[first_frame].inv_mul(&ground_truth[last_frame]);
+            let estimated_delta = estimated[first_frame].inv_mul(&estimated[last_frame]);
+            let pose_error = estimated_delta.inv_mul(&ground_truth_delta);
+            let rotation_error: T = rotation_angle(&pose_error.rotation);
+            (
+                translation_acc + pose_error.translation.vector.norm_squared(),
+                rotation_acc + rotation_error * rotation_error,
+            )
+        },
+    );
+
+    let num_pairs: T = num_pairs.as_();
+    Ok(RelativePoseError {
+        translation_error: (translation_error_sum / num_pairs).sqrt(),
+        rotation_error: (rotation_error_sum / num_pairs).sqrt(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use nalgebra::{
+        Isometry2, Isometry3, Point2, Point3, Translation3, UnitComplex, UnitQuaternion, Vector2,
+        Vector3,
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_absolute_trajectory_error() {
+        let ground_truth = [
+            Isometry2::new(Vector2::new(0.0, 0.0), 0.0),
+            Isometry2::new(Vector2::new(1.0, 0.0), 0.1),
+        ];
+        let estimated = [
+            Isometry2::new(Vector2::new(0.0, 3.0), 0.0),
+            Isometry2::new(Vector2::new(1.0, 0.0), -0.1),
+        ];
+        assert_eq!(
+            absolute_trajectory_error(&ground_truth, &ground_truth),
+            Ok(0.0)
+        );
+        assert_eq!(
+            absolute_trajectory_error(&ground_truth, &estimated),
+            Ok((4.5f64).sqrt())
+        );
+
+        assert_eq!(
+            absolute_trajectory_error(&ground_truth, &estimated[..1]),
+            Err(EvaluationError::MismatchedLengths {
+                ground_truth: 2,
+                estimated: 1
+            })
+        );
+        assert_eq!(
+            absolute_trajectory_error::<f64, UnitComplex<f64>, 2>(&[], &[]),
+            Err(EvaluationError::EmptyTrajectory)
+        );
+    }
+
+    fn generate_ground_truth() -> Vec<Isometry3<f64>> {
+        // A helix, so that the positions span all three dimensions
+        (0..40)
+            .map(|idx| {
+                let angle = idx as f64 * 0.2;
+                Isometry3::from_parts(
+                    Translation3::new(5.0 * angle.cos(), 5.0 * angle.sin(), idx as f64 * 0.3),
+                    UnitQuaternion::from_axis_angle(&Vector3::z_axis(), angle),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_umeyama_alignment() {
+        let source = [
+            Point2::<f64>::new(0.0, 0.0),
+            Point2::new(1.0, 0.0),
+            Point2::new(0.0, 2.0),
+            Point2::new(-1.0, 1.0),
+        ];
+        let transform = Isometry2::new(Vector2::new(3.0, -1.0), 0.7);
+        let target = source
+            .iter()
+            .map(|point| transform.transform_point(&(point * 2.5)))
+            .collect::<Vec<_>>();
+
+        let rigid = umeyama_alignment(&source, &target, false).unwrap();
+        assert_eq!(rigid.scaling(), 1.0);
+        assert!((rigid.isometry.rotation.angle() - 0.7).abs() < 1e-9);
+
+        let similarity = umeyama_alignment(&source, &target, true).unwrap();
+        assert!((similarity.scaling() - 2.5).abs() < 1e-9);
+        assert!((similarity.isometry.rotation.angle() - 0.7).abs() < 1e-9);
+        assert!((similarity.isometry.translation.vector - Vector2::new(3.0, -1.0)).norm() < 1e-9);
+
+        assert_eq!(
+            umeyama_alignment(
+                &[Point3::new(1.0, 2.0, 3.0); 3],
+                &[Point3::origin(); 3],
+                false
+            ),
+            Err(EvaluationError::DegenerateTrajectory)
+        );
+    }
+
+    #[test]
+    fn test_aligned_absolute_trajectory_error() {
+        let ground_truth = generate_ground_truth();
+        let offset = Isometry3::from_parts(
+            Translation3::new(-2.0, 7.0, 0.5),
+            UnitQuaternion::from_euler_angles(0.3, -0.2, 1.1),
+        );
+        let rigidly_moved = ground_truth
+            .iter()
+            .map(|pose| offset * pose)
+            .collect::<Vec<_>>();
+        let scaled = rigidly_moved
+            .iter()
+            .map(|pose| {
+                Isometry3::from_parts((pose.translation.vector * 0.5).into(), pose.rotation)
+            })
+            .collect::<Vec<_>>();
+
+        assert!(absolute_trajectory_error(&ground_truth, &rigidly_moved).unwrap() > 1.0);
+        assert!(
+            aligned_absolute_trajectory_error(
+                &ground_truth,
+                &rigidly_moved,
+                TrajectoryAlignment::Rigid
+            )
+            .unwrap()
+                < 1e-9
+        );
+        assert!(
+            aligned_absolute_trajectory_error(&ground_truth, &scaled, TrajectoryAlignment::Rigid)
+                .unwrap()
+                > 1.0
+        );
+        assert!(
+            aligned_absolute_trajectory_error(
+                &ground_truth,
+                &scaled,
+                TrajectoryAlignment::Similarity
+            )
+            .unwrap()
+                < 1e-9
+        );
+
+        // The aligned orientations should match the ground truth as well
+        let aligned =
+            align_trajectory(&ground_truth, &rigidly_moved, TrajectoryAlignment::Rigid).unwrap();
+        for (pose, ground_truth_pose) in aligned.iter().zip(ground_truth.iter()) {
+            assert!(pose.rotation.angle_to(&ground_truth_pose.rotation) < 1e-6);
+        }
+    }
+
+    #[test]
+    fn test_relative_pose_error() {
+        let ground_truth = generate_ground_truth();
+        // Relative motions are unaffected by a global offset
+        let offset = Isometry3::new(Vector3::new(1.0, 2.0, 3.0), Vector3::new(0.1, 0.2, 0.3));
+        let moved = ground_truth
+            .iter()
+            .map(|pose| offset * pose)
+            .collect::<Vec<_>>();
+        let error = relative_pose_error(&ground_truth, &moved, 1).unwrap();
+        assert!(error.translation_error < 1e-9);
+        assert!(error.rotation_error < 1e-6);
+
+        // Every motion in 2D overshoots its rotation by 0.05 radians, and its translation by one unit
+        let ground_truth = (0..10)
+            .map(|idx| Isometry2::new(Vector2::new(idx as f64, 0.0), 0.0))
+            .collect::<Vec<_>>();
+        let estimated = (0..10)
+            .scan(Isometry2::identity(), |pose, _| {
+                let current_pose = *pose;
+                *pose *= Isometry2::new(Vector2::new(2.0, 0.0), 0.05);
+                Some(current_pose)
+            })
+            .collect::<Vec<_>>();
+        let error = relative_pose_error(&ground_truth, &estimated, 1).unwrap();
+        assert!((error.translation_error - 1.0).abs() < 1e-9);
+        assert!((error.rotation_error - 0.05).abs() < 1e-6);
+
+        assert_eq!(
+            relative_pose_error(&ground_truth, &estimated, 10),
+            Err(EvaluationError::InvalidFrameDelta {
+                delta: 10,
+                num_poses: 10
+            })
+        );
+        assert_eq!(
+            relative_pose_error(&ground_truth, &estimated, 0),
+            Err(EvaluationError::InvalidFrameDelta {
+                delta: 0,
+                num_poses: 10
+            })
+        );
+    }
+}
@@ -0,0 +1,320 @@
+// SPDX-License-Identifier: MIT
+/*
+ * Copyright (c) [2023 - Present] Emily Matheys <emilymatt96@gmail.com>
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+use nalgebra::{Isometry3, RealField};
+
+use super::types::StampedPose;
+use crate::Vec;
+
+#[cfg(feature = "io")]
+use {
+    crate::io::{helpers::parse_value, types::IOError},
+    core::fmt::Display,
+    nalgebra::{Quaternion, Translation3, UnitQuaternion},
+    num_traits::AsPrimitive,
+    std::{
+        fs::File,
+        io::{BufRead, BufReader, Write},
+        path::Path,
+    },
+};
+
+/// Reads a TUM trajectory, where each line contains `timestamp tx ty tz qx qy qz qw`.
+/// Lines starting with `#` are treated as comments, and values may be separated by either whitespace or commas.
+///
+/// # Arguments
+/// * `reader`: a [`BufRead`], from which to read the trajectory.
+///
+/// # Generics
+/// * `T`: Either an [`f32`] or [`f64`].
+///
+/// # Returns
+/// A [`Vec`] of [`StampedPose`], one for each line, or an [`IOError`] if the trajectory could not be read.
+///
+/// # Warnings
+/// * The quaternion of each pose is normalized, to account for rounding in the file.
+#[cfg(feature = "io")]
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument("Read TUM Trajectory", skip_all, level = "info")
+)]
+pub fn read_trajectory<T, R>(reader: R) -> Result<Vec<StampedPose<T>>, IOError>
+where
+    T: Copy + RealField,
+    f64: AsPrimitive<T>,
+    R: BufRead,
+{
+    let mut poses = Vec::new();
+    for (line_idx, line) in reader.lines().enumerate() {
+        let line = line?;
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let values = line
+            .split(|character: char| character.is_whitespace() || character == ',')
+            .filter(|token| !token.is_empty())
+            .map(|token| parse_value(token, line_idx))
+            .collect::<Result<Vec<_>, _>>()?;
+        if values.len() != 8 {
+            return Err(IOError::InvalidData {
+                reason: format!(
+                    "line {} has {} values, expected 8",
+                    line_idx + 1,
+                    values.len()
+                ),
+            });
+        }
+
+        poses.push(StampedPose {
+            timestamp: values[0],
+            pose: Isometry3::from_parts(
+                Translation3::new(values[1].as_(), values[2].as_(), values[3].as_()),
+                UnitQuaternion::from_quaternion(Quaternion::new(
+                    values[7].as_(),
+                    values[4].as_(),
+                    values[5].as_(),
+                    values[6].as_(),
+                )),
+            ),
+        });
+    }
+
+    Ok(poses)
+}
+
+/// Reads a TUM trajectory file, see [`read_trajectory`].
+///
+/// # Arguments
+/// * `path`: the path of the trajectory file.
+///
+/// # Generics
+/// * `T`: Either an [`f32`] or [`f64`].
+///
+/// # Returns
+/// A [`Vec`] of [`StampedPose`], one for each line, or an [`IOError`] if the trajectory could not be read.
+#[cfg(feature = "io")]
+pub fn read_trajectory_file<T>(path: impl AsRef<Path>) -> Result<Vec<StampedPose<T>>, IOError>
+where
+    T: Copy + RealField,
+    f64: AsPrimitive<T>,
+{
+    read_trajectory(BufReader::new(File::open(path)?))
+}
+
+/// Writes a trajectory in the TUM format, so that it can be evaluated by the benchmark's tools.
+///
+/// # Arguments
+/// * `writer`: a [`Write`], to which to write the trajectory.
+/// * `poses`: a slice of [`StampedPose`].
+///
+/// # Generics
+/// * `T`: Either an [`f32`] or [`f64`].
+///
+/// # Returns
+/// An [`IOError`] if the trajectory could not be written.
+#[cfg(feature = "io")]
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument("Write TUM Trajectory", skip_all, level = "info")
+)]
+pub fn write_trajectory<T, W>(mut writer: W, poses: &[StampedPose<T>]) -> Result<(), IOError>
+where
+    T: Copy + Display + RealField,
+    W: Write,
+{
+    for stamped_pose in poses {
+        let translation = &stamped_pose.pose.translation.vector;
+        let rotation = &stamped_pose.pose.rotation.coords;
+        writeln!(
+            writer,
+            "{} {} {} {} {} {} {} {}",
+            stamped_pose.timestamp,
+            translation.x,
+            translation.y,
+            translation.z,
+            rotation.x,
+            rotation.y,
+            rotation.z,
+            rotation.w
+        )?;
+    }
+    Ok(())
+}
+
+/// Matches the poses of two trajectories by their timestamps, since trajectories recorded by different sources rarely share them.
+/// Candidate pairs are matched greedily, starting with the pair with the smallest time difference, and each pose is matched at most once.
+///
+/// # Arguments
+/// * `ground_truth`: a slice of [`StampedPose`], representing the ground truth trajectory.
+/// * `estimated`: a slice of [`StampedPose`], representing the estimated trajectory.
+/// * `max_time_difference`: the maximum difference between the timestamps of matched poses, in seconds.
+///
+/// # Generics
+/// * `T`: Either an [`f32`] or [`f64`].
+///
+/// # Returns
+/// A tuple of two [`Vec`]s of [`Isometry3`] with the same length, the matched ground truth and estimated poses,
+/// ordered by the timestamps of the estimated poses. These can be passed directly to the evaluation metrics.
+///
+/// # Warnings
+/// * `ground_truth` must be sorted by timestamp, as is the case for TUM trajectory files.
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument("Associate Trajectories", skip_all, level = "info")
+)]
+pub fn associate_trajectories<T>(
+    ground_truth: &[StampedPose<T>],
+    estimated: &[StampedPose<T>],
+    max_time_difference: f64,
+) -> (Vec<Isometry3<T>>, Vec<Isometry3<T>>)
+where
+    T: Copy + RealField,
+{
+    let mut candidates = Vec::new();
+    for (estimated_idx, estimated_pose) in estimated.iter().enumerate() {
+        let first_candidate = ground_truth.partition_point(|ground_truth_pose| {
+            ground_truth_pose.timestamp < estimated_pose.timestamp - max_time_difference
+        });
+        candidates.extend(
+            ground_truth[first_candidate..]
+                .iter()
+                .enumerate()
+                .map(|(idx, ground_truth_pose)| {
+                    (
+                        (ground_truth_pose.timestamp - estimated_pose.timestamp).abs(),
+                        first_candidate + idx,
+                        estimated_idx,
+                    )
+                })
+                .take_while(|(time_difference, _, _)| *time_difference <= max_time_difference),
+        );
+    }
+    candidates.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+    let mut ground_truth_matched = Vec::new();
+    ground_truth_matched.resize(ground_truth.len(), false);
+    let mut estimated_matches = Vec::new();
+    estimated_matches.resize(estimated.len(), None);
+    for (_, ground_truth_idx, estimated_idx) in candidates {
+        if ground_truth_matched[ground_truth_idx] || estimated_matches[estimated_idx].is_some() {
+            continue;
+        }
+        ground_truth_matched[ground_truth_idx] = true;
+        estimated_matches[estimated_idx] = Some(ground_truth_idx);
+    }
+
+    let mut estimated_order = (0..estimated.len()).collect::<Vec<_>>();
+    estimated_order.sort_by(|&a, &b| estimated[a].timestamp.total_cmp(&estimated[b].timestamp));
+    estimated_order
+        .into_iter()
+        .filter_map(|estimated_idx| {
+            estimated_matches[estimated_idx].map(|ground_truth_idx| {
+                (
+                    ground_truth[ground_truth_idx].pose,
+                    estimated[estimated_idx].pose,
+                )
+            })
+        })
+        .unzip()
+}
+
+#[cfg(test)]
+mod tests {
+    use nalgebra::{Translation3, UnitQuaternion, Vector3};
+
+    use super::*;
+
+    fn generate_trajectory(num_poses: usize, time_offset: f64) -> Vec<StampedPose<f64>> {
+        (0..num_poses)
+            .map(|idx| StampedPose {
+                timestamp: 1305031102.0 + idx as f64 * 0.1 + time_offset,
+                pose: Isometry3::from_parts(
+                    Translation3::new(idx as f64, 0.5 * idx as f64, 0.0),
+                    UnitQuaternion::from_axis_angle(&Vector3::z_axis(), idx as f64 * 0.1),
+                ),
+            })
+            .collect()
+    }
+
+    #[cfg(feature = "io")]
+    #[test]
+    fn test_read_trajectory() {
+        let contents = "# timestamp tx ty tz qx qy qz qw\n\
+                        1305031102.175304 1.3405 0.6266 1.6575 0.6574 0.6126 -0.2949 -0.3248\n\
+                        \n\
+                        1305031102.211214,1.3303,0.6256,1.6464,0.6579,0.6161,-0.2932,-0.3189\n";
+        let poses = read_trajectory::<f64, _>(std::io::Cursor::new(contents)).unwrap();
+        assert_eq!(poses.len(), 2);
+        assert_eq!(poses[0].timestamp, 1305031102.175304);
+        assert_eq!(
+            poses[1].pose.translation.vector,
+            Vector3::new(1.3303, 0.6256, 1.6464)
+        );
+        assert!((poses[0].pose.rotation.coords.norm() - 1.0).abs() < 1e-12);
+        assert!((poses[0].pose.rotation.coords.x - 0.6574).abs() < 1e-3);
+
+        let trajectory = generate_trajectory(5, 0.0);
+        let mut buffer = Vec::new();
+        write_trajectory(&mut buffer, &trajectory).unwrap();
+        let written_poses = read_trajectory::<f64, _>(std::io::Cursor::new(buffer)).unwrap();
+        for (pose, original_pose) in written_poses.iter().zip(trajectory.iter()) {
+            assert_eq!(pose.timestamp, original_pose.timestamp);
+            assert!(
+                (pose.pose.to_homogeneous() - original_pose.pose.to_homogeneous()).norm() < 1e-12
+            );
+        }
+
+        assert!(matches!(
+            read_trajectory::<f64, _>(std::io::Cursor::new("0.0 1 2 3 0 0 0\n")),
+            Err(IOError::InvalidData { .. })
+        ));
+    }
+
+    #[test]
+    fn test_associate_trajectories() {
+        let ground_truth = generate_trajectory(10, 0.0);
+        // Recorded slightly later, and missing every other pose
+        let estimated = generate_trajectory(10, 0.01)
+            .into_iter()
+            .step_by(2)
+            .collect::<Vec<_>>();
+
+        let (matched_ground_truth, matched_estimated) =
+            associate_trajectories(&ground_truth, &estimated, 0.02);
+        assert_eq!(matched_ground_truth.len(), 5);
+        assert_eq!(matched_estimated.len(), 5);
+        for (idx, (ground_truth_pose, estimated_pose)) in matched_ground_truth
+            .iter()
+            .zip(matched_estimated.iter())
+            .enumerate()
+        {
+            assert_eq!(*ground_truth_pose, ground_truth[idx * 2].pose);
+            assert_eq!(*ground_truth_pose, *estimated_pose);
+        }
+
+        let (matched_ground_truth, _) = associate_trajectories(&ground_truth, &estimated, 0.005);
+        assert!(matched_ground_truth.is_empty());
+    }
+}
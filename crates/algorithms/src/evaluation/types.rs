@@ -0,0 +1,123 @@
+// SPDX-License-Identifier: MIT
+/*
+ * Copyright (c) [2023 - Present] Emily Matheys <emilymatt96@gmail.com>
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+use core::fmt::{Display, Formatter};
+use nalgebra::{Isometry3, RealField};
+
+/// Describes why a trajectory could not be evaluated.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum EvaluationError {
+    /// The trajectories contain no poses.
+    EmptyTrajectory,
+    /// The ground truth and estimated trajectories do not contain the same amount of poses.
+    MismatchedLengths {
+        /// The amount of poses in the ground truth trajectory.
+        ground_truth: usize,
+        /// The amount of poses in the estimated trajectory.
+        estimated: usize,
+    },
+    /// The poses being aligned are all at the same position, so no alignment can be estimated from them.
+    DegenerateTrajectory,
+    /// The frame delta is either `0`, or too large for the trajectory to contain any pair of poses that far apart.
+    InvalidFrameDelta {
+        /// The requested frame delta.
+        delta: usize,
+        /// The amount of poses in the trajectory.
+        num_poses: usize,
+    },
+}
+
+impl Display for EvaluationError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::EmptyTrajectory => write!(f, "Trajectory is empty"),
+            Self::MismatchedLengths {
+                ground_truth,
+                estimated,
+            } => write!(
+                f,
+                "Ground truth has {ground_truth} poses, but the estimated trajectory has {estimated}"
+            ),
+            Self::DegenerateTrajectory => {
+                write!(f, "Trajectory poses are all at the same position")
+            }
+            Self::InvalidFrameDelta { delta, num_poses } => write!(
+                f,
+                "Frame delta of {delta} is invalid for a trajectory of {num_poses} poses"
+            ),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for EvaluationError {}
+
+/// The drift accumulated by an estimated trajectory over a single segment of the ground truth trajectory.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SegmentError<T> {
+    /// The index of the first pose of the segment.
+    pub first_frame: usize,
+    /// The length of the segment, as travelled along the ground truth trajectory.
+    pub segment_length: T,
+    /// The translation error at the end of the segment, divided by the segment's length.
+    pub translation_error: T,
+    /// The rotation error at the end of the segment in radians, divided by the segment's length.
+    pub rotation_error: T,
+}
+
+/// Describes how an estimated trajectory is aligned to the ground truth trajectory before it is evaluated.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum TrajectoryAlignment {
+    /// The trajectories are compared as-is, they must already be expressed in the same frame.
+    #[default]
+    None,
+    /// The estimated trajectory is rotated and translated to best fit the ground truth trajectory.
+    Rigid,
+    /// The estimated trajectory is rotated, translated and scaled to best fit the ground truth trajectory,
+    /// useful for trajectories with an unknown scale, such as those of monocular odometry.
+    Similarity,
+}
+
+/// The Relative Pose Error of an estimated trajectory, measuring its drift over a fixed amount of frames.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RelativePoseError<T> {
+    /// The Root Mean Squared translation error of the relative motions.
+    pub translation_error: T,
+    /// The Root Mean Squared rotation error of the relative motions, in radians.
+    pub rotation_error: T,
+}
+
+/// A pose along with the time at which it was recorded, as stored in a TUM trajectory file.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct StampedPose<T: RealField> {
+    /// The time at which the pose was recorded, in seconds.
+    /// This is always an [`f64`], as [`f32`] cannot represent Unix timestamps with enough precision.
+    pub timestamp: f64,
+    /// The pose itself.
+    pub pose: Isometry3<T>,
+}
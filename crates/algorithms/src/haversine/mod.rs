@@ -35,7 +35,7 @@ fn half_angle_sine_squared<T>(input: T) -> T
 /// Calculates the Haversine distance between two points on a sphere using floating-point arithmetic.
 ///
 /// # Arguments
-/// * `point_a`: A [`Point2'] representing the first geographical point.
+/// * `point_a`: A [`Point2`] representing the first geographical point.
 /// * `point_b`: A [`Point2`] representing the second geographical point.
 /// * `sphere_radius`: A `T` representing the radius of the sphere, typically the Earth's radius in kilometers or miles.
 ///
@@ -89,7 +89,7 @@ pub fn calculate_haversine_distance<T>(
 ///
 /// # Returns
 /// * A value that representing the initial bearing from `point_a` to `point_b`, in radians.
-/// The result is normalized to a range of 0 to 2 PI radians.
+///   The result is normalized to a range of 0 to 2 PI radians.
 #[cfg_attr(
     feature = "tracing",
     tracing::instrument("Calculate Bearing Between Points", skip_all)
@@ -121,7 +121,7 @@ pub mod [<$doc _precision>] {
                 #[doc = "Calculates the Haversine distance between two points on a sphere using " $doc "-precision floating-point arithmetic."]
                 #[doc = ""]
                 #[doc = "# Arguments"]
-                #[doc = "* `point_a`: A [`Point2'](nalgebra::Point2) representing the first geographical point."]
+                #[doc = "* `point_a`: A [`Point2`](nalgebra::Point2) representing the first geographical point."]
                 #[doc = "* `point_b`: A [`Point2`](nalgebra::Point2) representing the second geographical point."]
                 #[doc = "* `sphere_radius`: The radius of the sphere, typically the Earth's radius in kilometers or miles."]
                 #[doc = ""]
@@ -0,0 +1,398 @@
+// SPDX-License-Identifier: MIT
+/*
+ * Copyright (c) [2023 - Present] Emily Matheys <emilymatt96@gmail.com>
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+use crate::{
+    icp::{
+        helpers::{
+            calculate_correspondence_weights, calculate_mse, create_icp_success,
+            find_closest_point_indices, has_converged, validate_input,
+        },
+        types::{ICPConfiguration, ICPError, ICPSuccess},
+    },
+    kd_tree::KDTree,
+    types::{AbstractIsometry, IsometryAbstractor},
+    utils::{point_cloud::calculate_point_cloud_covariance, symmetric_eigen_decomposition},
+    Sum, Vec,
+};
+use nalgebra::{
+    Isometry, Isometry3, Matrix3, Matrix6, Point3, RealField, UnitQuaternion, Vector3, Vector6,
+};
+use num_traits::{AsPrimitive, Bounded};
+
+/// The amount of neighbouring points used to estimate the local covariance around each point.
+const COVARIANCE_ESTIMATION_NEIGHBOURS: usize = 20;
+
+/// The variance assigned to the direction of each point's surface normal, relative to the variance along the surface.
+const SURFACE_NORMAL_VARIANCE: f32 = 0.001;
+
+/// Estimates the local covariance around each point in the point cloud,
+/// regularized so that each point represents a small patch of a plane.
+///
+/// # Arguments
+/// * `points`: a slice of [`Point3`], representing the point cloud.
+/// * `num_neighbours`: the amount of neighbouring points to take into account for each point, including itself.
+///
+/// # Generics
+/// * `T`: Either an [`f32`] or [`f64`].
+///
+/// # Returns
+/// A [`Vec`] of [`Matrix3`], one for each point in `points`, with a variance of `1` along the surface,
+/// and a small variance along the surface normal.
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument("Estimate Plane Covariances", skip_all, level = "debug")
+)]
+fn estimate_plane_covariances<T>(points: &[Point3<T>], num_neighbours: usize) -> Vec<Matrix3<T>>
+where
+    T: Copy + Default + RealField,
+    f32: AsPrimitive<T>,
+    usize: AsPrimitive<T>,
+{
+    let kd_tree = KDTree::from(points);
+    points
+        .iter()
+        .map(|point| {
+            let neighbours = kd_tree
+                .k_nearest(point, num_neighbours)
+                .into_iter()
+                .map(|(neighbour, _)| neighbour)
+                .collect::<Vec<_>>();
+            let (_, eigenvectors) =
+                symmetric_eigen_decomposition(calculate_point_cloud_covariance(&neighbours));
+
+            // Eigenvalues are sorted in ascending order, so the first eigenvector is the surface normal
+            eigenvectors
+                * Matrix3::from_diagonal(&Vector3::new(
+                    SURFACE_NORMAL_VARIANCE.as_(),
+                    T::one(),
+                    T::one(),
+                ))
+                * eigenvectors.transpose()
+        })
+        .collect()
+}
+
+/// Calculates a new transform, by solving the linearized plane-to-plane system,
+/// where each residual is weighted by the inverse of the combined covariance of its two points.
+///
+/// # Arguments
+/// * `old_transform`: A reference to the current [`Isometry3`], which was used to transform the source points.
+/// * `transformed_points_a`: a slice of [`Point3`], representing the transformed source point cloud.
+/// * `covariances_a`: a slice of [`Matrix3`], representing the covariance of each point in the source point cloud.
+/// * `closest_points`: a slice of [`Point3`], representing the target nearest neighbour for each point in `transformed_points_a`.
+/// * `closest_covariances`: a slice of [`Matrix3`], representing the covariance of each point in `closest_points`.
+/// * `weights`: a slice of the weight of each correspondence, rejected correspondences have a weight of `0`.
+///
+/// # Generics
+/// * `T`: Either an [`f32`] or [`f64`].
+///
+/// # Returns
+/// The updated [`Isometry3`], or [`None`] if the system could not be solved, meaning the geometry does not constrain every degree of freedom.
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument("Update Plane-To-Plane Transform", skip_all, level = "debug")
+)]
+fn update_transform_plane_to_plane<T>(
+    old_transform: &Isometry3<T>,
+    transformed_points_a: &[Point3<T>],
+    covariances_a: &[Matrix3<T>],
+    closest_points: &[Point3<T>],
+    closest_covariances: &[Matrix3<T>],
+    weights: &[T],
+) -> Option<Isometry3<T>>
+where
+    T: Copy + RealField,
+{
+    let rotation = old_transform.rotation.to_rotation_matrix();
+
+    let (hessian, gradient) = transformed_points_a
+        .iter()
+        .zip(covariances_a.iter())
+        .zip(closest_points.iter().zip(closest_covariances.iter()))
+        .zip(weights.iter())
+        .filter(|(_, weight)| **weight > T::zero())
+        .filter_map(
+            |(
+                ((transformed_point_a, covariance_a), (closest_point, closest_covariance)),
+                weight,
+            )| {
+                // The source covariance must be rotated into the target's frame of reference
+                let mahalanobis = (closest_covariance
+                    + rotation.matrix() * covariance_a * rotation.matrix().transpose())
+                .try_inverse()?;
+                Some((transformed_point_a, closest_point, mahalanobis * *weight))
+            },
+        )
+        .fold(
+            (Matrix6::zeros(), Vector6::zeros()),
+            |(hessian, gradient), (transformed_point_a, closest_point, mahalanobis)| {
+                let (point_hessian, point_gradient) = IsometryAbstractor::<T, 3>::normal_equations(
+                    transformed_point_a,
+                    &mahalanobis,
+                    &(mahalanobis * (transformed_point_a - closest_point)),
+                );
+                (hessian + point_hessian, gradient + point_gradient)
+            },
+        );
+
+    let increment = IsometryAbstractor::<T, 3>::solve_increment(&hessian, &gradient)?;
+    Some(IsometryAbstractor::<T, 3>::apply_increment(
+        old_transform,
+        &increment,
+    ))
+}
+
+/// A Generalized-ICP function, implementing the plane-to-plane formulation in 3D space.
+/// Each point in both point clouds is assigned a local covariance, estimated from its nearest neighbours,
+/// and the distance between each pair of matched points is weighted by the inverse of their combined covariances.
+/// This makes the algorithm robust to differences in sampling between the two point clouds.
+///
+/// # Arguments
+/// * `points_a`: A slice of [`Point3`], representing the source point cloud.
+/// * `points_b`: A slice of [`Point3`], representing the target point cloud.
+/// * `config`: a reference to an [`ICPConfiguration`], specifying the behaviour of the algorithm.
+///
+/// # Generics
+/// * `T`: Either [`prim@f32`] or [`prim@f64`]
+///
+/// # Returns
+/// An [`ICPSuccess`] struct with an [`Isometry3`] transform with a `T` precision, or an [`ICPError`] explaining what went wrong.
+///
+/// [^convergence_note]: This does not guarantee that the transformation is correct, only that no further benefit can be gained by running another iteration.
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument("Full GICP Algorithm", skip_all, level = "info")
+)]
+pub fn gicp<T>(
+    points_a: &[Point3<T>],
+    points_b: &[Point3<T>],
+    config: ICPConfiguration<T>,
+) -> Result<ICPSuccess<T, UnitQuaternion<T>, 3>, ICPError<T, UnitQuaternion<T>, 3>>
+where
+    T: Bounded + Copy + Default + RealField + Sum,
+    f32: AsPrimitive<T>,
+    usize: AsPrimitive<T>,
+{
+    validate_input(points_a, points_b, &config)?;
+
+    let covariances_a = estimate_plane_covariances(points_a, COVARIANCE_ESTIMATION_NEIGHBOURS);
+    let covariances_b = estimate_plane_covariances(points_b, COVARIANCE_ESTIMATION_NEIGHBOURS);
+    log::trace!("Estimated point covariances");
+
+    let target_points_tree = config.use_kd_tree.then(|| KDTree::from_indexed(points_b));
+    let mut transformed_points = points_a.to_vec();
+    let mut current_transform = Isometry::identity();
+    let mut current_mse = <T as Bounded>::max_value();
+
+    for iteration_num in 0..config.max_iterations {
+        log::trace!(
+            "Running iteration number {iteration_num}/{}",
+            config.max_iterations
+        );
+
+        let (closest_points, closest_covariances): (Vec<_>, Vec<_>) = find_closest_point_indices(
+            &transformed_points,
+            points_b,
+            target_points_tree.as_ref(),
+            config.search_mode,
+        )
+        .into_iter()
+        .map(|closest_idx| (points_b[closest_idx], covariances_b[closest_idx]))
+        .unzip();
+        log::trace!("Found nearest neighbours");
+
+        let weights =
+            calculate_correspondence_weights(&transformed_points, &closest_points, &config);
+        if weights.iter().all(|weight| *weight <= T::zero()) {
+            return Err(ICPError::NoCorrespondences);
+        }
+        current_transform = update_transform_plane_to_plane(
+            &current_transform,
+            &transformed_points,
+            &covariances_a,
+            &closest_points,
+            &closest_covariances,
+            &weights,
+        )
+        .ok_or(ICPError::DegenerateGeometry)?;
+
+        for (transformed_point, point_a) in transformed_points.iter_mut().zip(points_a.iter()) {
+            *transformed_point = current_transform.transform_point(point_a);
+        }
+        let new_mse = calculate_mse(&transformed_points, &closest_points, &weights);
+        log::trace!("New MSE: {new_mse}");
+
+        // If the MSE difference is lower than the threshold, then this is as good as it gets
+        if has_converged(current_mse, new_mse, &config) {
+            log::trace!("Converged after {iteration_num} iterations with an MSE of {new_mse}");
+            // Each distance was weighted by the inverse of the combined covariances of its correspondence
+            let rotation = current_transform.rotation.to_rotation_matrix();
+            let residual_weights = covariances_a
+                .iter()
+                .zip(closest_covariances.iter())
+                .map(|(covariance_a, closest_covariance)| {
+                    (closest_covariance
+                        + rotation.matrix() * covariance_a * rotation.matrix().transpose())
+                    .try_inverse()
+                    .unwrap_or_else(Matrix3::zeros)
+                })
+                .collect::<Vec<_>>();
+            return Ok(create_icp_success(
+                points_a,
+                &transformed_points,
+                &closest_points,
+                &residual_weights,
+                current_transform,
+                new_mse,
+                iteration_num,
+                &config,
+            ));
+        }
+
+        current_mse = new_mse;
+    }
+
+    Err(ICPError::DidNotConverge {
+        last_transform: current_transform,
+        last_mse: current_mse,
+        iterations: config.max_iterations,
+    })
+}
+
+#[cfg(feature = "pregenerated")]
+macro_rules! impl_gicp_algorithm {
+    ($precision:expr, doc $doc:tt) => {
+        ::paste::paste! {
+            #[doc = "A " $doc "-precision implementation of a Generalized-ICP algorithm"]
+            pub mod [<$doc _precision>] {
+                use nalgebra::{Point3, UnitQuaternion};
+                use crate::icp::types::{ICPConfiguration, ICPError, ICPSuccess};
+
+                #[doc = "A Generalized-ICP algorithm in 3D space."]
+                #[doc = "# Arguments"]
+                #[doc = "* `points_a`: A slice of [`Point3`], representing the source point cloud."]
+                #[doc = "* `points_b`: A slice of [`Point3`], representing the target point cloud."]
+                #[doc = "* `config`: a reference to an [`ICPConfiguration`], specifying the behaviour of the algorithm."]
+                #[doc = ""]
+                #[doc = "# Returns"]
+                #[doc = "An [`ICPSuccess`] struct with an [`Isometry3`](nalgebra::Isometry3) transform with an `" $precision "` precision, or an [`ICPError`] explaining what went wrong."]
+                #[doc = ""]
+                #[doc = "[^convergence_note]: This does not guarantee that the transformation is correct, only that no further benefit can be gained by running another iteration."]
+                pub fn gicp_3d(points_a: &[Point3<$precision>],
+                    points_b: &[Point3<$precision>],
+                    config: ICPConfiguration<$precision>) -> Result<ICPSuccess<$precision, UnitQuaternion<$precision>, 3>, ICPError<$precision, UnitQuaternion<$precision>, 3>> {
+                        super::gicp(points_a, points_b, config)
+                }
+            }
+        }
+    };
+}
+
+#[cfg(feature = "pregenerated")]
+impl_gicp_algorithm!(f32, doc single);
+#[cfg(feature = "pregenerated")]
+impl_gicp_algorithm!(f64, doc double);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        array,
+        utils::point_cloud::{generate_corner_3d, generate_point_cloud, transform_point_cloud},
+    };
+
+    #[test]
+    fn test_estimate_plane_covariances() {
+        let covariances = estimate_plane_covariances(&generate_corner_3d(12, 0.5), 9);
+
+        // A point in the middle of the floor should have almost no variance along the Z axis
+        let floor_covariance = covariances[3 * (6 * 12 + 6)];
+        assert!((floor_covariance.m33 - 0.001).abs() < 1e-6);
+        assert!((floor_covariance.m11 - 1.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_gicp_errors() {
+        let points = generate_point_cloud(10, array::from_fn(|_| -15.0..=15.0));
+        let config_builder = ICPConfiguration::builder();
+
+        let res = single_precision::gicp_3d(&[], &points, config_builder.build());
+        assert_eq!(res.unwrap_err(), ICPError::EmptySource);
+
+        let res = single_precision::gicp_3d(&points, &[], config_builder.build());
+        assert_eq!(res.unwrap_err(), ICPError::EmptyTarget);
+    }
+
+    #[test]
+    fn test_gicp_3d() {
+        let points = generate_corner_3d(12, 0.5);
+        let isom = Isometry3::new(
+            Vector3::new(0.2, -0.15, 0.1),
+            Vector3::new(-0.03, 0.02, 0.05),
+        );
+        let points_transformed = transform_point_cloud(&points, isom);
+
+        let res = double_precision::gicp_3d(
+            &points,
+            &points_transformed,
+            ICPConfiguration::builder()
+                .with_max_iterations(30)
+                .with_mse_interval_threshold(0.0001)
+                .build(),
+        );
+        assert!(res.is_ok());
+        let res = res.unwrap();
+        assert!(res.mse < 0.01);
+        assert!((res.transform.translation.vector - isom.translation.vector).norm() < 0.01);
+    }
+
+    #[test]
+    fn test_gicp_3d_with_kd_tree() {
+        let points = generate_corner_3d(12, 0.5);
+        let isom = Isometry3::new(
+            Vector3::new(0.2, -0.15, 0.1),
+            Vector3::new(-0.03, 0.02, 0.05),
+        );
+        let points_transformed = transform_point_cloud(&points, isom);
+
+        let config_builder = ICPConfiguration::builder()
+            .with_max_iterations(30)
+            .with_mse_interval_threshold(0.0001);
+        let exhaustive_res =
+            double_precision::gicp_3d(&points, &points_transformed, config_builder.build())
+                .unwrap();
+        let kd_tree_res = double_precision::gicp_3d(
+            &points,
+            &points_transformed,
+            config_builder.with_kd_tree(true).build(),
+        )
+        .unwrap();
+        assert_eq!(kd_tree_res.iteration_num, exhaustive_res.iteration_num);
+        assert!(
+            (kd_tree_res.transform.translation.vector
+                - exhaustive_res.transform.translation.vector)
+                .norm()
+                < 1e-9
+        );
+    }
+}
@@ -23,27 +23,333 @@
 
 use crate::{
     array,
-    types::SameSizeMat,
-    utils::{distance_squared, point_cloud::calculate_point_cloud_center},
-    Sum,
+    icp::types::{ICPConfiguration, ICPError, ICPSuccess},
+    kd_tree::{KDTree, SearchMode},
+    types::{AbstractIsometry, IsometryAbstractor, MaybeSend, MaybeSync, SameSizeMat},
+    utils::{
+        distance_squared,
+        point_cloud::{find_closest_point, find_closest_point_index},
+    },
+    Sum, Vec,
 };
+use core::cmp::Ordering;
 use nalgebra::{
-    ArrayStorage, ClosedAdd, ClosedDiv, ClosedSub, Const, Matrix, Point, Scalar, Vector,
+    AbstractRotation, ArrayStorage, ClosedAdd, ClosedDiv, ClosedMul, ClosedSub, Const, Isometry,
+    Matrix, Point, RealField, SVector, Scalar, Vector,
 };
-use num_traits::{AsPrimitive, NumOps, Zero};
+use num_traits::{AsPrimitive, Bounded, NumOps, Zero};
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+
+/// Verifies that the input point clouds and configuration allow for an ICP variant to run.
+///
+/// # Arguments
+/// * `points_a`: A slice of [`Point`], representing the source point cloud.
+/// * `points_b`: A slice of [`Point`], representing the target point cloud.
+/// * `config`: a reference to an [`ICPConfiguration`], specifying the behaviour of the algorithm.
+///
+/// # Generics
+/// * `T`: Either an [`f32`] or [`f64`].
+/// * `R`: The rotation type of the algorithm's resulting transform.
+/// * `N`: A const usize, specifying the amount of dimensions in the points.
+///
+/// # Returns
+/// An empty [`Ok`] if the algorithm can run, or an [`ICPError`] explaining what went wrong.
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument("Validate ICP Input", skip_all, level = "debug")
+)]
+pub(crate) fn validate_input<T, R, const N: usize>(
+    points_a: &[Point<T, N>],
+    points_b: &[Point<T, N>],
+    config: &ICPConfiguration<T>,
+) -> Result<(), ICPError<T, R, N>>
+where
+    T: Copy + RealField,
+    R: AbstractRotation<T, N>,
+{
+    if points_a.is_empty() {
+        return Err(ICPError::EmptySource);
+    }
+
+    if points_b.is_empty() {
+        return Err(ICPError::EmptyTarget);
+    }
+
+    if config.max_iterations == 0 {
+        return Err(ICPError::InvalidConfiguration {
+            reason: "Must have more than one iteration",
+        });
+    }
+
+    if config.mse_interval_threshold <= T::default_epsilon() {
+        return Err(ICPError::InvalidConfiguration {
+            reason: "MSE interval threshold too low, convergence impossible",
+        });
+    }
+
+    if config
+        .mse_absolute_threshold
+        .map(|thres| thres <= T::default_epsilon())
+        .unwrap_or_default()
+    {
+        return Err(ICPError::InvalidConfiguration {
+            reason: "Absolute MSE threshold too low, convergence impossible",
+        });
+    }
+
+    if config
+        .max_correspondence_distance
+        .map(|distance| distance <= T::zero())
+        .unwrap_or_default()
+    {
+        return Err(ICPError::InvalidConfiguration {
+            reason: "Max correspondence distance must be positive",
+        });
+    }
+
+    if config
+        .trim_ratio
+        .map(|ratio| ratio <= T::zero() || ratio > T::one())
+        .unwrap_or_default()
+    {
+        return Err(ICPError::InvalidConfiguration {
+            reason: "Trim ratio must be larger than 0 and no larger than 1",
+        });
+    }
+
+    if !config.search_mode.is_valid() {
+        return Err(ICPError::InvalidConfiguration {
+            reason:
+                "Search mode must have a non-negative epsilon or a positive amount of leaf checks",
+        });
+    }
+
+    if config
+        .robust_kernel
+        .map(|kernel| kernel.scale() <= T::zero())
+        .unwrap_or_default()
+    {
+        return Err(ICPError::InvalidConfiguration {
+            reason: "Robust kernel scale must be positive",
+        });
+    }
+
+    Ok(())
+}
+
+/// Finds the nearest neighbour in the target point cloud, for each point in the transformed source point cloud.
+///
+/// # Arguments
+/// * `transformed_points_a`: a slice of [`Point`], representing the source point cloud, transformed by the current [`Isometry`].
+/// * `points_b`: a slice of [`Point`], representing the target point cloud.
+/// * `target_points_tree`: an optional [`KDTree`] of `points_b`, when [`None`], an exhaustive search is performed instead.
+/// * `search_mode`: a [`SearchMode`], specifying how thoroughly `target_points_tree` is searched.
+///
+/// # Generics
+/// * `T`: Either an [`f32`] or [`f64`].
+/// * `N`: A const usize, specifying the amount of dimensions in the points.
+///
+/// # Returns
+/// A [`Vec`] of [`Point`], containing the nearest neighbour of each point in `transformed_points_a`.
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument("Find Closest Points", skip_all, level = "debug")
+)]
+pub(crate) fn find_closest_points<T, const N: usize>(
+    transformed_points_a: &[Point<T, N>],
+    points_b: &[Point<T, N>],
+    target_points_tree: Option<&KDTree<T, N>>,
+    search_mode: SearchMode<T>,
+) -> Vec<Point<T, N>>
+where
+    T: Bounded + Copy + Default + RealField,
+{
+    maybe_par_iter!(transformed_points_a)
+        .map(|transformed_point_a| {
+            target_points_tree
+                .and_then(|kd_tree| kd_tree.search_nearest(transformed_point_a, search_mode))
+                .map(|(closest_point, _)| closest_point)
+                .unwrap_or_else(|| find_closest_point(transformed_point_a, points_b))
+        })
+        .collect()
+}
+
+/// Finds the index of the nearest neighbour in the target point cloud, for each point in the transformed source point cloud.
+/// This is used by ICP variants which keep additional per-point data for the target point cloud, such as normals or covariances.
+///
+/// # Arguments
+/// * `transformed_points_a`: a slice of [`Point`], representing the source point cloud, transformed by the current [`Isometry`].
+/// * `points_b`: a slice of [`Point`], representing the target point cloud.
+/// * `target_points_tree`: an optional [`KDTree`] of `points_b`, indexed with [`KDTree::from_indexed`], when [`None`], an exhaustive search is performed instead.
+/// * `search_mode`: a [`SearchMode`], specifying how thoroughly `target_points_tree` is searched.
+///
+/// # Generics
+/// * `T`: Either an [`f32`] or [`f64`].
+/// * `N`: A const usize, specifying the amount of dimensions in the points.
+///
+/// # Returns
+/// A [`Vec`] of indices into `points_b`, of the nearest neighbour of each point in `transformed_points_a`.
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument("Find Closest Point Indices", skip_all, level = "debug")
+)]
+pub(crate) fn find_closest_point_indices<T, const N: usize>(
+    transformed_points_a: &[Point<T, N>],
+    points_b: &[Point<T, N>],
+    target_points_tree: Option<&KDTree<T, N, usize>>,
+    search_mode: SearchMode<T>,
+) -> Vec<usize>
+where
+    T: Bounded + Copy + Default + RealField,
+{
+    maybe_par_iter!(transformed_points_a)
+        .map(|transformed_point_a| {
+            target_points_tree
+                .and_then(|kd_tree| kd_tree.search_nearest(transformed_point_a, search_mode))
+                .map(|(_, closest_idx)| *closest_idx)
+                .unwrap_or_else(|| find_closest_point_index(transformed_point_a, points_b))
+        })
+        .collect()
+}
+
+/// Calculates the weight of each correspondence, according to the rejection and robust kernel options in the configuration.
+///
+/// # Arguments
+/// * `transformed_points_a`: a slice of [`Point`], representing the source point cloud, transformed by the current [`Isometry`](nalgebra::Isometry) matrix.
+/// * `closest_points`: a slice of [`Point`], representing the target nearest neighbour for each point in `transformed_points_a`.
+/// * `config`: a reference to an [`ICPConfiguration`], specifying the behaviour of the algorithm.
+///
+/// # Generics
+/// * `T`: Either an [`f32`] or [`f64`].
+/// * `N`: A const usize, specifying the amount of dimensions in the points.
+///
+/// # Returns
+/// A [`Vec`] of weights, one for each correspondence, where rejected correspondences are assigned a weight of `0`.
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument("Calculate Correspondence Weights", skip_all, level = "debug")
+)]
+pub(crate) fn calculate_correspondence_weights<T, const N: usize>(
+    transformed_points_a: &[Point<T, N>],
+    closest_points: &[Point<T, N>],
+    config: &ICPConfiguration<T>,
+) -> Vec<T>
+where
+    T: Bounded + Copy + Default + RealField,
+    usize: AsPrimitive<T>,
+{
+    let distances = transformed_points_a
+        .iter()
+        .zip(closest_points.iter())
+        .map(|(transformed_point_a, closest_point)| {
+            distance_squared(transformed_point_a, closest_point).sqrt()
+        })
+        .collect::<Vec<_>>();
+
+    let mut is_inlier = distances
+        .iter()
+        .map(|distance| {
+            config
+                .max_correspondence_distance
+                .map(|max_distance| *distance <= max_distance)
+                .unwrap_or(true)
+        })
+        .collect::<Vec<_>>();
+
+    if config.reciprocal_correspondences {
+        let source_points_tree = config
+            .use_kd_tree
+            .then(|| KDTree::from(transformed_points_a));
+        for ((inlier, transformed_point_a), closest_point) in is_inlier
+            .iter_mut()
+            .zip(transformed_points_a.iter())
+            .zip(closest_points.iter())
+            .filter(|((inlier, _), _)| **inlier)
+        {
+            let reciprocal_point = source_points_tree
+                .as_ref()
+                .and_then(|kd_tree| kd_tree.nearest(closest_point))
+                .unwrap_or_else(|| find_closest_point(closest_point, transformed_points_a));
+            *inlier = reciprocal_point == *transformed_point_a;
+        }
+    }
+
+    if let Some(trim_ratio) = config.trim_ratio {
+        let mut inlier_distances = distances
+            .iter()
+            .zip(is_inlier.iter())
+            .filter_map(|(distance, inlier)| inlier.then_some(*distance))
+            .collect::<Vec<_>>();
+        inlier_distances.sort_unstable_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+
+        // Keep the smallest distances, up to the requested fraction of correspondences
+        let max_inliers = trim_ratio * inlier_distances.len().as_();
+        if let Some(max_distance) = inlier_distances
+            .iter()
+            .enumerate()
+            .take_while(|(idx, _)| idx.as_() < max_inliers)
+            .last()
+            .map(|(_, distance)| *distance)
+        {
+            is_inlier
+                .iter_mut()
+                .zip(distances.iter())
+                .for_each(|(inlier, distance)| *inlier = *inlier && *distance <= max_distance);
+        }
+    }
+
+    distances
+        .into_iter()
+        .zip(is_inlier)
+        .map(|(distance, inlier)| match (inlier, config.robust_kernel) {
+            (false, _) => T::zero(),
+            (true, Some(kernel)) => kernel.weight(distance),
+            (true, None) => T::one(),
+        })
+        .collect()
+}
+
+/// Checks whether the latest iteration of an ICP variant has converged, according to the configuration thresholds.
+///
+/// # Arguments
+/// * `previous_mse`: The MSE of the previous iteration.
+/// * `new_mse`: The MSE of the current iteration.
+/// * `config`: a reference to an [`ICPConfiguration`], specifying the behaviour of the algorithm.
+///
+/// # Generics
+/// * `T`: Either an [`f32`] or [`f64`].
+///
+/// # Returns
+/// `true` if the absolute MSE threshold was reached, or if the MSE difference is lower than the interval threshold.
+#[inline]
+pub(crate) fn has_converged<T>(previous_mse: T, new_mse: T, config: &ICPConfiguration<T>) -> bool
+where
+    T: Copy + RealField,
+{
+    config
+        .mse_absolute_threshold
+        .map(|thres| new_mse < thres)
+        .unwrap_or_default()
+        || (previous_mse - new_mse).abs() < config.mse_interval_threshold
+}
 
 /// Calculates the Mean Squared Error between two point clouds.
 ///
 /// # Arguments
 /// * `transformed_points_a`: a slice of [`Point`], representing the source point cloud, transformed by the current [`Isometry`](nalgebra::Isometry) matrix.
 /// * `points_b`: a slice of [`Point`], representing the point cloud to match against.
+/// * `weights`: a slice of the weight of each correspondence, correspondences with a weight of `0` are considered rejected.
 ///
 /// # Generics
 /// * `T`: Either an [`f32`] or [`f64`].
 /// * `N`: A const usize, specifying the amount of dimensions in the points.
 ///
 /// # Returns
-/// A [`T`], representing the sum of all squared distances between each point in `transformed_points_a` and its corresponding point in `points_b`.
+/// A [`T`], representing the sum of all squared distances between each point in `transformed_points_a` and its corresponding point in `points_b`,
+/// taking only correspondences that were not rejected into account.
+/// Each squared distance is weighted by its correspondence's weight, divided by the mean weight of the accepted correspondences,
+/// so that uniform weights result in the plain sum, while correspondences down-weighted by a robust kernel contribute less.
 #[inline]
 #[cfg_attr(
     feature = "tracing",
@@ -52,17 +358,33 @@
 pub(crate) fn calculate_mse<T, const N: usize>(
     transformed_points_a: &[Point<T, N>],
     closest_points_in_b: &[Point<T, N>],
+    weights: &[T],
 ) -> T
 where
-    T: Copy + Default + NumOps + Scalar + Sum,
+    T: Copy + Default + MaybeSend + MaybeSync + NumOps + PartialOrd + Scalar + Sum,
+    usize: AsPrimitive<T>,
 {
-    transformed_points_a
-        .iter()
-        .zip(closest_points_in_b.iter())
-        .map(|(transformed_a, closest_point_in_b)| {
-            distance_squared(transformed_a, closest_point_in_b)
+    let num_inliers = maybe_par_iter!(weights)
+        .filter(|weight| **weight > T::default())
+        .count();
+    if num_inliers == 0 {
+        return T::default();
+    }
+
+    let weight_sum = maybe_par_iter!(weights)
+        .filter(|weight| **weight > T::default())
+        .copied()
+        .sum::<T>();
+    let weighted_sum = maybe_par_iter!(transformed_points_a)
+        .zip(maybe_par_iter!(closest_points_in_b))
+        .zip(maybe_par_iter!(weights))
+        .filter(|(_, weight)| **weight > T::default())
+        .map(|((transformed_a, closest_point_in_b), weight)| {
+            distance_squared(transformed_a, closest_point_in_b) * *weight
         })
-        .sum()
+        .sum::<T>();
+
+    weighted_sum * num_inliers.as_() / weight_sum
 }
 
 /// Calculates the outer product of two `N` length [`Vector`]s.
@@ -94,24 +416,50 @@ pub(crate) fn outer_product<T, const N: usize>(
     })))
 }
 
+/// Calculates the weighted centeroid of a point cloud.
+///
+/// # Arguments
+/// * `points`: a slice of [`Point`], representing the point cloud.
+/// * `weights`: a slice of the weight of each point.
+/// * `total_weight`: the sum of `weights`.
+///
+/// # Generics
+/// * `T`: Either an [`f32`] or [`f64`].
+/// * `N`: A const usize, specifying the amount of dimensions in the points.
+///
+/// # Returns
+/// A [`Point`], representing the weighted centeroid.
+#[inline]
+fn calculate_weighted_center<T, const N: usize>(
+    points: &[Point<T, N>],
+    weights: &[T],
+    total_weight: T,
+) -> Point<T, N>
+where
+    T: ClosedAdd + ClosedDiv + ClosedMul + Copy + NumOps + Scalar + Zero,
+{
+    points.iter().zip(weights.iter()).fold(
+        Point::<T, N>::from([T::zero(); N]),
+        |acc, (point, weight)| Point::from(acc.coords + point.coords * *weight),
+    ) / total_weight
+}
+
 /// Calculates the estimated transformation matrix between the two point clouds.
 ///
 /// # Arguments
 /// * `points_a`: a slice of [`Point`], representing the source point cloud.
 /// * `closest_points`: a slice of [`Point`], representing the target nearest neighbour for each point in `points_a`.
+/// * `weights`: a slice of the weight of each correspondence, see [`calculate_correspondence_weights`].
 ///
 /// # Generics
 /// * `T`: Either an [`f32`] or [`f64`].
 /// * `N`: A const usize, specifying the amount of dimensions in the points.
 ///
 /// # Returns
-/// A tuple of
-/// * [`SameSizeMat`], representing the covariance matrix of the outer products of the centered point clouds.
-/// * [`Point`], representing the `points_a` centeroid.
-/// * [`Point`], representing the `closest_points` centeroid.
-///
-/// # Panics
-/// See [`calculate_mean`]
+/// [`None`] if all correspondences were rejected, otherwise a tuple of
+/// * [`SameSizeMat`], representing the weighted covariance matrix of the outer products of the centered point clouds.
+/// * [`Point`], representing the weighted `points_a` centeroid.
+/// * [`Point`], representing the weighted `closest_points` centeroid.
 #[inline]
 #[cfg_attr(
     feature = "tracing",
@@ -120,36 +468,169 @@ pub(crate) fn outer_product<T, const N: usize>(
 pub(crate) fn get_rotation_matrix_and_centeroids<T, const N: usize>(
     transformed_points_a: &[Point<T, N>],
     closest_points: &[Point<T, N>],
-) -> (SameSizeMat<T, N>, Point<T, N>, Point<T, N>)
+    weights: &[T],
+) -> Option<(SameSizeMat<T, N>, Point<T, N>, Point<T, N>)>
 where
-    T: ClosedAdd + ClosedDiv + ClosedSub + Copy + NumOps + Scalar + Zero,
-    usize: AsPrimitive<T>,
+    T: ClosedAdd + ClosedDiv + ClosedMul + ClosedSub + Copy + NumOps + PartialOrd + Scalar + Zero,
 {
+    let total_weight = weights.iter().fold(T::zero(), |acc, weight| acc + *weight);
+    if total_weight <= T::zero() {
+        return None;
+    }
+
     let (mean_transformed_a, mean_closest) = (
-        calculate_point_cloud_center(transformed_points_a),
-        calculate_point_cloud_center(closest_points),
+        calculate_weighted_center(transformed_points_a, weights, total_weight),
+        calculate_weighted_center(closest_points, weights, total_weight),
     );
 
-    let rot_mat = transformed_points_a.iter().zip(closest_points.iter()).fold(
-        Matrix::from_array_storage(ArrayStorage([[T::zero(); N]; N])),
-        |rot_mat, (transformed_point_a, closest_point)| {
-            let a_distance_from_centeroid = transformed_point_a - mean_transformed_a;
-            let closest_point_distance_from_centeroid = closest_point - mean_closest;
-            rot_mat
-                + outer_product(
-                    &a_distance_from_centeroid,
-                    &closest_point_distance_from_centeroid,
-                )
-        },
-    );
+    let rot_mat = transformed_points_a
+        .iter()
+        .zip(closest_points.iter())
+        .zip(weights.iter())
+        .fold(
+            Matrix::from_array_storage(ArrayStorage([[T::zero(); N]; N])),
+            |rot_mat, ((transformed_point_a, closest_point), weight)| {
+                let a_distance_from_centeroid = transformed_point_a - mean_transformed_a;
+                let closest_point_distance_from_centeroid = closest_point - mean_closest;
+                rot_mat
+                    + outer_product(
+                        &(a_distance_from_centeroid * *weight),
+                        &closest_point_distance_from_centeroid,
+                    )
+            },
+        );
+
+    Some((rot_mat, mean_transformed_a, mean_closest))
+}
+
+/// Calculates the unscaled information matrix of a transform, from the correspondences it was estimated with.
+/// This is the sum of `J^T * W * J` over every correspondence, where `J` is the jacobian of the transformed source point
+/// with respect to a small pose increment, see [`AbstractIsometry::normal_equations`], and `W` is the matrix weighting its residual.
+///
+/// # Arguments
+/// * `transformed_points_a`: a slice of [`Point`], representing the source point cloud, transformed by the resulting [`Isometry`].
+/// * `residual_weights`: a slice of the matrix weighting the residual of each correspondence in the minimized cost,
+///   such as the identity for point-to-point distances or `n * n^T` for point-to-plane distances.
+/// * `weights`: a slice of the weight of each correspondence, see [`calculate_correspondence_weights`].
+///
+/// # Generics
+/// * `T`: Either an [`f32`] or [`f64`].
+/// * `N`: A const usize, either `2` or `3`.
+///
+/// # Returns
+/// An [`IncrementMatrix`](AbstractIsometry::IncrementMatrix), with a row and a column for each degree of freedom of the transform.
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument("Calculate Information Matrix", skip_all, level = "debug")
+)]
+pub(crate) fn calculate_information_matrix<T, const N: usize>(
+    transformed_points_a: &[Point<T, N>],
+    residual_weights: &[SameSizeMat<T, N>],
+    weights: &[T],
+) -> <IsometryAbstractor<T, N> as AbstractIsometry<T, N>>::IncrementMatrix
+where
+    T: Copy + RealField,
+    IsometryAbstractor<T, N>: AbstractIsometry<T, N>,
+{
+    transformed_points_a
+        .iter()
+        .zip(residual_weights.iter())
+        .zip(weights.iter())
+        .filter(|(_, weight)| **weight > T::zero())
+        .fold(
+            Zero::zero(),
+            |information_matrix, ((transformed_point_a, residual_weight), weight)| {
+                let (point_information, _) = IsometryAbstractor::<T, N>::normal_equations(
+                    transformed_point_a,
+                    &(residual_weight * *weight),
+                    &SVector::zeros(),
+                );
+                information_matrix + point_information
+            },
+        )
+}
+
+/// Constructs an [`ICPSuccess`] from the resulting transform, and the correspondences found under it.
+///
+/// # Arguments
+/// * `points_a`: a slice of [`Point`], representing the source point cloud.
+/// * `transformed_points_a`: a slice of [`Point`], representing the source point cloud, transformed by `transform`.
+/// * `closest_points`: a slice of [`Point`], representing the target nearest neighbour for each point in `transformed_points_a`.
+/// * `residual_weights`: a slice of the matrix weighting the residual of each correspondence in the minimized cost, see [`calculate_information_matrix`].
+/// * `transform`: the resulting [`Isometry`] of the algorithm.
+/// * `mse`: the resulting Mean Squared Error of the algorithm.
+/// * `iteration_num`: the amount of iterations passed until convergence.
+/// * `config`: a reference to an [`ICPConfiguration`], specifying the behaviour of the algorithm.
+///
+/// # Generics
+/// * `T`: Either an [`f32`] or [`f64`].
+/// * `N`: A const usize, either `2` or `3`.
+///
+/// # Returns
+/// An [`ICPSuccess`], containing the transform along with metrics describing its quality.
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument("Create ICP Success", skip_all, level = "debug")
+)]
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn create_icp_success<T, const N: usize>(
+    points_a: &[Point<T, N>],
+    transformed_points_a: &[Point<T, N>],
+    closest_points: &[Point<T, N>],
+    residual_weights: &[SameSizeMat<T, N>],
+    transform: Isometry<T, <IsometryAbstractor<T, N> as AbstractIsometry<T, N>>::RotType, N>,
+    mse: T,
+    iteration_num: usize,
+    config: &ICPConfiguration<T>,
+) -> ICPSuccess<T, <IsometryAbstractor<T, N> as AbstractIsometry<T, N>>::RotType, N>
+where
+    T: Bounded + Copy + Default + RealField + Sum,
+    usize: AsPrimitive<T>,
+    IsometryAbstractor<T, N>: AbstractIsometry<T, N>,
+{
+    let weights = calculate_correspondence_weights(transformed_points_a, closest_points, config);
+    let correspondences = points_a
+        .iter()
+        .zip(closest_points.iter())
+        .zip(weights.iter())
+        .filter(|(_, weight)| **weight > T::zero())
+        .map(|((point_a, closest_point), _)| (*point_a, *closest_point))
+        .collect::<Vec<_>>();
+
+    let inlier_rmse = if correspondences.is_empty() {
+        T::zero()
+    } else {
+        (calculate_mse(transformed_points_a, closest_points, &weights)
+            / correspondences.len().as_())
+        .sqrt()
+    };
+
+    // Assume isotropic noise, whose per-axis variance is estimated from the inlier residuals.
+    let noise_variance = inlier_rmse * inlier_rmse / N.as_();
+    let information_matrix =
+        calculate_information_matrix(transformed_points_a, residual_weights, &weights);
+    let information_matrix = if noise_variance > T::zero() {
+        information_matrix * noise_variance.recip()
+    } else {
+        information_matrix
+    };
 
-    (rot_mat, mean_transformed_a, mean_closest)
+    ICPSuccess {
+        transform,
+        mse,
+        iteration_num,
+        fitness: correspondences.len().as_() / points_a.len().as_(),
+        inlier_rmse,
+        correspondences,
+        information_matrix,
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use nalgebra::{Point3, Vector3};
+    use crate::{icp::types::RobustKernel, utils::point_cloud::calculate_point_cloud_center};
+    use nalgebra::{Matrix2, Matrix3, Point3, Vector2, Vector3};
 
     #[test]
     fn test_calculate_mean() {
@@ -185,12 +666,167 @@ fn test_calculate_mse() {
         ];
 
         // Calculate MSE
-        let mse = calculate_mse(&transformed_points_a, &points_b);
+        let mse = calculate_mse(&transformed_points_a, &points_b, &[1.0; 3]);
 
         assert_eq!(
             mse, 13.0,
             "The calculated MSE does not match the expected value."
         );
+
+        // Rejected correspondences are not taken into account
+        let mse = calculate_mse(&transformed_points_a, &points_b, &[1.0, 0.5, 0.0]);
+        assert_eq!(
+            mse, 10.0,
+            "The calculated MSE does not match the expected value."
+        );
+
+        // Down-weighted correspondences contribute less, relative to the mean weight
+        let mse = calculate_mse(&transformed_points_a, &points_b, &[0.75, 0.0, 0.25]);
+        assert_eq!(
+            mse, 9.0,
+            "The calculated MSE does not match the expected value."
+        );
+    }
+
+    #[test]
+    fn test_calculate_correspondence_weights() {
+        let transformed_points_a: [Point<f64, 2>; 4] = [
+            Point::from([0.0, 0.0]),
+            Point::from([1.0, 0.0]),
+            Point::from([2.0, 0.0]),
+            Point::from([3.0, 0.0]),
+        ];
+        let closest_points: [Point<f64, 2>; 4] = [
+            Point::from([0.0, 0.1]),
+            Point::from([1.0, 0.4]),
+            Point::from([2.0, 2.0]),
+            Point::from([2.0, 0.1]),
+        ];
+        let config_builder = ICPConfiguration::builder();
+
+        let weights = calculate_correspondence_weights(
+            &transformed_points_a,
+            &closest_points,
+            &config_builder.build(),
+        );
+        assert_eq!(weights, Vec::from([1.0; 4]));
+
+        let weights = calculate_correspondence_weights(
+            &transformed_points_a,
+            &closest_points,
+            &config_builder
+                .with_max_correspondence_distance(Some(1.5))
+                .build(),
+        );
+        assert_eq!(weights, Vec::from([1.0, 1.0, 0.0, 1.0]));
+
+        let weights = calculate_correspondence_weights(
+            &transformed_points_a,
+            &closest_points,
+            &config_builder.with_trim_ratio(Some(0.5)).build(),
+        );
+        assert_eq!(weights, Vec::from([1.0, 1.0, 0.0, 0.0]));
+
+        // The last target point is closer to the third source point than to the fourth
+        let weights = calculate_correspondence_weights(
+            &transformed_points_a,
+            &closest_points,
+            &config_builder.with_reciprocal_correspondences(true).build(),
+        );
+        assert_eq!(weights, Vec::from([1.0, 1.0, 1.0, 0.0]));
+
+        let weights = calculate_correspondence_weights(
+            &transformed_points_a,
+            &closest_points,
+            &config_builder
+                .with_robust_kernel(Some(RobustKernel::Cauchy(0.2)))
+                .with_max_correspondence_distance(Some(1.5))
+                .build(),
+        );
+        assert_eq!(weights[2], 0.0);
+        assert!(weights[0] > weights[1] && weights[1] > weights[3]);
+    }
+
+    #[test]
+    fn test_calculate_information_matrix() {
+        let transformed_points_a: [Point<f64, 2>; 3] = [
+            Point::from([1.0, 0.0]),
+            Point::from([0.0, 1.0]),
+            Point::from([5.0, 5.0]),
+        ];
+
+        // The last correspondence was rejected, and so has no effect
+        let information_matrix = calculate_information_matrix(
+            &transformed_points_a,
+            &[Matrix2::identity(); 3],
+            &[1.0, 1.0, 0.0],
+        );
+        assert_eq!(
+            information_matrix,
+            Matrix3::new(2.0, -1.0, 1.0, -1.0, 2.0, 0.0, 1.0, 0.0, 2.0)
+        );
+
+        // Only the distance along the normal of each correspondence is constrained
+        let normal = Vector2::new(0.0, 1.0);
+        let information_matrix = calculate_information_matrix(
+            &transformed_points_a,
+            &[normal * normal.transpose(); 3],
+            &[1.0, 1.0, 0.0],
+        );
+        assert_eq!(
+            information_matrix,
+            Matrix3::new(1.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 0.0, 2.0)
+        );
+    }
+
+    #[test]
+    fn test_create_icp_success() {
+        let points_a: [Point<f64, 2>; 4] = [
+            Point::from([0.0, 0.0]),
+            Point::from([1.0, 0.0]),
+            Point::from([2.0, 0.0]),
+            Point::from([3.0, 0.0]),
+        ];
+        let closest_points: [Point<f64, 2>; 4] = [
+            Point::from([0.0, 0.3]),
+            Point::from([1.0, 0.4]),
+            Point::from([2.0, 5.0]),
+            Point::from([3.0, 6.0]),
+        ];
+
+        let res = create_icp_success(
+            &points_a,
+            &points_a,
+            &closest_points,
+            &[Matrix2::identity(); 4],
+            Isometry::identity(),
+            0.25,
+            3,
+            &ICPConfiguration::builder()
+                .with_max_correspondence_distance(Some(1.0))
+                .build(),
+        );
+        assert_eq!(res.fitness, 0.5);
+        assert!((res.inlier_rmse - (0.125f64).sqrt()).abs() < 1e-12);
+        assert_eq!(
+            res.correspondences,
+            Vec::from([
+                (points_a[0], closest_points[0]),
+                (points_a[1], closest_points[1])
+            ])
+        );
+        // Per-axis noise variance is 0.125 / 2, so the information matrix is scaled by 16.
+        let unscaled_information_matrix = calculate_information_matrix(
+            &points_a,
+            &[Matrix2::identity(); 4],
+            &[1.0, 1.0, 0.0, 0.0],
+        );
+        assert!(
+            (res.information_matrix - unscaled_information_matrix * 16.0)
+                .abs()
+                .max()
+                < 1e-9
+        );
     }
 
     #[test]
@@ -228,7 +864,8 @@ fn test_get_rotation_matrix_and_centeroids() {
         ];
 
         // Compute transform using centroids
-        let (rot_mat, mean_a, mean_b) = get_rotation_matrix_and_centeroids(&points_a, &points_b);
+        let (rot_mat, mean_a, mean_b) =
+            get_rotation_matrix_and_centeroids(&points_a, &points_b, &[1.0; 3]).unwrap();
         assert_eq!(
             mean_a,
             Point3::new(37.0, 28.0, 11.0),
@@ -248,5 +885,14 @@ fn test_get_rotation_matrix_and_centeroids() {
             ])),
             "The calculated rotation matrix does not match the expected value."
         );
+
+        // When every correspondence was rejected, nothing can be estimated
+        assert!(get_rotation_matrix_and_centeroids(&points_a, &points_b, &[0.0; 3]).is_none());
+
+        // Zero weighted correspondences do not affect the centeroids
+        let (_, mean_a, mean_b) =
+            get_rotation_matrix_and_centeroids(&points_a, &points_b, &[1.0, 0.0, 0.0]).unwrap();
+        assert_eq!(mean_a, points_a[0]);
+        assert_eq!(mean_b, points_b[0]);
     }
 }
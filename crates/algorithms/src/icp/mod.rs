@@ -23,17 +23,33 @@
 
 use crate::{
     kd_tree::KDTree,
-    types::{AbstractIsometry, IsometryAbstractor},
-    utils::point_cloud::find_closest_point,
+    types::{AbstractIsometry, IsometryAbstractor, SameSizeMat},
+    utils::point_cloud::transform_point_cloud,
     Sum, Vec,
 };
-use helpers::{calculate_mse, get_rotation_matrix_and_centeroids};
-use nalgebra::{ComplexField, Isometry, Point, RealField, SimdRealField};
+use helpers::{
+    calculate_correspondence_weights, calculate_mse, create_icp_success, find_closest_points,
+    get_rotation_matrix_and_centeroids, has_converged, validate_input,
+};
+use nalgebra::{Isometry, Point, RealField, SimdRealField};
 use num_traits::{AsPrimitive, Bounded};
-use types::{ICPConfiguration, ICPSuccess};
+use types::{
+    ICPConfiguration, ICPError, ICPIterationResult, ICPIterationState, ICPIterationStatus,
+    ICPObserver, ICPObserverAction, ICPResult,
+};
+
+/// A Generalized-ICP variant, matching the local surface around each point in both point clouds.
+pub mod gicp;
 
 mod helpers;
 
+/// A point-to-plane variant of the ICP algorithm, minimizing the distance between each source point and the surface around its match.
+/// In 2D space, this is a point-to-line variant.
+pub mod point_to_plane;
+
+/// A multi-resolution variant of the ICP algorithm, running coarse-to-fine on downsampled versions of the point clouds.
+pub mod pyramid;
+
 /// Structs in use as part of the public API of the ICP algorithm.
 pub mod types;
 
@@ -54,7 +70,8 @@
 /// * `N`: a usize, either `2` or `3`.
 ///
 /// # Returns
-/// An [`ICPSuccess`] struct with an [`Isometry`] transform with a `T` precision, or an error message explaining what went wrong.
+/// An [`ICPIterationStatus`], specifying whether the algorithm has converged[^convergence_note], along with the latest MSE,
+/// or an [`ICPError::NoCorrespondences`] if every correspondence was rejected, in which case the transform is not updated.
 ///
 /// [^convergence_note]: This does not guarantee that the transformation is correct, only that no further benefit can be gained by running another iteration.
 #[cfg_attr(
@@ -73,24 +90,26 @@ pub fn icp_iteration<T, const N: usize>(
     >,
     current_mse: &mut T,
     config: &ICPConfiguration<T>,
-) -> Result<T, (Point<T, N>, Point<T, N>)>
+) -> ICPIterationResult<T, N>
 where
     T: Bounded + Copy + Default + RealField + Sum + SimdRealField,
     usize: AsPrimitive<T>,
     IsometryAbstractor<T, N>: AbstractIsometry<T, N>,
 {
-    let closest_points = transformed_points
-        .iter()
-        .map(|transformed_point_a| {
-            target_points_tree
-                .and_then(|kd_tree| kd_tree.nearest(transformed_point_a))
-                .unwrap_or(find_closest_point(transformed_point_a, points_b))
-        })
-        .collect::<Vec<_>>();
+    let closest_points = find_closest_points(
+        transformed_points,
+        points_b,
+        target_points_tree,
+        config.search_mode,
+    );
     log::trace!("Found nearest neighbours");
 
-    let (rot_mat, mean_a, mean_b) =
-        get_rotation_matrix_and_centeroids(transformed_points, &closest_points);
+    let weights = calculate_correspondence_weights(transformed_points, &closest_points, config);
+    let Some((rot_mat, mean_a, mean_b)) =
+        get_rotation_matrix_and_centeroids(transformed_points, &closest_points, &weights)
+    else {
+        return Err(ICPError::NoCorrespondences);
+    };
     log::trace!("Generated covariance matrix");
 
     *current_transform =
@@ -99,21 +118,24 @@ pub fn icp_iteration<T, const N: usize>(
     for (idx, point_a) in points_a.iter().enumerate() {
         transformed_points[idx] = current_transform.transform_point(point_a);
     }
-    let new_mse = calculate_mse(transformed_points, closest_points.as_slice());
+    let new_mse = calculate_mse(transformed_points, closest_points.as_slice(), &weights);
     log::trace!("New MSE: {new_mse}");
 
     // If the MSE difference is lower than the threshold, then this is as good as it gets
-    if config
-        .mse_absolute_threshold
-        .map(|thres| new_mse < thres)
-        .unwrap_or_default()
-        || <T as ComplexField>::abs(*current_mse - new_mse) < config.mse_interval_threshold
-    {
-        return Ok(new_mse);
+    if has_converged(*current_mse, new_mse, config) {
+        return Ok(ICPIterationStatus::Converged {
+            mse: new_mse,
+            closest_points,
+        });
     }
 
     *current_mse = new_mse;
-    Err((mean_a, mean_b))
+    Ok(ICPIterationStatus::NotConverged {
+        mse: new_mse,
+        closest_points,
+        source_centeroid: mean_a,
+        target_centeroid: mean_b,
+    })
 }
 
 /// A free-form version of the ICP function, allowing for any input and output, under the constraints of the function
@@ -129,7 +151,7 @@ pub fn icp_iteration<T, const N: usize>(
 /// * `N`: a usize, either `2` or `3`
 ///
 /// # Returns
-/// An [`ICPSuccess`] struct with an [`Isometry`] transform with a `T` precision, or an error message explaining what went wrong.
+/// An [`ICPSuccess`](types::ICPSuccess) struct with an [`Isometry`] transform with a `T` precision, or an [`ICPError`] explaining what went wrong.
 ///
 /// [^convergence_note]: This does not guarantee that the transformation is correct, only that no further benefit can be gained by running another iteration.
 #[cfg_attr(
@@ -140,42 +162,102 @@ pub fn icp<T, const N: usize>(
     points_a: &[Point<T, N>],
     points_b: &[Point<T, N>],
     config: ICPConfiguration<T>,
-) -> Result<
-    ICPSuccess<T, <IsometryAbstractor<T, N> as AbstractIsometry<T, N>>::RotType, N>,
-    &'static str,
->
+) -> ICPResult<T, N>
 where
     T: Bounded + Copy + Default + RealField + Sum,
     usize: AsPrimitive<T>,
     IsometryAbstractor<T, N>: AbstractIsometry<T, N>,
 {
-    if points_a.is_empty() {
-        return Err("Source point cloud is empty");
-    }
-
-    if points_b.is_empty() {
-        return Err("Target point cloud is empty");
-    }
-
-    if config.max_iterations == 0 {
-        return Err("Must have more than one iteration");
-    }
+    icp_with_initial_guess(points_a, points_b, Isometry::identity(), config)
+}
 
-    if config.mse_interval_threshold <= T::default_epsilon() {
-        return Err("MSE interval threshold too low, convergence impossible");
-    }
+/// A free-form version of the ICP function, starting from a provided transform rather than the identity transform.
+/// This is useful for seeding the algorithm with an estimate from another source, such as wheel odometry or a previous match,
+/// which allows it to converge even when the motion between the two point clouds is large.
+///
+/// # Arguments
+/// * `points_a`: A slice of [`Point`], representing the source point cloud.
+/// * `points_b`: A slice of [`Point`], representing the target point cloud.
+/// * `initial_guess`: An [`Isometry`], representing the estimated transform between `points_a` and `points_b`.
+/// * `config`: a reference to an [`ICPConfiguration`], specifying the behaviour of the algorithm.
+///
+/// # Generics
+/// * `T`: Either [`prim@f32`] or [`prim@f64`]
+/// * `N`: a usize, either `2` or `3`
+///
+/// # Returns
+/// An [`ICPSuccess`](types::ICPSuccess) struct with an [`Isometry`] transform with a `T` precision, or an [`ICPError`] explaining what went wrong.
+/// Note that the resulting transform includes `initial_guess`, and is not relative to it.
+///
+/// [^convergence_note]: This does not guarantee that the transformation is correct, only that no further benefit can be gained by running another iteration.
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument("Full ICP Algorithm With Initial Guess", skip_all, level = "info")
+)]
+pub fn icp_with_initial_guess<T, const N: usize>(
+    points_a: &[Point<T, N>],
+    points_b: &[Point<T, N>],
+    initial_guess: Isometry<T, <IsometryAbstractor<T, N> as AbstractIsometry<T, N>>::RotType, N>,
+    config: ICPConfiguration<T>,
+) -> ICPResult<T, N>
+where
+    T: Bounded + Copy + Default + RealField + Sum,
+    usize: AsPrimitive<T>,
+    IsometryAbstractor<T, N>: AbstractIsometry<T, N>,
+{
+    icp_with_observer(
+        points_a,
+        points_b,
+        initial_guess,
+        config,
+        &mut |_: &ICPIterationState<_, _, N>| ICPObserverAction::Continue,
+    )
+}
 
-    if config
-        .mse_absolute_threshold
-        .map(|thres| thres <= T::default_epsilon())
-        .unwrap_or_default()
-    {
-        return Err("Absolute MSE threshold too low, convergence impossible");
-    }
+/// A free-form version of the ICP function, starting from a provided transform,
+/// and calling the provided [`ICPObserver`] after every iteration.
+/// This allows watching the progress of the algorithm, for example to drive a visualizer or a logger,
+/// and to stop it early, for example when a time budget is exceeded.
+///
+/// # Arguments
+/// * `points_a`: A slice of [`Point`], representing the source point cloud.
+/// * `points_b`: A slice of [`Point`], representing the target point cloud.
+/// * `initial_guess`: An [`Isometry`], representing the estimated transform between `points_a` and `points_b`.
+/// * `config`: a reference to an [`ICPConfiguration`], specifying the behaviour of the algorithm.
+/// * `observer`: A mutable reference to an [`ICPObserver`], such as a closure, which is called after every iteration.
+///
+/// # Generics
+/// * `T`: Either [`prim@f32`] or [`prim@f64`]
+/// * `O`: An [`ICPObserver`] of `T` and `N`, this may also be a trait object.
+/// * `N`: a usize, either `2` or `3`
+///
+/// # Returns
+/// An [`ICPSuccess`](types::ICPSuccess) struct with an [`Isometry`] transform with a `T` precision, or an [`ICPError`] explaining what went wrong.
+/// If the observer stops the algorithm before convergence, an [`ICPError::Stopped`] is returned, containing the last transform.
+///
+/// [^convergence_note]: This does not guarantee that the transformation is correct, only that no further benefit can be gained by running another iteration.
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument("Full ICP Algorithm With Observer", skip_all, level = "info")
+)]
+pub fn icp_with_observer<T, O, const N: usize>(
+    points_a: &[Point<T, N>],
+    points_b: &[Point<T, N>],
+    initial_guess: Isometry<T, <IsometryAbstractor<T, N> as AbstractIsometry<T, N>>::RotType, N>,
+    config: ICPConfiguration<T>,
+    observer: &mut O,
+) -> ICPResult<T, N>
+where
+    T: Bounded + Copy + Default + RealField + Sum,
+    O: ICPObserver<T, <IsometryAbstractor<T, N> as AbstractIsometry<T, N>>::RotType, N> + ?Sized,
+    usize: AsPrimitive<T>,
+    IsometryAbstractor<T, N>: AbstractIsometry<T, N>,
+{
+    validate_input(points_a, points_b, &config)?;
 
-    let mut points_to_transform = points_a.to_vec();
+    let mut points_to_transform = transform_point_cloud(points_a, initial_guess);
     let target_points_tree = config.use_kd_tree.then_some(KDTree::from(points_b));
-    let mut current_transform = Isometry::identity();
+    let mut current_transform = initial_guess;
     let mut current_mse = <T as Bounded>::max_value();
 
     for iteration_num in 0..config.max_iterations {
@@ -183,7 +265,7 @@ pub fn icp<T, const N: usize>(
             "Running iteration number {iteration_num}/{}",
             config.max_iterations
         );
-        if let Ok(mse) = icp_iteration::<T, N>(
+        let status = icp_iteration::<T, N>(
             points_a,
             &mut points_to_transform,
             points_b,
@@ -191,17 +273,47 @@ pub fn icp<T, const N: usize>(
             &mut current_transform,
             &mut current_mse,
             &config,
-        ) {
+        )?;
+        let action = observer.on_iteration(&ICPIterationState {
+            iteration_num,
+            transform: &current_transform,
+            mse: status.mse(),
+            source_points: points_a,
+            closest_points: status.closest_points(),
+        });
+
+        if status.has_converged() {
+            let mse = status.mse();
             log::trace!("Converged after {iteration_num} iterations with an MSE of {mse}");
-            return Ok(ICPSuccess {
-                transform: current_transform,
+            return Ok(create_icp_success(
+                points_a,
+                &points_to_transform,
+                status.closest_points(),
+                &core::iter::repeat(SameSizeMat::identity())
+                    .take(points_a.len())
+                    .collect::<Vec<_>>(),
+                current_transform,
                 mse,
                 iteration_num,
+                &config,
+            ));
+        }
+
+        if action == ICPObserverAction::Stop {
+            log::trace!("Stopped by observer after {iteration_num} iterations");
+            return Err(ICPError::Stopped {
+                last_transform: current_transform,
+                last_mse: status.mse(),
+                iterations: iteration_num + 1,
             });
         }
     }
 
-    Err("Could not converge")
+    Err(ICPError::DidNotConverge {
+        last_transform: current_transform,
+        last_mse: current_mse,
+        iterations: config.max_iterations,
+    })
 }
 
 #[cfg(feature = "pregenerated")]
@@ -215,14 +327,52 @@ macro_rules! impl_icp_algorithm {
             #[doc = "* `config`: a reference to an [`ICPConfiguration`], specifying the behaviour of the algorithm."]
             #[doc = ""]
             #[doc = "# Returns"]
-            #[doc = "An [`ICPSuccess`] struct with an [`Isometry`](nalgebra::Isometry) transform with an `" $precision "` precision, or an error message explaining what went wrong."]
+            #[doc = "An [`ICPSuccess`] struct with an [`Isometry`](nalgebra::Isometry) transform with an `" $precision "` precision, or an [`ICPError`] explaining what went wrong."]
             #[doc = ""]
             #[doc = "[^convergence_note]: This does not guarantee that the transformation is correct, only that no further benefit can be gained by running another iteration."]
             pub fn [<icp_$nd d>](points_a: &[Point<$precision, $nd>],
                 points_b: &[Point<$precision, $nd>],
-                config: ICPConfiguration<$precision>) -> Result<ICPSuccess<$precision, $rot_type<$precision>, $nd>, &'static str> {
+                config: ICPConfiguration<$precision>) -> Result<ICPSuccess<$precision, $rot_type<$precision>, $nd>, ICPError<$precision, $rot_type<$precision>, $nd>> {
                     super::icp(points_a, points_b, config)
             }
+
+            #[doc = "An ICP algorithm in " $nd "D space, starting from a provided transform rather than the identity transform."]
+            #[doc = "# Arguments"]
+            #[doc = "* `points_a`: A slice of [`Point`], representing the source point cloud."]
+            #[doc = "* `points_b`: A slice of [`Point`], representing the target point cloud."]
+            #[doc = "* `initial_guess`: An [`Isometry`], representing the estimated transform between `points_a` and `points_b`."]
+            #[doc = "* `config`: a reference to an [`ICPConfiguration`], specifying the behaviour of the algorithm."]
+            #[doc = ""]
+            #[doc = "# Returns"]
+            #[doc = "An [`ICPSuccess`] struct with an [`Isometry`] transform with an `" $precision "` precision, or an [`ICPError`] explaining what went wrong."]
+            #[doc = ""]
+            #[doc = "[^convergence_note]: This does not guarantee that the transformation is correct, only that no further benefit can be gained by running another iteration."]
+            pub fn [<icp_$nd d_with_initial_guess>](points_a: &[Point<$precision, $nd>],
+                points_b: &[Point<$precision, $nd>],
+                initial_guess: Isometry<$precision, $rot_type<$precision>, $nd>,
+                config: ICPConfiguration<$precision>) -> Result<ICPSuccess<$precision, $rot_type<$precision>, $nd>, ICPError<$precision, $rot_type<$precision>, $nd>> {
+                    super::icp_with_initial_guess(points_a, points_b, initial_guess, config)
+            }
+
+            #[doc = "An ICP algorithm in " $nd "D space, starting from a provided transform, and calling the provided observer after every iteration."]
+            #[doc = "# Arguments"]
+            #[doc = "* `points_a`: A slice of [`Point`], representing the source point cloud."]
+            #[doc = "* `points_b`: A slice of [`Point`], representing the target point cloud."]
+            #[doc = "* `initial_guess`: An [`Isometry`], representing the estimated transform between `points_a` and `points_b`."]
+            #[doc = "* `config`: a reference to an [`ICPConfiguration`], specifying the behaviour of the algorithm."]
+            #[doc = "* `observer`: A mutable reference to an [`ICPObserver`], such as a closure, which is called after every iteration."]
+            #[doc = ""]
+            #[doc = "# Returns"]
+            #[doc = "An [`ICPSuccess`] struct with an [`Isometry`] transform with an `" $precision "` precision, or an [`ICPError`] explaining what went wrong."]
+            #[doc = ""]
+            #[doc = "[^convergence_note]: This does not guarantee that the transformation is correct, only that no further benefit can be gained by running another iteration."]
+            pub fn [<icp_$nd d_with_observer>](points_a: &[Point<$precision, $nd>],
+                points_b: &[Point<$precision, $nd>],
+                initial_guess: Isometry<$precision, $rot_type<$precision>, $nd>,
+                config: ICPConfiguration<$precision>,
+                observer: &mut dyn ICPObserver<$precision, $rot_type<$precision>, $nd>) -> Result<ICPSuccess<$precision, $rot_type<$precision>, $nd>, ICPError<$precision, $rot_type<$precision>, $nd>> {
+                    super::icp_with_observer(points_a, points_b, initial_guess, config, observer)
+            }
         }
     };
 
@@ -230,8 +380,8 @@ pub fn [<icp_$nd d>](points_a: &[Point<$precision, $nd>],
         ::paste::paste! {
             #[doc = "A " $doc "-precision implementation of a basic ICP algorithm"]
             pub mod [<$doc _precision>] {
-                use nalgebra::{Point, UnitComplex, UnitQuaternion};
-                use super::{ICPConfiguration, ICPSuccess};
+                use nalgebra::{Isometry, Point, UnitComplex, UnitQuaternion};
+                use super::{types::ICPSuccess, ICPConfiguration, ICPError, ICPObserver};
 
                 impl_icp_algorithm!($precision, $doc, 2, UnitComplex);
                 impl_icp_algorithm!($precision, $doc, 3, UnitQuaternion);
@@ -250,9 +400,12 @@ mod tests {
     use super::*;
     use crate::{
         array,
+        icp::types::RobustKernel,
+        kd_tree::SearchMode,
         utils::point_cloud::{generate_point_cloud, transform_point_cloud},
+        Vec,
     };
-    use nalgebra::{Isometry2, Isometry3, Vector2, Vector3};
+    use nalgebra::{Isometry2, Isometry3, UnitComplex, Vector2, Vector3};
 
     #[test]
     fn test_icp_errors() {
@@ -260,17 +413,22 @@ fn test_icp_errors() {
         let config_builder = ICPConfiguration::builder();
 
         let res = single_precision::icp_2d(&[], points.as_slice(), config_builder.build());
-        assert_eq!(res.unwrap_err(), "Source point cloud is empty");
+        assert_eq!(res.unwrap_err(), ICPError::EmptySource);
 
         let res = single_precision::icp_2d(points.as_slice(), &[], config_builder.build());
-        assert_eq!(res.unwrap_err(), "Target point cloud is empty");
+        assert_eq!(res.unwrap_err(), ICPError::EmptyTarget);
 
         let res = single_precision::icp_2d(
             points.as_slice(),
             points.as_slice(),
             config_builder.with_max_iterations(0).build(),
         );
-        assert_eq!(res.unwrap_err(), "Must have more than one iteration");
+        assert_eq!(
+            res.unwrap_err(),
+            ICPError::InvalidConfiguration {
+                reason: "Must have more than one iteration"
+            }
+        );
 
         let res = single_precision::icp_2d(
             points.as_slice(),
@@ -279,7 +437,9 @@ fn test_icp_errors() {
         );
         assert_eq!(
             res.unwrap_err(),
-            "MSE interval threshold too low, convergence impossible"
+            ICPError::InvalidConfiguration {
+                reason: "MSE interval threshold too low, convergence impossible"
+            }
         );
 
         let res = single_precision::icp_2d(
@@ -291,8 +451,127 @@ fn test_icp_errors() {
         );
         assert_eq!(
             res.unwrap_err(),
-            "Absolute MSE threshold too low, convergence impossible"
+            ICPError::InvalidConfiguration {
+                reason: "Absolute MSE threshold too low, convergence impossible"
+            }
+        );
+
+        let res = single_precision::icp_2d(
+            points.as_slice(),
+            points.as_slice(),
+            config_builder
+                .with_max_correspondence_distance(Some(0.0))
+                .build(),
         );
+        assert_eq!(
+            res.unwrap_err(),
+            ICPError::InvalidConfiguration {
+                reason: "Max correspondence distance must be positive"
+            }
+        );
+
+        let res = single_precision::icp_2d(
+            points.as_slice(),
+            points.as_slice(),
+            config_builder.with_trim_ratio(Some(1.5)).build(),
+        );
+        assert_eq!(
+            res.unwrap_err(),
+            ICPError::InvalidConfiguration {
+                reason: "Trim ratio must be larger than 0 and no larger than 1"
+            }
+        );
+
+        let res = single_precision::icp_2d(
+            points.as_slice(),
+            points.as_slice(),
+            config_builder
+                .with_robust_kernel(Some(RobustKernel::Huber(-1.0)))
+                .build(),
+        );
+        assert_eq!(
+            res.unwrap_err(),
+            ICPError::InvalidConfiguration {
+                reason: "Robust kernel scale must be positive"
+            }
+        );
+
+        for search_mode in [
+            SearchMode::Approximate { epsilon: -0.5 },
+            SearchMode::MaxLeafChecks(0),
+        ] {
+            let res = single_precision::icp_2d(
+                points.as_slice(),
+                points.as_slice(),
+                config_builder.with_search_mode(search_mode).build(),
+            );
+            assert_eq!(
+                res.unwrap_err(),
+                ICPError::InvalidConfiguration {
+                    reason: "Search mode must have a non-negative epsilon or a positive amount of leaf checks"
+                }
+            );
+        }
+    }
+
+    #[test]
+    fn test_icp_no_correspondences() {
+        let points = generate_point_cloud(10, array::from_fn(|_| -15.0..=15.0));
+        let points_transformed =
+            transform_point_cloud(&points, Isometry2::new(Vector2::new(100.0, 0.0), 0.0));
+        let config = ICPConfiguration::builder()
+            .with_max_correspondence_distance(Some(1.0))
+            .build();
+
+        let res = single_precision::icp_2d(&points, &points_transformed, config.clone());
+        assert_eq!(res.unwrap_err(), ICPError::NoCorrespondences);
+
+        let res = point_to_plane::single_precision::icp_point_to_plane_2d(
+            &points,
+            &points_transformed,
+            config,
+        );
+        assert_eq!(res.unwrap_err(), ICPError::NoCorrespondences);
+    }
+
+    #[test]
+    fn test_icp_2d_with_outliers() {
+        let points = generate_point_cloud(100, array::from_fn(|_| -15.0..=15.0));
+        let isom = Isometry2::new(Vector2::new(-0.8, 1.3), 0.1);
+        let points_transformed = transform_point_cloud(&points, isom);
+
+        // Source points which have no counterpart in the target point cloud
+        let points_with_outliers = points
+            .iter()
+            .copied()
+            .chain(generate_point_cloud(20, array::from_fn(|_| 25.0..=35.0)))
+            .collect::<Vec<_>>();
+
+        let config_builder = ICPConfiguration::builder()
+            .with_max_iterations(20)
+            .with_mse_interval_threshold(0.01);
+        for config in [
+            config_builder
+                .with_max_correspondence_distance(Some(5.0))
+                .with_robust_kernel(Some(RobustKernel::Huber(0.5)))
+                .build(),
+            config_builder.with_trim_ratio(Some(0.8)).build(),
+            config_builder.with_reciprocal_correspondences(true).build(),
+        ] {
+            let res = single_precision::icp_2d(
+                points_with_outliers.as_slice(),
+                points_transformed.as_slice(),
+                config,
+            );
+            assert!(res.is_ok());
+            let res = res.unwrap();
+            let transform = res.transform;
+
+            // The outliers should never be matched under the resulting transform
+            assert!(res.fitness <= 100.0 / 120.0);
+            assert!((transform.translation.vector - isom.translation.vector).norm() < 0.1);
+            assert!((transform.rotation.angle() - isom.rotation.angle()).abs() < 0.01);
+        }
     }
 
     #[test]
@@ -310,8 +589,10 @@ fn test_no_convegence() {
                 .with_mse_interval_threshold(0.001)
                 .build(),
         );
-        assert!(res.is_err());
-        assert_eq!(res.unwrap_err(), "Could not converge");
+        assert!(matches!(
+            res.unwrap_err(),
+            ICPError::DidNotConverge { iterations: 1, .. }
+        ));
     }
 
     #[test]
@@ -351,7 +632,146 @@ fn test_icp_2d() {
                 .build(),
         );
         assert!(res.is_ok());
-        assert!(res.unwrap().mse < 0.01);
+        let res = res.unwrap();
+        assert!(res.mse < 0.01);
+
+        // Every source point has a counterpart in the target point cloud
+        assert_eq!(res.fitness, 1.0);
+        assert_eq!(res.correspondences.len(), points.len());
+        assert!(res.inlier_rmse < 0.01);
+        assert_eq!(res.information_matrix.shape(), (3, 3));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_icp_serde_round_trip() {
+        use types::ICPSuccess;
+
+        let points = generate_point_cloud(100, array::from_fn(|_| -15.0..=15.0));
+        let isom = Isometry2::new(Vector2::new(-0.8, 1.3), 0.1);
+        let points_transformed = transform_point_cloud(&points, isom);
+        let config = ICPConfiguration::builder()
+            .with_max_iterations(10)
+            .with_robust_kernel(Some(RobustKernel::Huber(1.0)))
+            .build();
+
+        let deserialized_config: ICPConfiguration<f32> =
+            serde_json::from_str(&serde_json::to_string(&config).unwrap()).unwrap();
+        assert_eq!(deserialized_config, config);
+
+        let res = single_precision::icp_2d(&points, &points_transformed, config).unwrap();
+        let deserialized_res: ICPSuccess<f32, UnitComplex<f32>, 2> =
+            serde_json::from_str(&serde_json::to_string(&res).unwrap()).unwrap();
+        assert_eq!(deserialized_res.transform, res.transform);
+        assert_eq!(deserialized_res.mse, res.mse);
+        assert_eq!(deserialized_res.iteration_num, res.iteration_num);
+        assert_eq!(deserialized_res.correspondences, res.correspondences);
+        assert_eq!(deserialized_res.information_matrix, res.information_matrix);
+
+        // The information matrix must contain exactly as many elements as its dimensions specify
+        assert!(
+            serde_json::from_str::<ICPSuccess<f32, UnitComplex<f32>, 2>>(
+                &serde_json::to_string(&res)
+                    .unwrap()
+                    .replace("\"information_matrix\":[", "\"information_matrix\":[0.0,")
+            )
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn test_icp_2d_with_initial_guess() {
+        let points = generate_point_cloud(100, array::from_fn(|_| -15.0..=15.0));
+        let isom = Isometry2::new(Vector2::new(6.0, -4.0), 1.2);
+        let points_transformed = transform_point_cloud(&points, isom);
+        let config = ICPConfiguration::builder()
+            .with_max_iterations(10)
+            .with_mse_interval_threshold(0.01)
+            .build();
+
+        // A rough estimate, such as one from wheel odometry, should be enough to get over the large motion
+        let res = single_precision::icp_2d_with_initial_guess(
+            points.as_slice(),
+            points_transformed.as_slice(),
+            Isometry2::new(Vector2::new(5.8, -4.1), 1.15),
+            config,
+        );
+        assert!(res.is_ok());
+        let res = res.unwrap();
+        assert!(res.mse < 0.01);
+        assert!((res.transform.translation.vector - isom.translation.vector).norm() < 0.01);
+    }
+
+    #[test]
+    fn test_icp_3d_with_initial_guess() {
+        let points = generate_point_cloud(500, array::from_fn(|_| -15.0..=15.0));
+        let isom = Isometry3::new(Vector3::new(5.0, -3.0, 2.0), Vector3::new(0.6, -0.4, 0.9));
+        let points_transformed = transform_point_cloud(&points, isom);
+
+        let res = single_precision::icp_3d_with_initial_guess(
+            points.as_slice(),
+            points_transformed.as_slice(),
+            Isometry3::new(
+                Vector3::new(4.8, -3.1, 2.2),
+                Vector3::new(0.58, -0.42, 0.88),
+            ),
+            ICPConfiguration::builder()
+                .with_kd_tree(true)
+                .with_max_iterations(50)
+                .with_mse_interval_threshold(0.01)
+                .build(),
+        );
+        assert!(res.is_ok());
+        assert!(res.unwrap().mse < 0.05);
+    }
+
+    #[test]
+    fn test_icp_2d_with_observer() {
+        let points = generate_point_cloud(100, array::from_fn(|_| -15.0..=15.0));
+        let isom = Isometry2::new(Vector2::new(-0.8, 1.3), 0.1);
+        let points_transformed = transform_point_cloud(&points, isom);
+        let config = ICPConfiguration::builder()
+            .with_max_iterations(10)
+            .with_mse_interval_threshold(0.01)
+            .build();
+
+        let mut mse_history = Vec::new();
+        let res = single_precision::icp_2d_with_observer(
+            points.as_slice(),
+            points_transformed.as_slice(),
+            Isometry2::identity(),
+            config.clone(),
+            &mut |state: &ICPIterationState<f32, UnitComplex<f32>, 2>| {
+                assert_eq!(state.iteration_num, mse_history.len());
+                assert_eq!(state.closest_points.len(), state.source_points.len());
+                mse_history.push(state.mse);
+                ICPObserverAction::Continue
+            },
+        );
+        assert!(res.is_ok());
+        let res = res.unwrap();
+        assert_eq!(mse_history.len(), res.iteration_num + 1);
+        assert_eq!(mse_history.last().copied(), Some(res.mse));
+
+        // Stop immediately, the transform should still have moved towards the target
+        let res = single_precision::icp_2d_with_observer(
+            points.as_slice(),
+            points_transformed.as_slice(),
+            Isometry2::identity(),
+            config,
+            &mut |_: &ICPIterationState<f32, UnitComplex<f32>, 2>| ICPObserverAction::Stop,
+        );
+        match res.unwrap_err() {
+            ICPError::Stopped {
+                last_transform,
+                iterations,
+                ..
+            } => {
+                assert_eq!(iterations, 1);
+                assert!(last_transform != Isometry2::identity());
+            }
+            err => panic!("Unexpected error: {err:?}"),
+        }
     }
 
     #[test]
@@ -390,7 +810,10 @@ fn test_icp_3d() {
                 .build(),
         );
         assert!(res.is_ok());
-        assert!(res.unwrap().mse < 0.05);
+        let res = res.unwrap();
+        assert!(res.mse < 0.05);
+        assert_eq!(res.fitness, 1.0);
+        assert_eq!(res.information_matrix.shape(), (6, 6));
     }
 
     #[test]
@@ -413,4 +836,30 @@ fn test_icp_3d_with_kd() {
         assert!(res.is_ok());
         assert!(res.unwrap().mse < 0.05);
     }
+
+    #[test]
+    fn test_icp_3d_with_approximate_kd() {
+        let points = generate_point_cloud(500, array::from_fn(|_| -15.0..=15.0));
+        let translation = Vector3::new(-0.8, 1.3, 0.2);
+        let rotation = Vector3::new(0.1, 0.2, -0.21);
+        let isom = Isometry3::new(translation, rotation);
+        let points_transformed = transform_point_cloud(&points, isom);
+
+        let config_builder = ICPConfiguration::builder()
+            .with_kd_tree(true)
+            .with_max_iterations(50)
+            .with_mse_interval_threshold(0.01);
+        for search_mode in [
+            SearchMode::Approximate { epsilon: 0.5 },
+            SearchMode::MaxLeafChecks(64),
+        ] {
+            let res = single_precision::icp_3d(
+                points.as_slice(),
+                points_transformed.as_slice(),
+                config_builder.with_search_mode(search_mode).build(),
+            );
+            assert!(res.is_ok());
+            assert!(res.unwrap().mse < 0.05);
+        }
+    }
 }
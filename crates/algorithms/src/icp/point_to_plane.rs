@@ -0,0 +1,409 @@
+// SPDX-License-Identifier: MIT
+/*
+ * Copyright (c) [2023 - Present] Emily Matheys <emilymatt96@gmail.com>
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+use crate::{
+    icp::{
+        helpers::{
+            calculate_correspondence_weights, calculate_mse, create_icp_success,
+            find_closest_point_indices, has_converged, validate_input,
+        },
+        types::{ICPConfiguration, ICPError, ICPResult},
+    },
+    kd_tree::KDTree,
+    types::{AbstractIsometry, IsometryAbstractor, NeighbourhoodSearch},
+    utils::point_cloud::estimate_normals,
+    Sum, Vec,
+};
+use nalgebra::{Isometry, Point, RealField, SVector};
+use num_traits::{AsPrimitive, Bounded, Zero};
+
+/// The amount of neighbouring target points used to estimate the surface normal around each target point.
+const NORMAL_ESTIMATION_NEIGHBOURS: usize = 8;
+
+/// Calculates a new transform, by solving the linearized least-squares system of point-to-plane distances.
+///
+/// # Arguments
+/// * `old_transform`: A reference to the current [`Isometry`], which was used to transform the source points.
+/// * `transformed_points_a`: a slice of [`Point`], representing the transformed source point cloud.
+/// * `closest_points`: a slice of [`Point`], representing the target nearest neighbour for each point in `transformed_points_a`.
+/// * `closest_normals`: a slice of [`SVector`], representing the surface normal of each point in `closest_points`.
+/// * `weights`: a slice of the weight of each correspondence, rejected correspondences have a weight of `0`.
+///
+/// # Generics
+/// * `T`: Either an [`f32`] or [`f64`].
+/// * `N`: A const usize, either `2` or `3`.
+///
+/// # Returns
+/// The updated [`Isometry`], or [`None`] if the system could not be solved, meaning the geometry does not constrain every degree of freedom.
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument("Update Point-To-Plane Transform", skip_all, level = "debug")
+)]
+fn update_transform_point_to_plane<T, const N: usize>(
+    old_transform: &Isometry<T, <IsometryAbstractor<T, N> as AbstractIsometry<T, N>>::RotType, N>,
+    transformed_points_a: &[Point<T, N>],
+    closest_points: &[Point<T, N>],
+    closest_normals: &[SVector<T, N>],
+    weights: &[T],
+) -> Option<Isometry<T, <IsometryAbstractor<T, N> as AbstractIsometry<T, N>>::RotType, N>>
+where
+    T: Copy + RealField,
+    IsometryAbstractor<T, N>: AbstractIsometry<T, N>,
+{
+    let (hessian, gradient) = transformed_points_a
+        .iter()
+        .zip(closest_points.iter().zip(closest_normals.iter()))
+        .zip(weights.iter())
+        .filter(|(_, weight)| **weight > T::zero())
+        .fold(
+            (Zero::zero(), Zero::zero()),
+            |(hessian, gradient): (
+                <IsometryAbstractor<T, N> as AbstractIsometry<T, N>>::IncrementMatrix,
+                <IsometryAbstractor<T, N> as AbstractIsometry<T, N>>::Increment,
+            ),
+             ((transformed_point_a, (closest_point, closest_normal)), weight)| {
+                // Only the distance along the normal is penalized
+                let residual = closest_normal.dot(&(transformed_point_a - closest_point));
+                let (point_hessian, point_gradient) = IsometryAbstractor::<T, N>::normal_equations(
+                    transformed_point_a,
+                    &(closest_normal * closest_normal.transpose() * *weight),
+                    &(closest_normal * (residual * *weight)),
+                );
+                (hessian + point_hessian, gradient + point_gradient)
+            },
+        );
+
+    let increment = IsometryAbstractor::<T, N>::solve_increment(&hessian, &gradient)?;
+    Some(IsometryAbstractor::<T, N>::apply_increment(
+        old_transform,
+        &increment,
+    ))
+}
+
+/// A free-form version of the point-to-plane ICP function, allowing for any input and output, under the constraints of the function.
+/// Instead of minimizing the distance between each source point and its match, this minimizes the distance between
+/// each source point and the plane (or line, in 2D space) passing through its match, which is estimated from the target point cloud.
+/// This tends to converge in much fewer iterations for structured scenes, such as walls and floors.
+///
+/// # Arguments
+/// * `points_a`: A slice of [`Point`], representing the source point cloud.
+/// * `points_b`: A slice of [`Point`], representing the target point cloud.
+/// * `config`: a reference to an [`ICPConfiguration`], specifying the behaviour of the algorithm.
+///
+/// # Generics
+/// * `T`: Either [`prim@f32`] or [`prim@f64`]
+/// * `N`: a usize, either `2` or `3`
+///
+/// # Returns
+/// An [`ICPSuccess`](crate::icp::types::ICPSuccess) struct with an [`Isometry`] transform with a `T` precision, or an [`ICPError`] explaining what went wrong.
+///
+/// [^convergence_note]: This does not guarantee that the transformation is correct, only that no further benefit can be gained by running another iteration.
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument("Full Point-To-Plane ICP Algorithm", skip_all, level = "info")
+)]
+pub fn icp_point_to_plane<T, const N: usize>(
+    points_a: &[Point<T, N>],
+    points_b: &[Point<T, N>],
+    config: ICPConfiguration<T>,
+) -> ICPResult<T, N>
+where
+    T: Bounded + Copy + Default + RealField + Sum,
+    usize: AsPrimitive<T>,
+    IsometryAbstractor<T, N>: AbstractIsometry<T, N>,
+{
+    validate_input(points_a, points_b, &config)?;
+
+    let normals_b = estimate_normals(
+        points_b,
+        NeighbourhoodSearch::KNearest(NORMAL_ESTIMATION_NEIGHBOURS),
+        &Point::origin(),
+    )
+    .into_iter()
+    // A point without a normal adds no constraint to the system
+    .map(|point_normal| {
+        point_normal.map_or_else(SVector::zeros, |point_normal| point_normal.normal)
+    })
+    .collect::<Vec<_>>();
+    log::trace!("Estimated target normals");
+
+    let target_points_tree = config.use_kd_tree.then(|| KDTree::from_indexed(points_b));
+    let mut transformed_points = points_a.to_vec();
+    let mut current_transform = Isometry::identity();
+    let mut current_mse = <T as Bounded>::max_value();
+
+    for iteration_num in 0..config.max_iterations {
+        log::trace!(
+            "Running iteration number {iteration_num}/{}",
+            config.max_iterations
+        );
+
+        let (closest_points, closest_normals): (Vec<_>, Vec<_>) = find_closest_point_indices(
+            &transformed_points,
+            points_b,
+            target_points_tree.as_ref(),
+            config.search_mode,
+        )
+        .into_iter()
+        .map(|closest_idx| (points_b[closest_idx], normals_b[closest_idx]))
+        .unzip();
+        log::trace!("Found nearest neighbours");
+
+        let weights =
+            calculate_correspondence_weights(&transformed_points, &closest_points, &config);
+        if weights.iter().all(|weight| *weight <= T::zero()) {
+            return Err(ICPError::NoCorrespondences);
+        }
+        current_transform = update_transform_point_to_plane(
+            &current_transform,
+            &transformed_points,
+            &closest_points,
+            &closest_normals,
+            &weights,
+        )
+        .ok_or(ICPError::DegenerateGeometry)?;
+
+        for (transformed_point, point_a) in transformed_points.iter_mut().zip(points_a.iter()) {
+            *transformed_point = current_transform.transform_point(point_a);
+        }
+        let new_mse = calculate_mse(&transformed_points, &closest_points, &weights);
+        log::trace!("New MSE: {new_mse}");
+
+        // If the MSE difference is lower than the threshold, then this is as good as it gets
+        if has_converged(current_mse, new_mse, &config) {
+            log::trace!("Converged after {iteration_num} iterations with an MSE of {new_mse}");
+            // Only the distance along the normal of each correspondence was minimized
+            let residual_weights = closest_normals
+                .iter()
+                .map(|closest_normal| closest_normal * closest_normal.transpose())
+                .collect::<Vec<_>>();
+            return Ok(create_icp_success(
+                points_a,
+                &transformed_points,
+                &closest_points,
+                &residual_weights,
+                current_transform,
+                new_mse,
+                iteration_num,
+                &config,
+            ));
+        }
+
+        current_mse = new_mse;
+    }
+
+    Err(ICPError::DidNotConverge {
+        last_transform: current_transform,
+        last_mse: current_mse,
+        iterations: config.max_iterations,
+    })
+}
+
+#[cfg(feature = "pregenerated")]
+macro_rules! impl_icp_point_to_plane_algorithm {
+    ($precision:expr, $doc:tt, $nd:expr, $rot_type:expr) => {
+        ::paste::paste! {
+            #[doc = "A point-to-plane ICP algorithm in " $nd "D space."]
+            #[doc = "# Arguments"]
+            #[doc = "* `points_a`: A slice of [`Point`], representing the source point cloud."]
+            #[doc = "* `points_b`: A slice of [`Point`], representing the target point cloud."]
+            #[doc = "* `config`: a reference to an [`ICPConfiguration`], specifying the behaviour of the algorithm."]
+            #[doc = ""]
+            #[doc = "# Returns"]
+            #[doc = "An [`ICPSuccess`] struct with an [`Isometry`](nalgebra::Isometry) transform with an `" $precision "` precision, or an [`ICPError`] explaining what went wrong."]
+            #[doc = ""]
+            #[doc = "[^convergence_note]: This does not guarantee that the transformation is correct, only that no further benefit can be gained by running another iteration."]
+            pub fn [<icp_point_to_plane_$nd d>](points_a: &[Point<$precision, $nd>],
+                points_b: &[Point<$precision, $nd>],
+                config: ICPConfiguration<$precision>) -> Result<ICPSuccess<$precision, $rot_type<$precision>, $nd>, ICPError<$precision, $rot_type<$precision>, $nd>> {
+                    super::icp_point_to_plane(points_a, points_b, config)
+            }
+        }
+    };
+
+    ($precision:expr, doc $doc:tt) => {
+        ::paste::paste! {
+            #[doc = "A " $doc "-precision implementation of a point-to-plane ICP algorithm"]
+            pub mod [<$doc _precision>] {
+                use nalgebra::{Point, UnitComplex, UnitQuaternion};
+                use crate::icp::types::{ICPConfiguration, ICPError, ICPSuccess};
+
+                impl_icp_point_to_plane_algorithm!($precision, $doc, 2, UnitComplex);
+                impl_icp_point_to_plane_algorithm!($precision, $doc, 3, UnitQuaternion);
+            }
+        }
+    }
+}
+
+#[cfg(feature = "pregenerated")]
+impl_icp_point_to_plane_algorithm!(f32, doc single);
+#[cfg(feature = "pregenerated")]
+impl_icp_point_to_plane_algorithm!(f64, doc double);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::point_cloud::{generate_corner_3d, transform_point_cloud};
+    use nalgebra::{Isometry2, Isometry3, Point2, Point3, Vector2, Vector3};
+
+    // An L-shaped corridor corner, made of two perpendicular walls
+    fn generate_corner_2d() -> Vec<Point2<f64>> {
+        (0..50)
+            .map(|idx| Point2::new(idx as f64 * 0.2, 0.0))
+            .chain((1..50).map(|idx| Point2::new(0.0, idx as f64 * 0.2)))
+            .collect()
+    }
+
+    #[test]
+    fn test_icp_point_to_plane_errors() {
+        let points = generate_corner_2d();
+        let config_builder = ICPConfiguration::builder();
+
+        let res = double_precision::icp_point_to_plane_2d(&[], &points, config_builder.build());
+        assert_eq!(res.unwrap_err(), ICPError::EmptySource);
+
+        let res = double_precision::icp_point_to_plane_2d(&points, &[], config_builder.build());
+        assert_eq!(res.unwrap_err(), ICPError::EmptyTarget);
+
+        // A single straight wall can't constrain translation along it
+        let wall = (0..20)
+            .map(|idx| Point2::new(idx as f64, 0.0))
+            .collect::<Vec<_>>();
+        let res = double_precision::icp_point_to_plane_2d(&wall, &wall, config_builder.build());
+        assert_eq!(res.unwrap_err(), ICPError::DegenerateGeometry);
+    }
+
+    #[test]
+    fn test_icp_point_to_plane_2d() {
+        let points = generate_corner_2d();
+        let isom = Isometry2::new(Vector2::new(-0.3, 0.2), 0.05);
+        let points_transformed = transform_point_cloud(&points, isom);
+
+        let res = double_precision::icp_point_to_plane_2d(
+            &points,
+            &points_transformed,
+            ICPConfiguration::builder()
+                .with_max_iterations(20)
+                .with_mse_interval_threshold(0.0001)
+                .build(),
+        );
+        assert!(res.is_ok());
+        let res = res.unwrap();
+        assert!(res.mse < 0.01);
+        assert!((res.transform.translation.vector - isom.translation.vector).norm() < 0.01);
+    }
+
+    #[test]
+    fn test_icp_point_to_plane_3d() {
+        let points = generate_corner_3d(15, 0.4);
+        let isom = Isometry3::new(
+            Vector3::new(-0.2, 0.1, 0.15),
+            Vector3::new(0.02, -0.03, 0.04),
+        );
+        let points_transformed = transform_point_cloud(&points, isom);
+
+        let res = double_precision::icp_point_to_plane_3d(
+            &points,
+            &points_transformed,
+            ICPConfiguration::builder()
+                .with_max_iterations(20)
+                .with_mse_interval_threshold(0.0001)
+                .build(),
+        );
+        assert!(res.is_ok());
+        let res = res.unwrap();
+        assert!(res.mse < 0.01);
+        assert!((res.transform.translation.vector - isom.translation.vector).norm() < 0.01);
+    }
+
+    #[test]
+    fn test_icp_point_to_plane_3d_with_kd_tree() {
+        let points = generate_corner_3d(15, 0.4);
+        let isom = Isometry3::new(
+            Vector3::new(-0.2, 0.1, 0.15),
+            Vector3::new(0.02, -0.03, 0.04),
+        );
+        let points_transformed = transform_point_cloud(&points, isom);
+
+        let config_builder = ICPConfiguration::builder()
+            .with_max_iterations(20)
+            .with_mse_interval_threshold(0.0001);
+        let exhaustive_res = double_precision::icp_point_to_plane_3d(
+            &points,
+            &points_transformed,
+            config_builder.build(),
+        )
+        .unwrap();
+        let kd_tree_res = double_precision::icp_point_to_plane_3d(
+            &points,
+            &points_transformed,
+            config_builder.with_kd_tree(true).build(),
+        )
+        .unwrap();
+        assert_eq!(kd_tree_res.iteration_num, exhaustive_res.iteration_num);
+        assert!(
+            (kd_tree_res.transform.translation.vector
+                - exhaustive_res.transform.translation.vector)
+                .norm()
+                < 1e-9
+        );
+    }
+
+    #[test]
+    fn test_icp_point_to_plane_information_matrix() {
+        // A single flat floor leaves the translation along it and the rotation around its normal unconstrained
+        let floor = (0..15)
+            .flat_map(|a| (0..15).map(move |b| Point3::new(a as f64 * 0.4, b as f64 * 0.4, 0.0)))
+            .collect::<Vec<_>>();
+        let residual_weights = estimate_normals(
+            &floor,
+            NeighbourhoodSearch::KNearest(NORMAL_ESTIMATION_NEIGHBOURS),
+            &Point::origin(),
+        )
+        .into_iter()
+        .map(|point_normal| {
+            let normal = point_normal.unwrap().normal;
+            normal * normal.transpose()
+        })
+        .collect::<Vec<_>>();
+
+        let res = create_icp_success(
+            &floor,
+            &floor,
+            &floor,
+            &residual_weights,
+            Isometry::identity(),
+            0.0,
+            0,
+            &ICPConfiguration::builder().build(),
+        );
+        let eigenvalues = res.information_matrix.symmetric_eigenvalues();
+        assert_eq!(
+            eigenvalues
+                .iter()
+                .filter(|eigenvalue| eigenvalue.abs() < 1e-9)
+                .count(),
+            3
+        );
+    }
+}
@@ -0,0 +1,285 @@
+// SPDX-License-Identifier: MIT
+/*
+ * Copyright (c) [2023 - Present] Emily Matheys <emilymatt96@gmail.com>
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+use crate::{
+    icp::{
+        icp_with_initial_guess,
+        types::{ICPConfiguration, ICPError, ICPResult},
+    },
+    types::{AbstractIsometry, IsometryAbstractor},
+    utils::point_cloud::voxel_downsample_point_cloud,
+    Sum,
+};
+use nalgebra::{Isometry, Point, RealField};
+use num_traits::{AsPrimitive, Bounded};
+
+/// A single level of a multi-resolution ICP pyramid, see [`pyramid_icp`].
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ICPPyramidLevel<T> {
+    /// The voxel size both point clouds are downsampled with before running this level,
+    /// when [`None`], the point clouds are used in their full resolution.
+    pub voxel_size: Option<T>,
+    /// The configuration used by the ICP algorithm on this level,
+    /// coarse levels usually require a larger max correspondence distance, and looser thresholds.
+    pub config: ICPConfiguration<T>,
+}
+
+impl<T> ICPPyramidLevel<T> {
+    /// Creates a new pyramid level.
+    ///
+    /// # Arguments
+    /// * `voxel_size`: If is [`Some`], sets the voxel size both point clouds are downsampled with before running this level.
+    /// * `config`: an [`ICPConfiguration`], specifying the behaviour of the algorithm on this level.
+    ///
+    /// # Returns
+    /// An [`ICPPyramidLevel`].
+    pub fn new(voxel_size: Option<T>, config: ICPConfiguration<T>) -> Self {
+        Self { voxel_size, config }
+    }
+}
+
+/// A coarse-to-fine version of the ICP function, which runs the algorithm on increasingly finer resolutions of the point clouds.
+/// Each level downsamples both point clouds using [`voxel_downsample_point_cloud`], and starts from the transform found by the previous level.
+/// Coarse levels are cheap, and are less prone to falling into local minima when the initial offset is large,
+/// while the fine levels refine the transform to the full precision of the point clouds.
+///
+/// # Arguments
+/// * `points_a`: A slice of [`Point`], representing the source point cloud.
+/// * `points_b`: A slice of [`Point`], representing the target point cloud.
+/// * `initial_guess`: An [`Isometry`], representing the estimated transform between `points_a` and `points_b`.
+/// * `levels`: A slice of [`ICPPyramidLevel`], ordered from the coarsest level to the finest one.
+///
+/// # Generics
+/// * `T`: Either [`prim@f32`] or [`prim@f64`]
+/// * `N`: a usize, either `2` or `3`
+///
+/// # Returns
+/// The [`ICPSuccess`](crate::icp::types::ICPSuccess) of the finest level, with an [`Isometry`] transform with a `T` precision, or an [`ICPError`] explaining what went wrong.
+/// Note that `iteration_num` only counts the iterations of the finest level.
+///
+/// # Warnings
+/// * A level other than the finest one that does not converge is not considered an error, its last transform is passed on to the next level.
+///
+/// [^convergence_note]: This does not guarantee that the transformation is correct, only that no further benefit can be gained by running another iteration.
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument("Full Pyramid ICP Algorithm", skip_all, level = "info")
+)]
+pub fn pyramid_icp<T, const N: usize>(
+    points_a: &[Point<T, N>],
+    points_b: &[Point<T, N>],
+    initial_guess: Isometry<T, <IsometryAbstractor<T, N> as AbstractIsometry<T, N>>::RotType, N>,
+    levels: &[ICPPyramidLevel<T>],
+) -> ICPResult<T, N>
+where
+    T: AsPrimitive<isize> + Bounded + Copy + Default + RealField + Sum,
+    usize: AsPrimitive<T>,
+    IsometryAbstractor<T, N>: AbstractIsometry<T, N>,
+{
+    let Some((finest_level, coarse_levels)) = levels.split_last() else {
+        return Err(ICPError::InvalidConfiguration {
+            reason: "Pyramid must contain at least one level",
+        });
+    };
+
+    if levels
+        .iter()
+        .any(|level| level.voxel_size.is_some_and(|size| size <= T::zero()))
+    {
+        return Err(ICPError::InvalidConfiguration {
+            reason: "Voxel size must be positive",
+        });
+    }
+
+    let run_level = |level: &ICPPyramidLevel<T>, initial_guess| match level.voxel_size {
+        Some(voxel_size) => icp_with_initial_guess(
+            &voxel_downsample_point_cloud(points_a, voxel_size),
+            &voxel_downsample_point_cloud(points_b, voxel_size),
+            initial_guess,
+            level.config.clone(),
+        ),
+        None => icp_with_initial_guess(points_a, points_b, initial_guess, level.config.clone()),
+    };
+
+    let mut current_transform = initial_guess;
+    for (level_num, level) in coarse_levels.iter().enumerate() {
+        log::trace!("Running pyramid level number {level_num}/{}", levels.len());
+        current_transform = match run_level(level, current_transform) {
+            Ok(success) => success.transform,
+            Err(ICPError::DidNotConverge { last_transform, .. }) => {
+                log::trace!(
+                    "Level {level_num} did not converge, continuing with its last transform"
+                );
+                last_transform
+            }
+            Err(err) => return Err(err),
+        };
+    }
+
+    log::trace!("Running finest pyramid level");
+    run_level(finest_level, current_transform)
+}
+
+#[cfg(feature = "pregenerated")]
+macro_rules! impl_pyramid_icp_algorithm {
+    ($precision:expr, $doc:tt, $nd:expr, $rot_type:expr) => {
+        ::paste::paste! {
+            #[doc = "A coarse-to-fine ICP algorithm in " $nd "D space."]
+            #[doc = "# Arguments"]
+            #[doc = "* `points_a`: A slice of [`Point`], representing the source point cloud."]
+            #[doc = "* `points_b`: A slice of [`Point`], representing the target point cloud."]
+            #[doc = "* `initial_guess`: An [`Isometry`], representing the estimated transform between `points_a` and `points_b`."]
+            #[doc = "* `levels`: A slice of [`ICPPyramidLevel`], ordered from the coarsest level to the finest one."]
+            #[doc = ""]
+            #[doc = "# Returns"]
+            #[doc = "An [`ICPSuccess`] struct with an [`Isometry`] transform with an `" $precision "` precision, or an [`ICPError`] explaining what went wrong."]
+            #[doc = ""]
+            #[doc = "[^convergence_note]: This does not guarantee that the transformation is correct, only that no further benefit can be gained by running another iteration."]
+            pub fn [<pyramid_icp_$nd d>](points_a: &[Point<$precision, $nd>],
+                points_b: &[Point<$precision, $nd>],
+                initial_guess: Isometry<$precision, $rot_type<$precision>, $nd>,
+                levels: &[ICPPyramidLevel<$precision>]) -> Result<ICPSuccess<$precision, $rot_type<$precision>, $nd>, ICPError<$precision, $rot_type<$precision>, $nd>> {
+                    super::pyramid_icp(points_a, points_b, initial_guess, levels)
+            }
+        }
+    };
+
+    ($precision:expr, doc $doc:tt) => {
+        ::paste::paste! {
+            #[doc = "A " $doc "-precision implementation of a coarse-to-fine ICP algorithm"]
+            pub mod [<$doc _precision>] {
+                use nalgebra::{Isometry, Point, UnitComplex, UnitQuaternion};
+                use super::ICPPyramidLevel;
+                use crate::icp::types::{ICPError, ICPSuccess};
+
+                impl_pyramid_icp_algorithm!($precision, $doc, 2, UnitComplex);
+                impl_pyramid_icp_algorithm!($precision, $doc, 3, UnitQuaternion);
+            }
+        }
+    }
+}
+
+#[cfg(feature = "pregenerated")]
+impl_pyramid_icp_algorithm!(f32, doc single);
+#[cfg(feature = "pregenerated")]
+impl_pyramid_icp_algorithm!(f64, doc double);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        array,
+        utils::point_cloud::{generate_point_cloud, transform_point_cloud},
+        Vec,
+    };
+    use nalgebra::{Isometry2, Isometry3, Vector2, Vector3};
+
+    fn generate_levels(voxel_sizes: &[Option<f32>]) -> Vec<ICPPyramidLevel<f32>> {
+        voxel_sizes
+            .iter()
+            .map(|voxel_size| {
+                // Coarse levels allow for far away correspondences, and don't need to be as precise
+                let scale = voxel_size.unwrap_or(0.5);
+                ICPPyramidLevel::new(
+                    *voxel_size,
+                    ICPConfiguration::builder()
+                        .with_max_iterations(50)
+                        .with_mse_interval_threshold(0.001)
+                        .with_max_correspondence_distance(Some(scale * 4.0))
+                        .build(),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_pyramid_icp_errors() {
+        let points = generate_point_cloud(10, array::from_fn(|_| -15.0..=15.0));
+
+        let res = single_precision::pyramid_icp_2d(&points, &points, Isometry2::identity(), &[]);
+        assert_eq!(
+            res.unwrap_err(),
+            ICPError::InvalidConfiguration {
+                reason: "Pyramid must contain at least one level"
+            }
+        );
+
+        let res = single_precision::pyramid_icp_2d(
+            &points,
+            &points,
+            Isometry2::identity(),
+            &generate_levels(&[Some(-1.0), None]),
+        );
+        assert_eq!(
+            res.unwrap_err(),
+            ICPError::InvalidConfiguration {
+                reason: "Voxel size must be positive"
+            }
+        );
+
+        let res = single_precision::pyramid_icp_2d(
+            &[],
+            &points,
+            Isometry2::identity(),
+            &generate_levels(&[Some(1.0), None]),
+        );
+        assert_eq!(res.unwrap_err(), ICPError::EmptySource);
+    }
+
+    #[test]
+    fn test_pyramid_icp_2d() {
+        let points = generate_point_cloud(1000, array::from_fn(|_| -15.0..=15.0));
+        let isom = Isometry2::new(Vector2::new(2.5, -1.5), 0.3);
+        let points_transformed = transform_point_cloud(&points, isom);
+
+        let res = single_precision::pyramid_icp_2d(
+            &points,
+            &points_transformed,
+            Isometry2::identity(),
+            &generate_levels(&[Some(3.0), Some(1.0), None]),
+        );
+        assert!(res.is_ok());
+        let transform = res.unwrap().transform;
+        assert!((transform.translation.vector - isom.translation.vector).norm() < 0.05);
+        assert!((transform.rotation.angle() - isom.rotation.angle()).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_pyramid_icp_3d() {
+        let points = generate_point_cloud(1000, array::from_fn(|_| -15.0..=15.0));
+        let isom = Isometry3::new(Vector3::new(2.5, -1.5, 1.0), Vector3::new(0.1, 0.2, -0.2));
+        let points_transformed = transform_point_cloud(&points, isom);
+
+        let res = single_precision::pyramid_icp_3d(
+            &points,
+            &points_transformed,
+            Isometry3::identity(),
+            &generate_levels(&[Some(6.0), Some(3.0), None]),
+        );
+        assert!(res.is_ok());
+        let transform = res.unwrap().transform;
+        assert!((transform.translation.vector - isom.translation.vector).norm() < 0.05);
+        assert!(transform.rotation.angle_to(&isom.rotation) < 0.01);
+    }
+}
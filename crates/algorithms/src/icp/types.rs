@@ -21,13 +21,83 @@
  * SOFTWARE.
  */
 
-use crate::Debug;
-use nalgebra::{AbstractRotation, Isometry, Scalar};
+use crate::{
+    kd_tree::SearchMode,
+    types::{AbstractIsometry, IsometryAbstractor},
+    Debug, Vec,
+};
+use core::fmt::{Display, Formatter};
+use nalgebra::{AbstractRotation, Isometry, Point, RealField, Scalar};
 use num_traits::AsPrimitive;
 
-/// Contains the resulting transform, the resulting Mean Squared Error, and the number of iterations taken for a successful ICP convergence.
+/// A robust M-estimator kernel, used to weigh down correspondences with large residuals,
+/// so that outliers have less of an effect over the estimated transform.
+/// Each kernel is parameterized by a scale, above which residuals are considered increasingly unlikely to be inliers.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum RobustKernel<T> {
+    /// Quadratic for residuals smaller than the scale, and linear above it.
+    Huber(T),
+    /// Weighs residuals by their inverse square relative to the scale, never fully rejecting a correspondence.
+    Cauchy(T),
+    /// Also known as the bisquare kernel, completely rejects residuals larger than the scale.
+    Tukey(T),
+    /// Rapidly weighs down residuals larger than the scale, without fully rejecting them.
+    GemanMcClure(T),
+}
+
+impl<T: Copy + RealField> RobustKernel<T> {
+    /// Returns the scale parameter of the kernel.
+    pub fn scale(&self) -> T {
+        match *self {
+            Self::Huber(scale)
+            | Self::Cauchy(scale)
+            | Self::Tukey(scale)
+            | Self::GemanMcClure(scale) => scale,
+        }
+    }
+
+    /// Calculates the weight of a correspondence, according to its residual.
+    ///
+    /// # Arguments
+    /// * `residual`: The distance between the two points of the correspondence.
+    ///
+    /// # Returns
+    /// A `T` between `0` and `1`, where a residual of `0` always has a weight of `1`.
+    pub fn weight(&self, residual: T) -> T {
+        let scaled_residual_squared = (residual / self.scale()) * (residual / self.scale());
+        match *self {
+            Self::Huber(scale) => {
+                if residual.abs() <= scale {
+                    T::one()
+                } else {
+                    scale / residual.abs()
+                }
+            }
+            Self::Cauchy(_) => T::one() / (T::one() + scaled_residual_squared),
+            Self::Tukey(_) => {
+                if scaled_residual_squared <= T::one() {
+                    (T::one() - scaled_residual_squared) * (T::one() - scaled_residual_squared)
+                } else {
+                    T::zero()
+                }
+            }
+            Self::GemanMcClure(_) => {
+                let denominator = T::one() + scaled_residual_squared;
+                T::one() / (denominator * denominator)
+            }
+        }
+    }
+}
+
+/// Contains the resulting transform, the resulting Mean Squared Error, and the number of iterations taken for a successful ICP convergence,
+/// along with several metrics describing how trustworthy the resulting transform is.
 #[derive(Debug)]
-pub struct ICPSuccess<T: Scalar, R: AbstractRotation<T, N>, const N: usize> {
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ICPSuccess<T: RealField, R: AbstractRotation<T, N>, const N: usize>
+where
+    IsometryAbstractor<T, N>: AbstractIsometry<T, N>,
+{
     /// An isometric matrix, containing the translation and rotation between the point sets.
     /// In 2D space, its rotation component would be a [`UnitComplex`](nalgebra::UnitComplex), in 3D space it would be a [`UnitQuaternion`](nalgebra::UnitQuaternion).
     pub transform: Isometry<T, R, N>,
@@ -36,19 +106,297 @@ pub struct ICPSuccess<T: Scalar, R: AbstractRotation<T, N>, const N: usize> {
     pub mse: T,
     /// The amount of iterations passed until convergence.
     pub iteration_num: usize,
+    /// The fraction of source points that have an inlier correspondence in the target point cloud, under the resulting transform.
+    /// Correspondences are considered inliers if they were not rejected according to the [`ICPConfiguration`],
+    /// so this is a measure of the overlap between the two point clouds.
+    pub fitness: T,
+    /// The Root Mean Squared distance between the points of every inlier correspondence, under the resulting transform.
+    /// When a [`RobustKernel`] is configured, each squared distance is weighted by the kernel, like [`mse`](ICPSuccess::mse).
+    pub inlier_rmse: T,
+    /// The inlier correspondences under the resulting transform,
+    /// each one containing a point from `points_a` (not transformed), and its nearest neighbour from `points_b`.
+    pub correspondences: Vec<(Point<T, N>, Point<T, N>)>,
+    /// The information matrix (inverse covariance) of the resulting transform, calculated from the inlier correspondences.
+    /// It is scaled by the inverse of the per-axis noise variance, estimated as `inlier_rmse^2 / N`,
+    /// and is left unscaled when the inliers match perfectly.
+    /// Its rows and columns are ordered with the rotational components first, followed by the translational components,
+    /// so it is a 3x3 matrix in 2D space, and a 6x6 matrix in 3D space.
+    #[cfg_attr(
+        feature = "serde",
+        serde(bound(
+            serialize = "<IsometryAbstractor<T, N> as AbstractIsometry<T, N>>::IncrementMatrix: serde::Serialize",
+            deserialize = "<IsometryAbstractor<T, N> as AbstractIsometry<T, N>>::IncrementMatrix: serde::Deserialize<'de>"
+        ))
+    )]
+    pub information_matrix: <IsometryAbstractor<T, N> as AbstractIsometry<T, N>>::IncrementMatrix,
 }
 
-/// A struct specifying configuration options for an ICP algorithm.
+/// Describes why an ICP algorithm could not return a transform.
 #[derive(Clone, Debug)]
+pub enum ICPError<T: Scalar, R: AbstractRotation<T, N>, const N: usize> {
+    /// The source point cloud contains no points.
+    EmptySource,
+    /// The target point cloud contains no points.
+    EmptyTarget,
+    /// The provided [`ICPConfiguration`] does not allow the algorithm to run, or to ever converge.
+    InvalidConfiguration {
+        /// Explains which part of the configuration is invalid.
+        reason: &'static str,
+    },
+    /// The algorithm ran out of iterations before converging.
+    /// The best transform found so far is still returned, as it may be good enough for some purposes.
+    DidNotConverge {
+        /// The transform calculated in the last iteration.
+        last_transform: Isometry<T, R, N>,
+        /// The Mean Squared Error of the last iteration.
+        last_mse: T,
+        /// The amount of iterations that were run.
+        iterations: usize,
+    },
+    /// The algorithm was stopped by an [`ICPObserver`] before converging.
+    Stopped {
+        /// The transform calculated in the last iteration.
+        last_transform: Isometry<T, R, N>,
+        /// The Mean Squared Error of the last iteration.
+        last_mse: T,
+        /// The amount of iterations that were run.
+        iterations: usize,
+    },
+    /// The geometry of the point clouds does not constrain every degree of freedom of the transform,
+    /// for example, when trying to match a single flat wall.
+    DegenerateGeometry,
+    /// Every correspondence was rejected according to the [`ICPConfiguration`],
+    /// for example, when no point is within the maximum correspondence distance of its nearest neighbour.
+    NoCorrespondences,
+}
+
+impl<T, R, const N: usize> PartialEq for ICPError<T, R, N>
+where
+    T: Scalar,
+    R: AbstractRotation<T, N>,
+    Isometry<T, R, N>: PartialEq,
+{
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::EmptySource, Self::EmptySource)
+            | (Self::EmptyTarget, Self::EmptyTarget)
+            | (Self::DegenerateGeometry, Self::DegenerateGeometry)
+            | (Self::NoCorrespondences, Self::NoCorrespondences) => true,
+            (
+                Self::InvalidConfiguration { reason },
+                Self::InvalidConfiguration {
+                    reason: other_reason,
+                },
+            ) => reason == other_reason,
+            (
+                Self::DidNotConverge {
+                    last_transform,
+                    last_mse,
+                    iterations,
+                },
+                Self::DidNotConverge {
+                    last_transform: other_last_transform,
+                    last_mse: other_last_mse,
+                    iterations: other_iterations,
+                },
+            )
+            | (
+                Self::Stopped {
+                    last_transform,
+                    last_mse,
+                    iterations,
+                },
+                Self::Stopped {
+                    last_transform: other_last_transform,
+                    last_mse: other_last_mse,
+                    iterations: other_iterations,
+                },
+            ) => {
+                last_transform == other_last_transform
+                    && last_mse == other_last_mse
+                    && iterations == other_iterations
+            }
+            _ => false,
+        }
+    }
+}
+
+impl<T, R, const N: usize> Display for ICPError<T, R, N>
+where
+    T: Scalar + Display,
+    R: AbstractRotation<T, N>,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::EmptySource => write!(f, "Source point cloud is empty"),
+            Self::EmptyTarget => write!(f, "Target point cloud is empty"),
+            Self::InvalidConfiguration { reason } => write!(f, "Invalid configuration: {reason}"),
+            Self::DidNotConverge {
+                last_mse,
+                iterations,
+                ..
+            } => write!(
+                f,
+                "Could not converge after {iterations} iterations, last MSE was {last_mse}"
+            ),
+            Self::Stopped {
+                last_mse,
+                iterations,
+                ..
+            } => write!(
+                f,
+                "Stopped by observer after {iterations} iterations, last MSE was {last_mse}"
+            ),
+            Self::DegenerateGeometry => {
+                write!(f, "Degenerate geometry, could not solve for a transform")
+            }
+            Self::NoCorrespondences => write!(f, "Every correspondence was rejected"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T, R, const N: usize> std::error::Error for ICPError<T, R, N>
+where
+    T: Scalar + Display,
+    R: AbstractRotation<T, N> + Debug,
+{
+}
+
+/// A shorthand way of specifying the result of an ICP algorithm in `N` dimensions,
+/// where the rotation type of the transform is chosen according to `N`.
+pub type ICPResult<T, const N: usize> = Result<
+    ICPSuccess<T, <IsometryAbstractor<T, N> as AbstractIsometry<T, N>>::RotType, N>,
+    ICPError<T, <IsometryAbstractor<T, N> as AbstractIsometry<T, N>>::RotType, N>,
+>;
+
+/// A shorthand way of specifying the result of a single ICP iteration in `N` dimensions,
+/// where the rotation type of the transform is chosen according to `N`.
+pub type ICPIterationResult<T, const N: usize> = Result<
+    ICPIterationStatus<T, N>,
+    ICPError<T, <IsometryAbstractor<T, N> as AbstractIsometry<T, N>>::RotType, N>,
+>;
+
+/// The outcome of a single ICP iteration, see [`icp_iteration`](crate::icp::icp_iteration).
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ICPIterationStatus<T: Scalar, const N: usize> {
+    /// The MSE has reached one of the configured thresholds, running further iterations is unnecessary.
+    Converged {
+        /// The Mean Squared Error after this iteration.
+        mse: T,
+        /// The nearest neighbour in the target point cloud of each source point, as found in this iteration.
+        closest_points: Vec<Point<T, N>>,
+    },
+    /// The transform was updated, but has not converged yet.
+    NotConverged {
+        /// The Mean Squared Error after this iteration.
+        mse: T,
+        /// The nearest neighbour in the target point cloud of each source point, as found in this iteration.
+        closest_points: Vec<Point<T, N>>,
+        /// The centeroid of the transformed source points that were used to update the transform.
+        source_centeroid: Point<T, N>,
+        /// The centeroid of the target points that were used to update the transform.
+        target_centeroid: Point<T, N>,
+    },
+}
+
+impl<T: Scalar + Copy, const N: usize> ICPIterationStatus<T, N> {
+    /// Returns whether the iteration has reached convergence.
+    pub fn has_converged(&self) -> bool {
+        matches!(self, Self::Converged { .. })
+    }
+
+    /// Returns the Mean Squared Error after the iteration.
+    pub fn mse(&self) -> T {
+        match self {
+            Self::Converged { mse, .. } | Self::NotConverged { mse, .. } => *mse,
+        }
+    }
+
+    /// Returns the nearest neighbour in the target point cloud of each source point, as found in the iteration.
+    pub fn closest_points(&self) -> &[Point<T, N>] {
+        match self {
+            Self::Converged { closest_points, .. } | Self::NotConverged { closest_points, .. } => {
+                closest_points
+            }
+        }
+    }
+}
+
+/// The state of an ICP algorithm after a single iteration, as passed to an [`ICPObserver`].
+#[derive(Debug)]
+pub struct ICPIterationState<'a, T: Scalar, R: AbstractRotation<T, N>, const N: usize> {
+    /// The index of the iteration, starting at `0`.
+    pub iteration_num: usize,
+    /// The transform calculated in this iteration.
+    pub transform: &'a Isometry<T, R, N>,
+    /// The Mean Squared Error after this iteration.
+    pub mse: T,
+    /// The source point cloud, not transformed.
+    pub source_points: &'a [Point<T, N>],
+    /// The nearest neighbour in the target point cloud of each point in `source_points`, as found in this iteration.
+    /// Note that this includes correspondences that were rejected according to the [`ICPConfiguration`].
+    pub closest_points: &'a [Point<T, N>],
+}
+
+/// Specifies whether an ICP algorithm should keep running, as requested by an [`ICPObserver`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ICPObserverAction {
+    /// Keep running until convergence, or until running out of iterations.
+    Continue,
+    /// Stop the algorithm immediately, returning an [`ICPError::Stopped`] unless this iteration has converged.
+    Stop,
+}
+
+/// A hook which is called after every iteration of an ICP algorithm,
+/// allowing to drive visualizers, loggers or watchdogs without re-implementing the algorithm.
+/// This is implemented for any closure receiving an [`ICPIterationState`] and returning an [`ICPObserverAction`].
+pub trait ICPObserver<T: Scalar, R: AbstractRotation<T, N>, const N: usize> {
+    /// Called after every iteration of the algorithm.
+    ///
+    /// # Arguments
+    /// * `state`: a reference to an [`ICPIterationState`], describing the state of the algorithm after the iteration.
+    ///
+    /// # Returns
+    /// An [`ICPObserverAction`], specifying whether the algorithm should keep running.
+    fn on_iteration(&mut self, state: &ICPIterationState<T, R, N>) -> ICPObserverAction;
+}
+
+impl<T, R, F, const N: usize> ICPObserver<T, R, N> for F
+where
+    T: Scalar,
+    R: AbstractRotation<T, N>,
+    F: FnMut(&ICPIterationState<T, R, N>) -> ICPObserverAction,
+{
+    fn on_iteration(&mut self, state: &ICPIterationState<T, R, N>) -> ICPObserverAction {
+        self(state)
+    }
+}
+
+/// A struct specifying configuration options for an ICP algorithm.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ICPConfiguration<T> {
     /// Whether to use a KDTree structure to find nearest neighbours, becomes increasingly effective with point cloud growth.
     pub(crate) use_kd_tree: bool,
+    /// How thoroughly the KDTree is searched for nearest neighbours, only used when `use_kd_tree` is `true`.
+    pub(crate) search_mode: SearchMode<T>,
     /// The amount of iterations before giving up and exiting the algorithm.
     pub(crate) max_iterations: usize,
     /// When provided, the algorithm will consider itself converged when the MSE is smaller than the given value, without any more iterations.
     pub(crate) mse_absolute_threshold: Option<T>,
     /// This will specify the interval between iteration MSE's than when reached, will declare ICP convergence.
     pub(crate) mse_interval_threshold: T,
+    /// When provided, correspondences whose points are further apart than this distance are rejected.
+    pub(crate) max_correspondence_distance: Option<T>,
+    /// When provided, only this fraction of the correspondences, with the smallest distances, is kept.
+    pub(crate) trim_ratio: Option<T>,
+    /// Whether to reject correspondences whose target point's nearest source point is not the correspondence's source point.
+    pub(crate) reciprocal_correspondences: bool,
+    /// When provided, correspondences are weighted according to their distance using this kernel.
+    pub(crate) robust_kernel: Option<RobustKernel<T>>,
 }
 
 impl<T: 'static + Copy> ICPConfiguration<T>
@@ -63,9 +411,14 @@ pub fn builder() -> ICPConfigurationBuilder<T> {
         ICPConfigurationBuilder {
             _internal: ICPConfiguration {
                 use_kd_tree: false,
+                search_mode: SearchMode::Exact,
                 max_iterations: 20,
                 mse_absolute_threshold: None,
                 mse_interval_threshold: 0.01.as_(),
+                max_correspondence_distance: None,
+                trim_ratio: None,
+                reciprocal_correspondences: false,
+                robust_kernel: None,
             },
         }
     }
@@ -95,6 +448,23 @@ pub fn with_kd_tree(&self, use_kd_tree: bool) -> Self {
         }
     }
 
+    /// Sets how thoroughly the KD Tree is searched for nearest neighbours, allowing approximate nearest neighbours in exchange for speed.
+    /// This has no effect unless a KD Tree is used, see [`with_kd_tree`](Self::with_kd_tree).
+    ///
+    /// # Arguments
+    /// * `search_mode`: A [`SearchMode`], an approximate mode must have a non-negative epsilon or a positive amount of leaf checks.
+    ///
+    /// # Returns
+    /// A copy of self, with the updated parameters
+    pub fn with_search_mode(&self, search_mode: SearchMode<T>) -> Self {
+        Self {
+            _internal: ICPConfiguration {
+                search_mode,
+                ..self._internal
+            },
+        }
+    }
+
     /// The amount of iterations before giving up and exiting the algorithm.
     ///
     /// # Arguments
@@ -143,6 +513,74 @@ pub fn with_mse_interval_threshold(&self, mse_interval_threshold: T) -> Self {
         }
     }
 
+    /// When provided, correspondences whose points are further apart than the given distance are rejected,
+    /// this is useful when the point clouds only partially overlap.
+    ///
+    /// # Arguments
+    /// * `max_correspondence_distance`: If is [`Some`], sets the maximum distance between the points of an accepted correspondence.
+    ///
+    /// # Returns
+    /// A copy of self, with the updated parameters
+    pub fn with_max_correspondence_distance(&self, max_correspondence_distance: Option<T>) -> Self {
+        Self {
+            _internal: ICPConfiguration {
+                max_correspondence_distance,
+                ..self._internal
+            },
+        }
+    }
+
+    /// When provided, only the given fraction of the correspondences is kept in each iteration,
+    /// choosing the ones with the smallest distances, also known as Trimmed ICP.
+    ///
+    /// # Arguments
+    /// * `trim_ratio`: If is [`Some`], sets the fraction of correspondences to keep, must be larger than `0` and no larger than `1`.
+    ///
+    /// # Returns
+    /// A copy of self, with the updated parameters
+    pub fn with_trim_ratio(&self, trim_ratio: Option<T>) -> Self {
+        Self {
+            _internal: ICPConfiguration {
+                trim_ratio,
+                ..self._internal
+            },
+        }
+    }
+
+    /// Enables rejection of correspondences that are not mutual nearest neighbours,
+    /// i.e. the source point is not the nearest source point to its matched target point.
+    ///
+    /// # Arguments
+    /// * `reciprocal_correspondences`: Whether to only keep mutual nearest neighbours.
+    ///
+    /// # Returns
+    /// A copy of self, with the updated parameters
+    pub fn with_reciprocal_correspondences(&self, reciprocal_correspondences: bool) -> Self {
+        Self {
+            _internal: ICPConfiguration {
+                reciprocal_correspondences,
+                ..self._internal
+            },
+        }
+    }
+
+    /// When provided, each correspondence is weighted by the given [`RobustKernel`], according to its distance.
+    /// The reported MSE is weighted the same way, so down-weighted correspondences also affect the convergence check less.
+    ///
+    /// # Arguments
+    /// * `robust_kernel`: If is [`Some`], sets the kernel used to weigh correspondences.
+    ///
+    /// # Returns
+    /// A copy of self, with the updated parameters
+    pub fn with_robust_kernel(&self, robust_kernel: Option<RobustKernel<T>>) -> Self {
+        Self {
+            _internal: ICPConfiguration {
+                robust_kernel,
+                ..self._internal
+            },
+        }
+    }
+
     /// Generates an [`ICPConfiguration`] from the struct currently contained by the builder
     ///
     /// # Returns
@@ -151,3 +589,55 @@ pub fn build(&self) -> ICPConfiguration<T> {
         self._internal.clone()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_icp_error_display() {
+        use nalgebra::{Isometry2, UnitComplex};
+
+        let err = ICPError::<f32, UnitComplex<f32>, 2>::InvalidConfiguration {
+            reason: "Must have more than one iteration",
+        };
+        assert_eq!(
+            err.to_string(),
+            "Invalid configuration: Must have more than one iteration"
+        );
+
+        let err = ICPError::DidNotConverge {
+            last_transform: Isometry2::identity(),
+            last_mse: 0.5f32,
+            iterations: 10,
+        };
+        assert_eq!(
+            err.to_string(),
+            "Could not converge after 10 iterations, last MSE was 0.5"
+        );
+    }
+
+    #[test]
+    fn test_robust_kernel_weights() {
+        let kernels = [
+            RobustKernel::Huber(1.0),
+            RobustKernel::Cauchy(1.0),
+            RobustKernel::Tukey(1.0),
+            RobustKernel::GemanMcClure(1.0),
+        ];
+
+        // Every kernel fully trusts a perfect correspondence, and weighs down larger residuals
+        for kernel in kernels {
+            assert_eq!(kernel.weight(0.0f64), 1.0);
+            assert!(kernel.weight(2.0) < kernel.weight(0.5));
+        }
+
+        assert_eq!(RobustKernel::Huber(1.0).weight(0.5f64), 1.0);
+        assert_eq!(RobustKernel::Huber(1.0).weight(-4.0f64), 0.25);
+        assert_eq!(RobustKernel::Cauchy(1.0).weight(1.0f64), 0.5);
+        assert_eq!(RobustKernel::Tukey(2.0).weight(1.0f64), 0.5625);
+        assert_eq!(RobustKernel::Tukey(1.0).weight(1.5f64), 0.0);
+        assert_eq!(RobustKernel::GemanMcClure(1.0).weight(1.0f64), 0.25);
+    }
+}
@@ -0,0 +1,204 @@
+// SPDX-License-Identifier: MIT
+/*
+ * Copyright (c) [2023 - Present] Emily Matheys <emilymatt96@gmail.com>
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+use core::fmt::Display;
+use nalgebra::Scalar;
+use num_traits::AsPrimitive;
+use std::io::{BufRead, Write};
+
+use super::{
+    helpers::{
+        assemble_point_cloud, column_names, default_column_names, parse_value, push_row,
+        write_ascii_rows,
+    },
+    types::{IOError, PointCloudData},
+};
+
+/// Reads a point cloud from comma separated values, one point per line.
+/// If the first line is a header, the coordinates are read from the columns named `x`, `y` and (in 3D) `z`,
+/// and all other columns are read as fields named after their header.
+/// Otherwise, the first `N` columns are the coordinates, and any further columns are read as fields named `field_<idx>`.
+///
+/// # Arguments
+/// * `reader`: a [`BufRead`], from which to read the point cloud.
+///
+/// # Generics
+/// * `T`: Either an [`f32`] or [`f64`].
+/// * `N`: A const usize, either `2` or `3`.
+///
+/// # Returns
+/// A [`PointCloudData`] containing the point cloud, or an [`IOError`] if it could not be read.
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument("Read CSV Point Cloud", skip_all, level = "info")
+)]
+pub fn read_csv<T, const N: usize, R>(reader: R) -> Result<PointCloudData<T, N>, IOError>
+where
+    T: Copy + Scalar,
+    f64: AsPrimitive<T>,
+    R: BufRead,
+{
+    let mut header = None;
+    let mut columns = Vec::new();
+    for (line_idx, line) in reader.lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let tokens = line.split(',').map(str::trim).collect::<Vec<_>>();
+        let is_first_line = header.is_none() && columns.is_empty();
+        if is_first_line && tokens.iter().any(|token| token.parse::<f64>().is_err()) {
+            header = Some(tokens.into_iter().map(String::from).collect::<Vec<_>>());
+            continue;
+        }
+
+        let values = tokens
+            .into_iter()
+            .map(|token| parse_value(token, line_idx))
+            .collect::<Result<Vec<_>, _>>()?;
+        if let Some(header) = header
+            .as_ref()
+            .filter(|header| header.len() != values.len())
+        {
+            return Err(IOError::InvalidData {
+                reason: format!(
+                    "line {} has {} values, but the header has {} columns",
+                    line_idx + 1,
+                    values.len(),
+                    header.len()
+                ),
+            });
+        }
+        push_row(&mut columns, values, line_idx)?;
+    }
+
+    let names = match header {
+        Some(header) => header,
+        None => default_column_names::<N>(columns.len())?,
+    };
+    assemble_point_cloud(names, columns)
+}
+
+/// Writes a point cloud as comma separated values, one point per line, with its coordinates followed by its fields.
+/// The first line is a header, containing the name of each column.
+///
+/// # Arguments
+/// * `writer`: a [`Write`], to which to write the point cloud.
+/// * `point_cloud`: a reference to the [`PointCloudData`] to write.
+///
+/// # Generics
+/// * `T`: Either an [`f32`] or [`f64`].
+/// * `N`: A const usize, either `2` or `3`.
+///
+/// # Returns
+/// An [`IOError`] if the point cloud could not be written.
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument("Write CSV Point Cloud", skip_all, level = "info")
+)]
+pub fn write_csv<T, const N: usize, W>(
+    mut writer: W,
+    point_cloud: &PointCloudData<T, N>,
+) -> Result<(), IOError>
+where
+    T: Copy + Display + Scalar,
+    W: Write,
+{
+    writeln!(writer, "{}", column_names(point_cloud)?.join(","))?;
+    write_ascii_rows(&mut writer, point_cloud, ",")
+}
+
+#[cfg(test)]
+mod tests {
+    use nalgebra::{Point2, Point3};
+    use std::io::Cursor;
+
+    use super::*;
+
+    #[test]
+    fn test_read_csv_with_header() {
+        let data = "intensity, z, y, x\n10, 3.0, 2.0, 1.0\n20, 0.5, -1.0, 4.0\n";
+        let point_cloud = read_csv::<f32, 3, _>(Cursor::new(data)).unwrap();
+        assert_eq!(
+            point_cloud.points,
+            [Point3::new(1.0, 2.0, 3.0), Point3::new(4.0, -1.0, 0.5)]
+        );
+        assert_eq!(point_cloud.fields.len(), 1);
+        assert_eq!(
+            point_cloud.field("intensity"),
+            Some([10.0, 20.0].as_slice())
+        );
+
+        assert!(matches!(
+            read_csv::<f32, 3, _>(Cursor::new("x,y,intensity\n1,2,3\n")),
+            Err(IOError::MissingCoordinate { name: "z" })
+        ));
+        assert!(matches!(
+            read_csv::<f32, 2, _>(Cursor::new("x,y\n1,2,3\n")),
+            Err(IOError::InvalidData { .. })
+        ));
+        assert!(read_csv::<f32, 2, _>(Cursor::new("x,y,intensity\n"))
+            .unwrap()
+            .points
+            .is_empty());
+    }
+
+    #[test]
+    fn test_read_csv_without_header() {
+        let point_cloud = read_csv::<f64, 2, _>(Cursor::new("1,2\n3,4\n")).unwrap();
+        assert_eq!(
+            point_cloud.points,
+            [Point2::new(1.0, 2.0), Point2::new(3.0, 4.0)]
+        );
+        assert!(point_cloud.fields.is_empty());
+    }
+
+    #[test]
+    fn test_csv_round_trip() {
+        let point_cloud = PointCloudData::new(Vec::from([
+            Point3::new(1.0, 2.0, 3.0),
+            Point3::new(-0.125, 0.0, 8.5),
+        ]))
+        .with_field("intensity", Vec::from([7.0, 9.0]))
+        .with_field("ring", Vec::from([1.0, 2.0]));
+
+        let mut buffer = Vec::new();
+        write_csv(&mut buffer, &point_cloud).unwrap();
+        assert_eq!(
+            String::from_utf8_lossy(&buffer),
+            "x,y,z,intensity,ring\n1,2,3,7,1\n-0.125,0,8.5,9,2\n"
+        );
+        assert_eq!(
+            read_csv::<f64, 3, _>(Cursor::new(buffer)).unwrap(),
+            point_cloud
+        );
+
+        // Every field must have a value for each point
+        let invalid_point_cloud = point_cloud.with_field("timestamp", Vec::from([0.0]));
+        assert!(matches!(
+            write_csv(&mut Vec::new(), &invalid_point_cloud),
+            Err(IOError::InvalidData { .. })
+        ));
+    }
+}
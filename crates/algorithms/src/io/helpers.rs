@@ -0,0 +1,287 @@
+// SPDX-License-Identifier: MIT
+/*
+ * Copyright (c) [2023 - Present] Emily Matheys <emilymatt96@gmail.com>
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+use core::fmt::Display;
+use nalgebra::{Point, Scalar};
+use num_traits::AsPrimitive;
+use std::io::Write;
+
+use super::types::{IOError, PointCloudData, PointField};
+
+/// The names of the coordinate fields, the first `N` of which are used for `N` dimensional points.
+const COORDINATE_NAMES: [&str; 3] = ["x", "y", "z"];
+
+/// The numeric types which may appear in the binary data of a file.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum ScalarType {
+    I8,
+    U8,
+    I16,
+    U16,
+    I32,
+    U32,
+    I64,
+    U64,
+    F32,
+    F64,
+}
+
+macro_rules! read_from_bytes {
+    ($ty:ty, $bytes:expr, $big_endian:expr) => {{
+        let bytes = $bytes
+            .try_into()
+            .expect("Slice length must match the scalar type");
+        (if $big_endian {
+            <$ty>::from_be_bytes(bytes)
+        } else {
+            <$ty>::from_le_bytes(bytes)
+        }) as f64
+    }};
+}
+
+impl ScalarType {
+    /// Returns the floating point type with the same size as `T`.
+    pub(crate) fn native<T>() -> Self {
+        if core::mem::size_of::<T>() == 4 {
+            Self::F32
+        } else {
+            Self::F64
+        }
+    }
+
+    /// Returns the size of the type, in bytes.
+    pub(crate) fn size(self) -> usize {
+        match self {
+            Self::I8 | Self::U8 => 1,
+            Self::I16 | Self::U16 => 2,
+            Self::I32 | Self::U32 | Self::F32 => 4,
+            Self::I64 | Self::U64 | Self::F64 => 8,
+        }
+    }
+
+    /// Converts a value of this type, encoded in `bytes`, into an [`f64`].
+    pub(crate) fn read(self, bytes: &[u8], big_endian: bool) -> f64 {
+        match self {
+            Self::I8 => read_from_bytes!(i8, bytes, big_endian),
+            Self::U8 => read_from_bytes!(u8, bytes, big_endian),
+            Self::I16 => read_from_bytes!(i16, bytes, big_endian),
+            Self::U16 => read_from_bytes!(u16, bytes, big_endian),
+            Self::I32 => read_from_bytes!(i32, bytes, big_endian),
+            Self::U32 => read_from_bytes!(u32, bytes, big_endian),
+            Self::I64 => read_from_bytes!(i64, bytes, big_endian),
+            Self::U64 => read_from_bytes!(u64, bytes, big_endian),
+            Self::F32 => read_from_bytes!(f32, bytes, big_endian),
+            Self::F64 => read_from_bytes!(f64, bytes, big_endian),
+        }
+    }
+}
+
+/// Writes a value in the little-endian binary encoding of its native floating point type.
+pub(crate) fn write_binary_value<T, W>(writer: &mut W, value: T) -> Result<(), IOError>
+where
+    T: AsPrimitive<f64>,
+    W: Write,
+{
+    let value: f64 = value.as_();
+    match ScalarType::native::<T>() {
+        ScalarType::F32 => writer.write_all(&(value as f32).to_le_bytes())?,
+        _ => writer.write_all(&value.to_le_bytes())?,
+    }
+    Ok(())
+}
+
+/// Parses a single ASCII value, reporting the line it appeared in on failure.
+pub(crate) fn parse_value(token: &str, line_idx: usize) -> Result<f64, IOError> {
+    token.parse().map_err(|_| IOError::InvalidData {
+        reason: format!("could not parse `{token}` in line {}", line_idx + 1),
+    })
+}
+
+/// Appends the values of a single row to their columns, making sure every row has the same amount of values.
+pub(crate) fn push_row(
+    columns: &mut Vec<Vec<f64>>,
+    values: Vec<f64>,
+    line_idx: usize,
+) -> Result<(), IOError> {
+    if columns.is_empty() {
+        columns.resize_with(values.len(), Vec::new);
+    }
+
+    if values.len() != columns.len() {
+        return Err(IOError::InvalidData {
+            reason: format!(
+                "line {} has {} values, expected {}",
+                line_idx + 1,
+                values.len(),
+                columns.len()
+            ),
+        });
+    }
+
+    columns
+        .iter_mut()
+        .zip(values)
+        .for_each(|(column, value)| column.push(value));
+    Ok(())
+}
+
+/// Names `num_columns` unnamed columns, where the first `N` columns are the coordinates and the rest are named `field_<idx>`.
+/// If there are no columns at all, only the coordinates are named, so that an empty point cloud can still be assembled.
+pub(crate) fn default_column_names<const N: usize>(
+    num_columns: usize,
+) -> Result<Vec<String>, IOError> {
+    let num_columns = if num_columns == 0 { N } else { num_columns };
+    Ok(coordinate_names::<N>()?
+        .iter()
+        .take(num_columns)
+        .map(|name| name.to_string())
+        .chain((N..num_columns).map(|column_idx| format!("field_{}", column_idx - N)))
+        .collect())
+}
+
+/// Assembles a point cloud from its columns, taking the coordinates from the columns named after them.
+/// All other columns become extra fields, except for padding fields.
+/// If there are no columns at all, the point cloud is empty.
+pub(crate) fn assemble_point_cloud<T, const N: usize>(
+    names: Vec<String>,
+    mut columns: Vec<Vec<f64>>,
+) -> Result<PointCloudData<T, N>, IOError>
+where
+    T: Copy + Scalar,
+    f64: AsPrimitive<T>,
+{
+    if columns.is_empty() {
+        columns.resize_with(names.len(), Vec::new);
+    }
+
+    let coordinate_names = coordinate_names::<N>()?;
+    let coordinate_columns = coordinate_names
+        .iter()
+        .map(|coordinate_name| {
+            names
+                .iter()
+                .position(|name| name.eq_ignore_ascii_case(coordinate_name))
+                .ok_or(IOError::MissingCoordinate {
+                    name: coordinate_name,
+                })
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let num_points = columns.first().map_or(0, Vec::len);
+    let points = (0..num_points)
+        .map(|point_idx| {
+            Point::from(core::array::from_fn(|idx| {
+                columns[coordinate_columns[idx]][point_idx].as_()
+            }))
+        })
+        .collect();
+
+    let fields = names
+        .into_iter()
+        .zip(columns.iter_mut())
+        .enumerate()
+        .filter(|(column_idx, _)| !coordinate_columns.contains(column_idx))
+        .map(|(_, (name, column))| PointField {
+            name,
+            values: column.drain(..).map(AsPrimitive::as_).collect(),
+        })
+        .collect();
+
+    Ok(PointCloudData { points, fields })
+}
+
+/// Returns the names of all columns of a point cloud, starting with its coordinates,
+/// after making sure that every field has a value for each point.
+pub(crate) fn column_names<T, const N: usize>(
+    point_cloud: &PointCloudData<T, N>,
+) -> Result<Vec<String>, IOError>
+where
+    T: Scalar,
+{
+    if let Some(field) = point_cloud
+        .fields
+        .iter()
+        .find(|field| field.values.len() != point_cloud.points.len())
+    {
+        return Err(IOError::InvalidData {
+            reason: format!(
+                "field `{}` has {} values, but the point cloud has {} points",
+                field.name,
+                field.values.len(),
+                point_cloud.points.len()
+            ),
+        });
+    }
+
+    Ok(coordinate_names::<N>()?
+        .iter()
+        .map(|name| name.to_string())
+        .chain(point_cloud.fields.iter().map(|field| field.name.clone()))
+        .collect())
+}
+
+/// Iterates over the values of every column of a single point, starting with its coordinates.
+pub(crate) fn row_values<T, const N: usize>(
+    point_cloud: &PointCloudData<T, N>,
+    point_idx: usize,
+) -> impl Iterator<Item = T> + '_
+where
+    T: Copy + Scalar,
+{
+    point_cloud.points[point_idx].iter().copied().chain(
+        point_cloud
+            .fields
+            .iter()
+            .map(move |field| field.values[point_idx]),
+    )
+}
+
+/// Writes the values of every point as a line of text, with the values separated by `delimiter`.
+pub(crate) fn write_ascii_rows<T, const N: usize, W>(
+    writer: &mut W,
+    point_cloud: &PointCloudData<T, N>,
+    delimiter: &str,
+) -> Result<(), IOError>
+where
+    T: Copy + Display + Scalar,
+    W: Write,
+{
+    for point_idx in 0..point_cloud.points.len() {
+        let line = row_values(point_cloud, point_idx)
+            .map(|value| value.to_string())
+            .collect::<Vec<_>>()
+            .join(delimiter);
+        writeln!(writer, "{line}")?;
+    }
+    Ok(())
+}
+
+/// Returns the names of the coordinate fields of `N` dimensional points.
+fn coordinate_names<const N: usize>() -> Result<&'static [&'static str], IOError> {
+    match N {
+        2 | 3 => Ok(&COORDINATE_NAMES[..N]),
+        _ => Err(IOError::Unsupported {
+            reason: format!("point clouds must be 2 or 3 dimensional, not {N}"),
+        }),
+    }
+}
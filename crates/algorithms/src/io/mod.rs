@@ -0,0 +1,175 @@
+// SPDX-License-Identifier: MIT
+/*
+ * Copyright (c) [2023 - Present] Emily Matheys <emilymatt96@gmail.com>
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+use core::fmt::Display;
+use nalgebra::Scalar;
+use num_traits::AsPrimitive;
+use std::{
+    fs::File,
+    io::{BufReader, BufWriter, Write},
+    path::Path,
+};
+use types::{Encoding, IOError, PointCloudData, PointCloudFormat};
+
+/// Comma separated values, with an optional header.
+pub mod csv;
+
+pub(crate) mod helpers;
+
+/// The Point Cloud Library's PCD format.
+pub mod pcd;
+
+/// The Polygon File Format, also known as the Stanford Triangle Format.
+pub mod ply;
+
+/// Structs in use as part of the public API of the point cloud readers and writers.
+pub mod types;
+
+/// Whitespace separated values, without a header.
+pub mod xyz;
+
+/// Reads a point cloud from a file, whose format is determined by its extension, see [`PointCloudFormat::from_path`].
+///
+/// # Arguments
+/// * `path`: the path of the file.
+///
+/// # Generics
+/// * `T`: Either an [`f32`] or [`f64`].
+/// * `N`: A const usize, either `2` or `3`.
+///
+/// # Returns
+/// A [`PointCloudData`] containing the point cloud, or an [`IOError`] if it could not be read.
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument("Read Point Cloud File", skip_all, level = "info")
+)]
+pub fn read_point_cloud_file<T, const N: usize>(
+    path: impl AsRef<Path>,
+) -> Result<PointCloudData<T, N>, IOError>
+where
+    T: Copy + Scalar,
+    f64: AsPrimitive<T>,
+{
+    let format = PointCloudFormat::from_path(&path).ok_or(IOError::UnknownFormat)?;
+    let reader = BufReader::new(File::open(path)?);
+    match format {
+        PointCloudFormat::Pcd => pcd::read_pcd(reader),
+        PointCloudFormat::Ply => ply::read_ply(reader),
+        PointCloudFormat::Xyz => xyz::read_xyz(reader),
+        PointCloudFormat::Csv => csv::read_csv(reader),
+    }
+}
+
+/// Writes a point cloud to a file, whose format is determined by its extension, see [`PointCloudFormat::from_path`].
+///
+/// # Arguments
+/// * `path`: the path of the file, which is created or truncated.
+/// * `point_cloud`: a reference to the [`PointCloudData`] to write.
+/// * `encoding`: the [`Encoding`] of the point data, ignored by formats which only support ASCII.
+///
+/// # Generics
+/// * `T`: Either an [`f32`] or [`f64`].
+/// * `N`: A const usize, either `2` or `3`.
+///
+/// # Returns
+/// An [`IOError`] if the point cloud could not be written.
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument("Write Point Cloud File", skip_all, level = "info")
+)]
+pub fn write_point_cloud_file<T, const N: usize>(
+    path: impl AsRef<Path>,
+    point_cloud: &PointCloudData<T, N>,
+    encoding: Encoding,
+) -> Result<(), IOError>
+where
+    T: AsPrimitive<f64> + Copy + Display + Scalar,
+{
+    let format = PointCloudFormat::from_path(&path).ok_or(IOError::UnknownFormat)?;
+    let mut writer = BufWriter::new(File::create(path)?);
+    match format {
+        PointCloudFormat::Pcd => pcd::write_pcd(&mut writer, point_cloud, encoding),
+        PointCloudFormat::Ply => ply::write_ply(&mut writer, point_cloud, encoding),
+        PointCloudFormat::Xyz => xyz::write_xyz(&mut writer, point_cloud),
+        PointCloudFormat::Csv => csv::write_csv(&mut writer, point_cloud),
+    }?;
+    writer.flush()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use nalgebra::Point3;
+
+    use super::*;
+
+    #[test]
+    fn test_point_cloud_format_from_path() {
+        assert_eq!(
+            PointCloudFormat::from_path("scans/000001.PCD"),
+            Some(PointCloudFormat::Pcd)
+        );
+        assert_eq!(
+            PointCloudFormat::from_path("mesh.ply"),
+            Some(PointCloudFormat::Ply)
+        );
+        assert_eq!(
+            PointCloudFormat::from_path("cloud.xyz"),
+            Some(PointCloudFormat::Xyz)
+        );
+        assert_eq!(
+            PointCloudFormat::from_path("cloud.csv"),
+            Some(PointCloudFormat::Csv)
+        );
+        assert_eq!(PointCloudFormat::from_path("cloud.bin"), None);
+        assert_eq!(PointCloudFormat::from_path("cloud"), None);
+    }
+
+    #[test]
+    fn test_point_cloud_file_round_trip() {
+        let point_cloud = PointCloudData::new(Vec::from([
+            Point3::new(1.0, 2.0, 3.0),
+            Point3::new(-4.0, 5.5, 0.0),
+        ]))
+        .with_field("intensity", Vec::from([1.0, 0.0]));
+
+        let directory =
+            std::env::temp_dir().join(format!("mapping-algorithms-io-test-{}", std::process::id()));
+        std::fs::create_dir_all(&directory).unwrap();
+        for file_name in ["cloud.pcd", "cloud.ply", "cloud.csv"] {
+            let path = directory.join(file_name);
+            write_point_cloud_file(&path, &point_cloud, Encoding::Binary).unwrap();
+            assert_eq!(read_point_cloud_file::<f64, 3>(&path).unwrap(), point_cloud);
+        }
+        std::fs::remove_dir_all(&directory).unwrap();
+
+        assert!(matches!(
+            write_point_cloud_file(directory.join("cloud.bin"), &point_cloud, Encoding::Ascii),
+            Err(IOError::UnknownFormat)
+        ));
+        assert!(matches!(
+            read_point_cloud_file::<f64, 3>(directory.join("missing.pcd")),
+            Err(IOError::Io(_))
+        ));
+    }
+}
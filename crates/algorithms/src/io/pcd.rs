@@ -0,0 +1,506 @@
+// SPDX-License-Identifier: MIT
+/*
+ * Copyright (c) [2023 - Present] Emily Matheys <emilymatt96@gmail.com>
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+use core::fmt::Display;
+use nalgebra::Scalar;
+use num_traits::AsPrimitive;
+use std::io::{BufRead, Write};
+
+use super::{
+    helpers::{
+        assemble_point_cloud, column_names, parse_value, push_row, row_values, write_ascii_rows,
+        write_binary_value, ScalarType,
+    },
+    types::{Encoding, IOError, PointCloudData},
+};
+
+/// The name used for padding fields, which are skipped when reading.
+const PADDING_FIELD_NAME: &str = "_";
+
+/// The maximal size of a single point in bytes, points declared larger than this are rejected.
+const MAX_POINT_SIZE: usize = 1 << 20;
+
+fn invalid_header<T>(reason: impl Into<String>) -> Result<T, IOError> {
+    Err(IOError::InvalidHeader {
+        reason: reason.into(),
+    })
+}
+
+fn parse_header_values<V: core::str::FromStr>(
+    key: &str,
+    tokens: core::str::SplitWhitespace,
+) -> Result<Vec<V>, IOError> {
+    tokens
+        .map(|token| {
+            token.parse().map_err(|_| IOError::InvalidHeader {
+                reason: format!("could not parse `{token}` in {key}"),
+            })
+        })
+        .collect()
+}
+
+fn scalar_type_from_pcd(type_name: &str, size: usize) -> Option<ScalarType> {
+    match (type_name, size) {
+        ("I", 1) => Some(ScalarType::I8),
+        ("U", 1) => Some(ScalarType::U8),
+        ("I", 2) => Some(ScalarType::I16),
+        ("U", 2) => Some(ScalarType::U16),
+        ("I", 4) => Some(ScalarType::I32),
+        ("U", 4) => Some(ScalarType::U32),
+        ("I", 8) => Some(ScalarType::I64),
+        ("U", 8) => Some(ScalarType::U64),
+        ("F", 4) => Some(ScalarType::F32),
+        ("F", 8) => Some(ScalarType::F64),
+        _ => None,
+    }
+}
+
+/// Reads a point cloud in the Point Cloud Library's PCD format, in either `ascii` or `binary` encoding.
+/// The coordinates are read from the fields named `x`, `y` and (in 3D) `z`, all other fields are read as extra fields,
+/// where fields with a `COUNT` larger than `1` are split into fields named `<name>_<idx>`, and padding fields named `_` are skipped.
+///
+/// # Arguments
+/// * `reader`: a [`BufRead`], from which to read the point cloud.
+///
+/// # Generics
+/// * `T`: Either an [`f32`] or [`f64`].
+/// * `N`: A const usize, either `2` or `3`.
+///
+/// # Returns
+/// A [`PointCloudData`] containing the point cloud, or an [`IOError`] if it could not be read.
+///
+/// # Warnings
+/// * The `binary_compressed` encoding is not supported.
+/// * Headers declaring points larger than 1 MiB are rejected as invalid.
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument("Read PCD Point Cloud", skip_all, level = "info")
+)]
+pub fn read_pcd<T, const N: usize, R>(mut reader: R) -> Result<PointCloudData<T, N>, IOError>
+where
+    T: Copy + Scalar,
+    f64: AsPrimitive<T>,
+    R: BufRead,
+{
+    let mut names = Vec::<String>::new();
+    let mut sizes = Vec::<usize>::new();
+    let mut types = Vec::<String>::new();
+    let mut counts = None::<Vec<usize>>;
+    let mut width = None::<usize>;
+    let mut height = None::<usize>;
+    let mut num_points = None::<usize>;
+    let mut line_idx = 0;
+    let encoding = loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line)? == 0 {
+            return invalid_header("missing DATA line");
+        }
+        line_idx += 1;
+
+        let mut tokens = line.split_whitespace();
+        let Some(key) = tokens.next().filter(|key| !key.starts_with('#')) else {
+            continue;
+        };
+        match key.to_ascii_uppercase().as_str() {
+            "VERSION" | "VIEWPOINT" => {}
+            "FIELDS" => names = tokens.map(String::from).collect(),
+            "SIZE" => sizes = parse_header_values(key, tokens)?,
+            "TYPE" => types = tokens.map(String::from).collect(),
+            "COUNT" => counts = Some(parse_header_values(key, tokens)?),
+            "WIDTH" => width = parse_header_values(key, tokens)?.first().copied(),
+            "HEIGHT" => height = parse_header_values(key, tokens)?.first().copied(),
+            "POINTS" => num_points = parse_header_values(key, tokens)?.first().copied(),
+            "DATA" => break tokens.next().unwrap_or_default().to_ascii_lowercase(),
+            _ => return invalid_header(format!("unknown key `{key}`")),
+        }
+    };
+
+    let counts = counts.unwrap_or_else(|| names.iter().map(|_| 1).collect());
+    if names.is_empty()
+        || sizes.len() != names.len()
+        || types.len() != names.len()
+        || counts.len() != names.len()
+    {
+        return invalid_header(
+            "FIELDS, SIZE, TYPE and COUNT must have the same, non-zero, amount of values",
+        );
+    }
+    let num_points = match (num_points, width.zip(height)) {
+        (Some(num_points), _) => num_points,
+        (None, Some((width, height))) => {
+            width
+                .checked_mul(height)
+                .ok_or_else(|| IOError::InvalidHeader {
+                    reason: format!(
+                        "WIDTH {width} and HEIGHT {height} overflow the amount of points"
+                    ),
+                })?
+        }
+        (None, None) => return invalid_header("missing POINTS"),
+    };
+
+    let mut column_names = Vec::new();
+    // The type of every value in a point, and whether it is read into a column or skipped as padding
+    let mut value_types = Vec::new();
+    let mut point_size = 0_usize;
+    for (((name, size), type_name), count) in names.into_iter().zip(sizes).zip(types).zip(counts) {
+        let Some(scalar_type) = scalar_type_from_pcd(&type_name, size) else {
+            return invalid_header(format!(
+                "unknown type `{type_name}` of size {size} for field `{name}`"
+            ));
+        };
+        point_size = size
+            .checked_mul(count)
+            .and_then(|field_size| point_size.checked_add(field_size))
+            .filter(|point_size| *point_size <= MAX_POINT_SIZE)
+            .ok_or_else(|| IOError::InvalidHeader {
+                reason: format!(
+                    "COUNT {count} of field `{name}` exceeds the maximal point size of {MAX_POINT_SIZE} bytes"
+                ),
+            })?;
+
+        let is_padding = name == PADDING_FIELD_NAME;
+        for element_idx in 0..count {
+            if !is_padding {
+                column_names.push(if count == 1 {
+                    name.clone()
+                } else {
+                    format!("{name}_{element_idx}")
+                });
+            }
+            value_types.push((scalar_type, !is_padding));
+        }
+    }
+    if column_names.is_empty() {
+        return invalid_header(
+            "COUNT must contain at least one non-zero value for a non-padding field",
+        );
+    }
+
+    let mut columns = Vec::new();
+    match encoding.as_str() {
+        "ascii" => {
+            let mut lines = reader.lines();
+            while columns.first().map_or(0, Vec::len) < num_points {
+                let Some(line) = lines.next().transpose()? else {
+                    return Err(IOError::InvalidData {
+                        reason: format!("expected {num_points} points, found fewer"),
+                    });
+                };
+                line_idx += 1;
+                if line.trim().is_empty() {
+                    continue;
+                }
+
+                let values = line
+                    .split_whitespace()
+                    .map(|token| parse_value(token, line_idx - 1))
+                    .collect::<Result<Vec<_>, _>>()?;
+                if values.len() != value_types.len() {
+                    return Err(IOError::InvalidData {
+                        reason: format!(
+                            "line {line_idx} has {} values, expected {}",
+                            values.len(),
+                            value_types.len()
+                        ),
+                    });
+                }
+                let values = values
+                    .into_iter()
+                    .zip(value_types.iter())
+                    .filter(|(_, (_, is_column))| *is_column)
+                    .map(|(value, _)| value)
+                    .collect();
+                push_row(&mut columns, values, line_idx - 1)?;
+            }
+        }
+        "binary" => {
+            let mut offset = 0;
+            let column_layout = value_types
+                .iter()
+                .filter_map(|(scalar_type, is_column)| {
+                    let value_offset = offset;
+                    offset += scalar_type.size();
+                    is_column.then_some((*scalar_type, value_offset))
+                })
+                .collect::<Vec<_>>();
+            let mut point_data = vec![0; point_size];
+            columns.resize_with(column_layout.len(), Vec::new);
+            for _ in 0..num_points {
+                reader.read_exact(&mut point_data)?;
+                for (column, (scalar_type, offset)) in columns.iter_mut().zip(column_layout.iter())
+                {
+                    column.push(
+                        scalar_type.read(&point_data[*offset..*offset + scalar_type.size()], false),
+                    );
+                }
+            }
+        }
+        "binary_compressed" => {
+            return Err(IOError::Unsupported {
+                reason: String::from("binary_compressed PCD data"),
+            })
+        }
+        _ => return invalid_header(format!("unknown DATA encoding `{encoding}`")),
+    }
+
+    assemble_point_cloud(column_names, columns)
+}
+
+/// Writes a point cloud in the Point Cloud Library's PCD format, as an unorganized point cloud.
+/// Every field is written as a floating point field of the same size as `T`.
+///
+/// # Arguments
+/// * `writer`: a [`Write`], to which to write the point cloud.
+/// * `point_cloud`: a reference to the [`PointCloudData`] to write.
+/// * `encoding`: the [`Encoding`] of the point data.
+///
+/// # Generics
+/// * `T`: Either an [`f32`] or [`f64`].
+/// * `N`: A const usize, either `2` or `3`.
+///
+/// # Returns
+/// An [`IOError`] if the point cloud could not be written.
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument("Write PCD Point Cloud", skip_all, level = "info")
+)]
+pub fn write_pcd<T, const N: usize, W>(
+    mut writer: W,
+    point_cloud: &PointCloudData<T, N>,
+    encoding: Encoding,
+) -> Result<(), IOError>
+where
+    T: AsPrimitive<f64> + Copy + Display + Scalar,
+    W: Write,
+{
+    let names = column_names(point_cloud)?;
+    let size = ScalarType::native::<T>().size();
+    let repeat = |value: &str| vec![value; names.len()].join(" ");
+    writeln!(writer, "# .PCD v0.7 - Point Cloud Data file format")?;
+    writeln!(writer, "VERSION 0.7")?;
+    writeln!(writer, "FIELDS {}", names.join(" "))?;
+    writeln!(writer, "SIZE {}", repeat(&size.to_string()))?;
+    writeln!(writer, "TYPE {}", repeat("F"))?;
+    writeln!(writer, "COUNT {}", repeat("1"))?;
+    writeln!(writer, "WIDTH {}", point_cloud.points.len())?;
+    writeln!(writer, "HEIGHT 1")?;
+    writeln!(writer, "VIEWPOINT 0 0 0 1 0 0 0")?;
+    writeln!(writer, "POINTS {}", point_cloud.points.len())?;
+
+    match encoding {
+        Encoding::Ascii => {
+            writeln!(writer, "DATA ascii")?;
+            write_ascii_rows(&mut writer, point_cloud, " ")
+        }
+        Encoding::Binary => {
+            writeln!(writer, "DATA binary")?;
+            for point_idx in 0..point_cloud.points.len() {
+                for value in row_values(point_cloud, point_idx) {
+                    write_binary_value(&mut writer, value)?;
+                }
+            }
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use nalgebra::{Point2, Point3};
+    use std::io::Cursor;
+
+    use super::*;
+
+    const ASCII_PCD: &str = "# .PCD v0.7 - Point Cloud Data file format
+VERSION 0.7
+FIELDS x y z intensity normal _
+SIZE 4 4 4 2 4 4
+TYPE F F F U F F
+COUNT 1 1 1 1 3 1
+WIDTH 2
+HEIGHT 1
+VIEWPOINT 0 0 0 1 0 0 0
+POINTS 2
+DATA ascii
+1.0 2.0 3.0 100 1 2 3 0
+-1.5 0.5 nan 200 4 5 6 0
+";
+
+    #[test]
+    fn test_read_ascii_pcd() {
+        let point_cloud = read_pcd::<f32, 3, _>(Cursor::new(ASCII_PCD)).unwrap();
+        assert_eq!(point_cloud.points.len(), 2);
+        assert_eq!(point_cloud.points[0], Point3::new(1.0, 2.0, 3.0));
+        assert!(point_cloud.points[1].z.is_nan());
+        assert_eq!(
+            point_cloud
+                .fields
+                .iter()
+                .map(|field| field.name.as_str())
+                .collect::<Vec<_>>(),
+            ["intensity", "normal_0", "normal_1", "normal_2"]
+        );
+        assert_eq!(
+            point_cloud.field("intensity"),
+            Some([100.0, 200.0].as_slice())
+        );
+        assert_eq!(point_cloud.field("normal_2"), Some([3.0, 6.0].as_slice()));
+
+        // The z coordinate is an extra field in 2D
+        let point_cloud = read_pcd::<f32, 2, _>(Cursor::new(ASCII_PCD)).unwrap();
+        assert_eq!(point_cloud.points[1], Point2::new(-1.5, 0.5));
+        assert_eq!(point_cloud.field("z").map(|values| values[0]), Some(3.0));
+    }
+
+    #[test]
+    fn test_read_binary_pcd() {
+        let mut data = Vec::from(
+            "VERSION .7\nFIELDS x y z ring\nSIZE 8 8 8 2\nTYPE F F F U\nWIDTH 1\nHEIGHT 1\nDATA binary\n"
+                .as_bytes(),
+        );
+        for value in [0.25f64, -8.0, 1024.5] {
+            data.extend(value.to_le_bytes());
+        }
+        data.extend(7u16.to_le_bytes());
+
+        let point_cloud = read_pcd::<f64, 3, _>(Cursor::new(data)).unwrap();
+        assert_eq!(point_cloud.points, [Point3::new(0.25, -8.0, 1024.5)]);
+        assert_eq!(point_cloud.field("ring"), Some([7.0].as_slice()));
+    }
+
+    #[test]
+    fn test_read_padded_pcd() {
+        let point_cloud = read_pcd::<f32, 3, _>(Cursor::new(
+            "FIELDS x y z _ intensity\nSIZE 4 4 4 1 4\nTYPE F F F U F\nCOUNT 1 1 1 4 1\nPOINTS 1\nDATA ascii\n1 2 3 0 0 0 0 0.5\n",
+        ))
+        .unwrap();
+        assert_eq!(point_cloud.points, [Point3::new(1.0, 2.0, 3.0)]);
+        assert_eq!(point_cloud.fields.len(), 1);
+        assert_eq!(point_cloud.field("intensity"), Some([0.5].as_slice()));
+
+        let mut data = Vec::from(
+            "FIELDS x y z _\nSIZE 4 4 4 1\nTYPE F F F U\nCOUNT 1 1 1 4\nPOINTS 1\nDATA binary\n"
+                .as_bytes(),
+        );
+        for value in [0.25f32, -8.0, 16.5] {
+            data.extend(value.to_le_bytes());
+        }
+        data.extend([0xFF; 4]);
+
+        let point_cloud = read_pcd::<f32, 3, _>(Cursor::new(data)).unwrap();
+        assert_eq!(point_cloud.points, [Point3::new(0.25, -8.0, 16.5)]);
+        assert!(point_cloud.fields.is_empty());
+    }
+
+    #[test]
+    fn test_read_invalid_pcd() {
+        assert!(matches!(
+            read_pcd::<f32, 3, _>(Cursor::new(
+                "FIELDS x y z\nSIZE 4 4 4\nTYPE F F F\nPOINTS 1\n"
+            )),
+            Err(IOError::InvalidHeader { .. })
+        ));
+        assert!(matches!(
+            read_pcd::<f32, 3, _>(Cursor::new(
+                "FIELDS x y z\nSIZE 4 4 4\nTYPE F F F\nPOINTS 1\nDATA binary_compressed\n"
+            )),
+            Err(IOError::Unsupported { .. })
+        ));
+        assert!(matches!(
+            read_pcd::<f32, 3, _>(Cursor::new(
+                "FIELDS x y z\nSIZE 4 4 4\nTYPE F F F\nPOINTS 2\nDATA ascii\n1 2 3\n"
+            )),
+            Err(IOError::InvalidData { .. })
+        ));
+        assert!(matches!(
+            read_pcd::<f32, 3, _>(Cursor::new(
+                "FIELDS x y z\nSIZE 4 4 4\nTYPE F F F\nPOINTS 1\nDATA binary\n\0\0"
+            )),
+            Err(IOError::Io(_))
+        ));
+    }
+
+    #[test]
+    fn test_read_malformed_pcd_header() {
+        assert!(matches!(
+            read_pcd::<f32, 3, _>(Cursor::new(
+                "FIELDS x y z\nSIZE 4 4 4\nTYPE F F F\nCOUNT 0 0 0\nPOINTS 1\nDATA binary\n"
+            )),
+            Err(IOError::InvalidHeader { .. })
+        ));
+        assert!(matches!(
+            read_pcd::<f32, 3, _>(Cursor::new(
+                "FIELDS x y z\nSIZE 4 4 4\nTYPE F F F\nWIDTH 18446744073709551615\nHEIGHT 2\nDATA binary\n"
+            )),
+            Err(IOError::InvalidHeader { .. })
+        ));
+        // A huge element count must fail on the header, rather than on allocation
+        assert!(matches!(
+            read_pcd::<f32, 3, _>(Cursor::new(
+                "FIELDS x y z _\nSIZE 4 4 4 1\nTYPE F F F U\nCOUNT 1 1 1 18446744073709551615\nPOINTS 1\nDATA binary\n"
+            )),
+            Err(IOError::InvalidHeader { .. })
+        ));
+        assert!(matches!(
+            read_pcd::<f32, 3, _>(Cursor::new(
+                "FIELDS x y z normal\nSIZE 4 4 4 4\nTYPE F F F F\nCOUNT 1 1 1 1000000000\nPOINTS 0\nDATA ascii\n"
+            )),
+            Err(IOError::InvalidHeader { .. })
+        ));
+        // A huge point count must fail on the missing data, rather than on allocation
+        assert!(matches!(
+            read_pcd::<f32, 3, _>(Cursor::new(
+                "FIELDS x y z\nSIZE 4 4 4\nTYPE F F F\nPOINTS 18446744073709551615\nDATA binary\n"
+            )),
+            Err(IOError::Io(_))
+        ));
+    }
+
+    #[test]
+    fn test_pcd_round_trip() {
+        let point_cloud = PointCloudData::new(Vec::from([
+            Point3::new(1.0f32, 2.0, 3.0),
+            Point3::new(-0.1, 0.2, -0.3),
+        ]))
+        .with_field("intensity", Vec::from([0.5, 0.75]));
+
+        for encoding in [Encoding::Ascii, Encoding::Binary] {
+            let mut buffer = Vec::new();
+            write_pcd(&mut buffer, &point_cloud, encoding).unwrap();
+            assert_eq!(
+                read_pcd::<f32, 3, _>(Cursor::new(buffer)).unwrap(),
+                point_cloud
+            );
+        }
+
+        let point_cloud = PointCloudData::new(Vec::from([Point2::new(1.0 / 3.0, 2.0f64)]));
+        let mut buffer = Vec::new();
+        write_pcd(&mut buffer, &point_cloud, Encoding::Binary).unwrap();
+        assert!(String::from_utf8_lossy(&buffer).contains("SIZE 8 8\n"));
+        assert_eq!(
+            read_pcd::<f64, 2, _>(Cursor::new(buffer)).unwrap(),
+            point_cloud
+        );
+    }
+}
@@ -0,0 +1,472 @@
+// SPDX-License-Identifier: MIT
+/*
+ * Copyright (c) [2023 - Present] Emily Matheys <emilymatt96@gmail.com>
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+use core::fmt::Display;
+use nalgebra::Scalar;
+use num_traits::AsPrimitive;
+use std::io::{self, BufRead, Read, Write};
+
+use super::{
+    helpers::{
+        assemble_point_cloud, column_names, parse_value, push_row, row_values, write_ascii_rows,
+        write_binary_value, ScalarType,
+    },
+    types::{Encoding, IOError, PointCloudData},
+};
+
+/// The name of the element containing the points.
+const VERTEX_ELEMENT_NAME: &str = "vertex";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum PlyFormat {
+    Ascii,
+    BinaryLittleEndian,
+    BinaryBigEndian,
+}
+
+/// An element declared in the header, with a [`None`] property type for list properties.
+#[derive(Debug)]
+struct PlyElement {
+    name: String,
+    count: usize,
+    properties: Vec<(String, Option<ScalarType>)>,
+}
+
+fn invalid_header<T>(reason: impl Into<String>) -> Result<T, IOError> {
+    Err(IOError::InvalidHeader {
+        reason: reason.into(),
+    })
+}
+
+fn scalar_type_from_ply(type_name: &str) -> Option<ScalarType> {
+    match type_name {
+        "char" | "int8" => Some(ScalarType::I8),
+        "uchar" | "uint8" => Some(ScalarType::U8),
+        "short" | "int16" => Some(ScalarType::I16),
+        "ushort" | "uint16" => Some(ScalarType::U16),
+        "int" | "int32" => Some(ScalarType::I32),
+        "uint" | "uint32" => Some(ScalarType::U32),
+        "float" | "float32" => Some(ScalarType::F32),
+        "double" | "float64" => Some(ScalarType::F64),
+        _ => None,
+    }
+}
+
+fn add_property(
+    elements: &mut [PlyElement],
+    name: &str,
+    scalar_type: Option<ScalarType>,
+) -> Result<(), IOError> {
+    match elements.last_mut() {
+        Some(element) => {
+            element.properties.push((name.to_string(), scalar_type));
+            Ok(())
+        }
+        None => invalid_header("property declared before any element"),
+    }
+}
+
+/// Reads the header, up to and including its `end_header` line.
+fn read_header<R: BufRead>(reader: &mut R) -> Result<(PlyFormat, Vec<PlyElement>), IOError> {
+    let mut read_line = |line: &mut String| -> Result<(), IOError> {
+        line.clear();
+        match reader.read_line(line)? {
+            0 => invalid_header("missing end_header line"),
+            _ => Ok(()),
+        }
+    };
+
+    let mut line = String::new();
+    read_line(&mut line)?;
+    if line.trim() != "ply" {
+        return invalid_header("missing ply magic number");
+    }
+
+    let mut format = None;
+    let mut elements = Vec::<PlyElement>::new();
+    loop {
+        read_line(&mut line)?;
+        let tokens = line.split_whitespace().collect::<Vec<_>>();
+        match tokens.as_slice() {
+            [] | ["comment", ..] | ["obj_info", ..] => {}
+            ["format", "ascii", _] => format = Some(PlyFormat::Ascii),
+            ["format", "binary_little_endian", _] => format = Some(PlyFormat::BinaryLittleEndian),
+            ["format", "binary_big_endian", _] => format = Some(PlyFormat::BinaryBigEndian),
+            ["element", name, count] => elements.push(PlyElement {
+                name: name.to_string(),
+                count: count.parse().map_err(|_| IOError::InvalidHeader {
+                    reason: format!("could not parse the count of element `{name}`"),
+                })?,
+                properties: Vec::new(),
+            }),
+            ["property", "list", _, _, name] => add_property(&mut elements, name, None)?,
+            ["property", type_name, name] => {
+                let Some(scalar_type) = scalar_type_from_ply(type_name) else {
+                    return invalid_header(format!(
+                        "unknown type `{type_name}` of property `{name}`"
+                    ));
+                };
+                add_property(&mut elements, name, Some(scalar_type))?
+            }
+            ["end_header"] => break,
+            _ => return invalid_header(format!("could not parse `{}`", line.trim())),
+        }
+    }
+
+    match format {
+        Some(format) => Ok((format, elements)),
+        None => invalid_header("missing format line"),
+    }
+}
+
+/// Reads a point cloud from the vertices of a file in the Polygon File Format, in either ASCII or binary encoding.
+/// The coordinates are read from the vertex properties named `x`, `y` and (in 3D) `z`, all other vertex properties are read as extra fields.
+/// Elements other than the vertices, such as faces, are skipped.
+///
+/// # Arguments
+/// * `reader`: a [`BufRead`], from which to read the point cloud.
+///
+/// # Generics
+/// * `T`: Either an [`f32`] or [`f64`].
+/// * `N`: A const usize, either `2` or `3`.
+///
+/// # Returns
+/// A [`PointCloudData`] containing the point cloud, or an [`IOError`] if it could not be read.
+///
+/// # Warnings
+/// * List properties are not supported in the vertex element, or in binary elements preceding it.
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument("Read PLY Point Cloud", skip_all, level = "info")
+)]
+pub fn read_ply<T, const N: usize, R>(mut reader: R) -> Result<PointCloudData<T, N>, IOError>
+where
+    T: Copy + Scalar,
+    f64: AsPrimitive<T>,
+    R: BufRead,
+{
+    let (format, elements) = read_header(&mut reader)?;
+    let big_endian = format == PlyFormat::BinaryBigEndian;
+
+    for element in elements {
+        let property_types = element
+            .properties
+            .iter()
+            .map(|(_, scalar_type)| *scalar_type)
+            .collect::<Option<Vec<_>>>();
+
+        if element.name != VERTEX_ELEMENT_NAME {
+            // Skip the element's data
+            match (format, property_types) {
+                (PlyFormat::Ascii, _) => {
+                    for _ in 0..element.count {
+                        reader.read_line(&mut String::new())?;
+                    }
+                }
+                (_, Some(property_types)) => {
+                    let Some(data_size) = property_types
+                        .iter()
+                        .map(|scalar_type| scalar_type.size())
+                        .sum::<usize>()
+                        .checked_mul(element.count)
+                    else {
+                        return invalid_header(format!(
+                            "the data size of element `{}` overflows",
+                            element.name
+                        ));
+                    };
+                    let data_size = data_size as u64;
+                    if io::copy(&mut reader.by_ref().take(data_size), &mut io::sink())? < data_size
+                    {
+                        return Err(io::Error::from(io::ErrorKind::UnexpectedEof).into());
+                    }
+                }
+                (_, None) => {
+                    return Err(IOError::Unsupported {
+                        reason: format!(
+                            "binary list properties in element `{}` preceding the vertices",
+                            element.name
+                        ),
+                    })
+                }
+            }
+            continue;
+        }
+
+        let Some(property_types) = property_types else {
+            return Err(IOError::Unsupported {
+                reason: String::from("list properties in the vertex element"),
+            });
+        };
+        if property_types.is_empty() {
+            return invalid_header("the vertex element has no properties");
+        }
+
+        let mut columns = Vec::new();
+        columns.resize_with(property_types.len(), Vec::new);
+        match format {
+            PlyFormat::Ascii => {
+                for line_idx in 0..element.count {
+                    let mut line = String::new();
+                    if reader.read_line(&mut line)? == 0 {
+                        return Err(IOError::InvalidData {
+                            reason: format!(
+                                "expected {} vertices, found {line_idx}",
+                                element.count
+                            ),
+                        });
+                    }
+
+                    let values = line
+                        .split_whitespace()
+                        .map(|token| parse_value(token, line_idx))
+                        .collect::<Result<Vec<_>, _>>()?;
+                    push_row(&mut columns, values, line_idx)?;
+                }
+            }
+            PlyFormat::BinaryLittleEndian | PlyFormat::BinaryBigEndian => {
+                let vertex_size = property_types
+                    .iter()
+                    .map(|scalar_type| scalar_type.size())
+                    .sum::<usize>();
+                let mut vertex_data = vec![0; vertex_size];
+                for _ in 0..element.count {
+                    reader.read_exact(&mut vertex_data)?;
+                    let mut offset = 0;
+                    for (column, scalar_type) in columns.iter_mut().zip(property_types.iter()) {
+                        column.push(scalar_type.read(
+                            &vertex_data[offset..offset + scalar_type.size()],
+                            big_endian,
+                        ));
+                        offset += scalar_type.size();
+                    }
+                }
+            }
+        }
+
+        let names = element
+            .properties
+            .into_iter()
+            .map(|(name, _)| name)
+            .collect();
+        return assemble_point_cloud(names, columns);
+    }
+
+    invalid_header("missing vertex element")
+}
+
+/// Writes a point cloud as the vertices of a file in the Polygon File Format.
+/// Every field is written as a floating point vertex property of the same size as `T`.
+///
+/// # Arguments
+/// * `writer`: a [`Write`], to which to write the point cloud.
+/// * `point_cloud`: a reference to the [`PointCloudData`] to write.
+/// * `encoding`: the [`Encoding`] of the point data, binary data is written in little-endian.
+///
+/// # Generics
+/// * `T`: Either an [`f32`] or [`f64`].
+/// * `N`: A const usize, either `2` or `3`.
+///
+/// # Returns
+/// An [`IOError`] if the point cloud could not be written.
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument("Write PLY Point Cloud", skip_all, level = "info")
+)]
+pub fn write_ply<T, const N: usize, W>(
+    mut writer: W,
+    point_cloud: &PointCloudData<T, N>,
+    encoding: Encoding,
+) -> Result<(), IOError>
+where
+    T: AsPrimitive<f64> + Copy + Display + Scalar,
+    W: Write,
+{
+    let names = column_names(point_cloud)?;
+    let type_name = match ScalarType::native::<T>() {
+        ScalarType::F32 => "float",
+        _ => "double",
+    };
+
+    writeln!(writer, "ply")?;
+    match encoding {
+        Encoding::Ascii => writeln!(writer, "format ascii 1.0")?,
+        Encoding::Binary => writeln!(writer, "format binary_little_endian 1.0")?,
+    }
+    writeln!(
+        writer,
+        "element {VERTEX_ELEMENT_NAME} {}",
+        point_cloud.points.len()
+    )?;
+    for name in names {
+        writeln!(writer, "property {type_name} {name}")?;
+    }
+    writeln!(writer, "end_header")?;
+
+    match encoding {
+        Encoding::Ascii => write_ascii_rows(&mut writer, point_cloud, " "),
+        Encoding::Binary => {
+            for point_idx in 0..point_cloud.points.len() {
+                for value in row_values(point_cloud, point_idx) {
+                    write_binary_value(&mut writer, value)?;
+                }
+            }
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use nalgebra::{Point2, Point3};
+    use std::io::Cursor;
+
+    use super::*;
+
+    #[test]
+    fn test_read_ascii_ply() {
+        let data = "ply
+format ascii 1.0
+comment A single triangle
+element vertex 3
+property float x
+property float y
+property float z
+property uchar intensity
+element face 1
+property list uchar int vertex_indices
+end_header
+0 0 0 10
+1 0 0 20
+0 1 0.5 30
+3 0 1 2
+";
+        let point_cloud = read_ply::<f32, 3, _>(Cursor::new(data)).unwrap();
+        assert_eq!(
+            point_cloud.points,
+            [
+                Point3::new(0.0, 0.0, 0.0),
+                Point3::new(1.0, 0.0, 0.0),
+                Point3::new(0.0, 1.0, 0.5)
+            ]
+        );
+        assert_eq!(
+            point_cloud.field("intensity"),
+            Some([10.0, 20.0, 30.0].as_slice())
+        );
+    }
+
+    #[test]
+    fn test_read_binary_big_endian_ply() {
+        let mut data = Vec::from(
+            "ply\nformat binary_big_endian 1.0\nelement camera 1\nproperty int id\nelement vertex 2\nproperty double y\nproperty double x\nproperty short ring\nend_header\n"
+                .as_bytes(),
+        );
+        data.extend(42i32.to_be_bytes());
+        for (y, x, ring) in [(1.5f64, -2.0f64, 3i16), (0.0, 4.25, -1)] {
+            data.extend(y.to_be_bytes());
+            data.extend(x.to_be_bytes());
+            data.extend(ring.to_be_bytes());
+        }
+
+        let point_cloud = read_ply::<f64, 2, _>(Cursor::new(data)).unwrap();
+        assert_eq!(
+            point_cloud.points,
+            [Point2::new(-2.0, 1.5), Point2::new(4.25, 0.0)]
+        );
+        assert_eq!(point_cloud.field("ring"), Some([3.0, -1.0].as_slice()));
+    }
+
+    #[test]
+    fn test_read_invalid_ply() {
+        assert!(matches!(
+            read_ply::<f32, 3, _>(Cursor::new("format ascii 1.0\nend_header\n")),
+            Err(IOError::InvalidHeader { .. })
+        ));
+        assert!(matches!(
+            read_ply::<f32, 3, _>(Cursor::new(
+                "ply\nformat ascii 1.0\nelement face 0\nend_header\n"
+            )),
+            Err(IOError::InvalidHeader { .. })
+        ));
+        assert!(matches!(
+            read_ply::<f32, 3, _>(Cursor::new(
+                "ply\nformat ascii 1.0\nelement vertex 1\nproperty list uchar float x\nend_header\n"
+            )),
+            Err(IOError::Unsupported { .. })
+        ));
+        assert!(matches!(
+            read_ply::<f32, 3, _>(Cursor::new(
+                "ply\nformat ascii 1.0\nelement vertex 2\nproperty float x\nproperty float y\nproperty float z\nend_header\n1 2 3\n"
+            )),
+            Err(IOError::InvalidData { .. })
+        ));
+    }
+
+    #[test]
+    fn test_read_malformed_ply_header() {
+        assert!(matches!(
+            read_ply::<f32, 3, _>(Cursor::new(
+                "ply\nformat binary_little_endian 1.0\nelement vertex 1\nend_header\n"
+            )),
+            Err(IOError::InvalidHeader { .. })
+        ));
+        assert!(matches!(
+            read_ply::<f32, 3, _>(Cursor::new(
+                "ply\nformat binary_little_endian 1.0\nelement camera 18446744073709551615\nproperty int id\nelement vertex 1\nproperty float x\nend_header\n"
+            )),
+            Err(IOError::InvalidHeader { .. })
+        ));
+        // Huge element counts must fail on the missing data, rather than on allocation
+        assert!(matches!(
+            read_ply::<f32, 3, _>(Cursor::new(
+                "ply\nformat binary_little_endian 1.0\nelement camera 1000000000000\nproperty int id\nelement vertex 1\nproperty float x\nend_header\n"
+            )),
+            Err(IOError::Io(_))
+        ));
+        for format in ["ascii", "binary_little_endian"] {
+            assert!(read_ply::<f32, 3, _>(Cursor::new(format!(
+                "ply\nformat {format} 1.0\nelement vertex 18446744073709551615\nproperty float x\nproperty float y\nproperty float z\nend_header\n"
+            )))
+            .is_err());
+        }
+    }
+
+    #[test]
+    fn test_ply_round_trip() {
+        let point_cloud = PointCloudData::new(Vec::from([
+            Point3::new(1.0f32, 2.0, 3.0),
+            Point3::new(-0.1, 0.2, -0.3),
+        ]))
+        .with_field("intensity", Vec::from([0.5, 0.75]));
+
+        for encoding in [Encoding::Ascii, Encoding::Binary] {
+            let mut buffer = Vec::new();
+            write_ply(&mut buffer, &point_cloud, encoding).unwrap();
+            assert_eq!(
+                read_ply::<f32, 3, _>(Cursor::new(buffer)).unwrap(),
+                point_cloud
+            );
+        }
+    }
+}
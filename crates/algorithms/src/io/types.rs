@@ -0,0 +1,195 @@
+// SPDX-License-Identifier: MIT
+/*
+ * Copyright (c) [2023 - Present] Emily Matheys <emilymatt96@gmail.com>
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+use core::fmt::{Display, Formatter};
+use nalgebra::{Point, Scalar};
+use std::path::Path;
+
+/// A named per-point attribute of a point cloud, such as intensity, ring or timestamp.
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PointField<T> {
+    /// The name of the field, as it appears in the file's header.
+    pub name: String,
+    /// The value of the field for each point, in the same order as the points.
+    pub values: Vec<T>,
+}
+
+/// A point cloud as stored in a file, containing the coordinates of each point along with any extra fields.
+///
+/// # Generics
+/// * `T`: Either an [`f32`] or [`f64`].
+/// * `N`: A const usize, either `2` or `3`.
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PointCloudData<T: Scalar, const N: usize> {
+    /// The coordinates of each point, read from the `x`, `y` and (in 3D) `z` fields.
+    pub points: Vec<Point<T, N>>,
+    /// Every other field in the file, in the order they appear in.
+    pub fields: Vec<PointField<T>>,
+}
+
+impl<T: Scalar, const N: usize> PointCloudData<T, N> {
+    /// Creates a point cloud without any extra fields.
+    ///
+    /// # Arguments
+    /// * `points`: a [`Vec`] of [`Point`], representing the point cloud.
+    pub fn new(points: Vec<Point<T, N>>) -> Self {
+        Self {
+            points,
+            fields: Vec::new(),
+        }
+    }
+
+    /// Adds an extra field to the point cloud.
+    ///
+    /// # Arguments
+    /// * `name`: the name of the field.
+    /// * `values`: the value of the field for each point, must have the same length as the point cloud.
+    ///
+    /// # Returns
+    /// The point cloud, containing the new field.
+    pub fn with_field(mut self, name: impl Into<String>, values: Vec<T>) -> Self {
+        self.fields.push(PointField {
+            name: name.into(),
+            values,
+        });
+        self
+    }
+
+    /// Looks up an extra field by its name.
+    ///
+    /// # Arguments
+    /// * `name`: the name of the field.
+    ///
+    /// # Returns
+    /// A slice of the values of the field, or [`None`] if the point cloud has no such field.
+    pub fn field(&self, name: &str) -> Option<&[T]> {
+        self.fields
+            .iter()
+            .find(|field| field.name == name)
+            .map(|field| field.values.as_slice())
+    }
+}
+
+/// Specifies how the point data of a file is encoded, for formats that support more than one encoding.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Encoding {
+    /// Human-readable text.
+    #[default]
+    Ascii,
+    /// Little-endian binary values, using the native size of the point cloud's floating point type.
+    Binary,
+}
+
+/// The file formats supported by this module.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum PointCloudFormat {
+    /// The Point Cloud Library's `.pcd` format, in either ASCII or binary encoding.
+    Pcd,
+    /// The Polygon File Format's `.ply`, in either ASCII or binary encoding, only its vertices are read.
+    Ply,
+    /// Whitespace separated values in a `.xyz` file, one point per line, with no header.
+    Xyz,
+    /// Comma separated values in a `.csv` file, one point per line, with an optional header.
+    Csv,
+}
+
+impl PointCloudFormat {
+    /// Determines the format of a file according to its extension, ignoring case.
+    ///
+    /// # Arguments
+    /// * `path`: the path of the file.
+    ///
+    /// # Returns
+    /// The matching [`PointCloudFormat`], or [`None`] if the extension is missing or unknown.
+    pub fn from_path(path: impl AsRef<Path>) -> Option<Self> {
+        let extension = path.as_ref().extension()?.to_str()?.to_ascii_lowercase();
+        match extension.as_str() {
+            "pcd" => Some(Self::Pcd),
+            "ply" => Some(Self::Ply),
+            "xyz" | "txt" => Some(Self::Xyz),
+            "csv" => Some(Self::Csv),
+            _ => None,
+        }
+    }
+}
+
+/// Describes why a point cloud could not be read or written.
+#[derive(Debug)]
+pub enum IOError {
+    /// The underlying reader or writer failed.
+    Io(std::io::Error),
+    /// The file's format could not be determined from its extension.
+    UnknownFormat,
+    /// The file's header is malformed.
+    InvalidHeader {
+        /// Explains which part of the header is invalid.
+        reason: String,
+    },
+    /// The point data does not match the header, or contains values which cannot be parsed.
+    InvalidData {
+        /// Explains which part of the data is invalid.
+        reason: String,
+    },
+    /// The file does not contain one of the coordinate fields required for the point cloud's dimensions.
+    MissingCoordinate {
+        /// The name of the missing field.
+        name: &'static str,
+    },
+    /// The file uses a feature of its format which is not supported, such as compressed data.
+    Unsupported {
+        /// Explains which feature is not supported.
+        reason: String,
+    },
+}
+
+impl Display for IOError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Io(error) => write!(f, "I/O error: {error}"),
+            Self::UnknownFormat => write!(f, "Unknown point cloud file format"),
+            Self::InvalidHeader { reason } => write!(f, "Invalid header: {reason}"),
+            Self::InvalidData { reason } => write!(f, "Invalid data: {reason}"),
+            Self::MissingCoordinate { name } => write!(f, "Missing coordinate field `{name}`"),
+            Self::Unsupported { reason } => write!(f, "Unsupported: {reason}"),
+        }
+    }
+}
+
+impl std::error::Error for IOError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for IOError {
+    fn from(error: std::io::Error) -> Self {
+        Self::Io(error)
+    }
+}
@@ -0,0 +1,155 @@
+// SPDX-License-Identifier: MIT
+/*
+ * Copyright (c) [2023 - Present] Emily Matheys <emilymatt96@gmail.com>
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+use core::fmt::Display;
+use nalgebra::Scalar;
+use num_traits::AsPrimitive;
+use std::io::{BufRead, Write};
+
+use super::{
+    helpers::{
+        assemble_point_cloud, column_names, default_column_names, parse_value, push_row,
+        write_ascii_rows,
+    },
+    types::{IOError, PointCloudData},
+};
+
+/// Reads a point cloud from whitespace separated values, one point per line.
+/// The first `N` values of each line are the coordinates of the point, and any further values are read as fields named `field_<idx>`.
+/// Empty lines and lines starting with `#` are skipped.
+///
+/// # Arguments
+/// * `reader`: a [`BufRead`], from which to read the point cloud.
+///
+/// # Generics
+/// * `T`: Either an [`f32`] or [`f64`].
+/// * `N`: A const usize, either `2` or `3`.
+///
+/// # Returns
+/// A [`PointCloudData`] containing the point cloud, or an [`IOError`] if it could not be read.
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument("Read XYZ Point Cloud", skip_all, level = "info")
+)]
+pub fn read_xyz<T, const N: usize, R>(reader: R) -> Result<PointCloudData<T, N>, IOError>
+where
+    T: Copy + Scalar,
+    f64: AsPrimitive<T>,
+    R: BufRead,
+{
+    let mut columns = Vec::new();
+    for (line_idx, line) in reader.lines().enumerate() {
+        let line = line?;
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let values = line
+            .split_whitespace()
+            .map(|token| parse_value(token, line_idx))
+            .collect::<Result<Vec<_>, _>>()?;
+        push_row(&mut columns, values, line_idx)?;
+    }
+
+    assemble_point_cloud(default_column_names::<N>(columns.len())?, columns)
+}
+
+/// Writes a point cloud as space separated values, one point per line, with its coordinates followed by its fields.
+///
+/// # Arguments
+/// * `writer`: a [`Write`], to which to write the point cloud.
+/// * `point_cloud`: a reference to the [`PointCloudData`] to write.
+///
+/// # Generics
+/// * `T`: Either an [`f32`] or [`f64`].
+/// * `N`: A const usize, either `2` or `3`.
+///
+/// # Returns
+/// An [`IOError`] if the point cloud could not be written.
+///
+/// # Warnings
+/// * The format has no header, so the names of the fields are not preserved.
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument("Write XYZ Point Cloud", skip_all, level = "info")
+)]
+pub fn write_xyz<T, const N: usize, W>(
+    mut writer: W,
+    point_cloud: &PointCloudData<T, N>,
+) -> Result<(), IOError>
+where
+    T: Copy + Display + Scalar,
+    W: Write,
+{
+    column_names(point_cloud)?;
+    write_ascii_rows(&mut writer, point_cloud, " ")
+}
+
+#[cfg(test)]
+mod tests {
+    use nalgebra::{Point2, Point3};
+    use std::io::Cursor;
+
+    use super::*;
+
+    #[test]
+    fn test_read_xyz() {
+        let data = "# x y z intensity\n1.0 2.0 3.0 10\n\n-4.5 0.0 1e-1 20\n";
+        let point_cloud = read_xyz::<f64, 3, _>(Cursor::new(data)).unwrap();
+        assert_eq!(
+            point_cloud.points,
+            [Point3::new(1.0, 2.0, 3.0), Point3::new(-4.5, 0.0, 0.1)]
+        );
+        assert_eq!(point_cloud.field("field_0"), Some([10.0, 20.0].as_slice()));
+
+        assert!(matches!(
+            read_xyz::<f64, 3, _>(Cursor::new("1.0 2.0 3.0\n1.0 2.0\n")),
+            Err(IOError::InvalidData { .. })
+        ));
+        assert!(matches!(
+            read_xyz::<f64, 3, _>(Cursor::new("1.0 2.0\n")),
+            Err(IOError::MissingCoordinate { name: "z" })
+        ));
+    }
+
+    #[test]
+    fn test_xyz_round_trip() {
+        let point_cloud = PointCloudData::new(Vec::from([
+            Point2::new(1.5f32, -2.25),
+            Point2::new(0.0, 100.0),
+        ]))
+        .with_field("intensity", Vec::from([0.5, 1.0]));
+
+        let mut buffer = Vec::new();
+        write_xyz(&mut buffer, &point_cloud).unwrap();
+        assert_eq!(String::from_utf8_lossy(&buffer), "1.5 -2.25 0.5\n0 100 1\n");
+
+        let read_point_cloud = read_xyz::<f32, 2, _>(Cursor::new(buffer)).unwrap();
+        assert_eq!(read_point_cloud.points, point_cloud.points);
+        assert_eq!(
+            read_point_cloud.field("field_0"),
+            point_cloud.field("intensity")
+        );
+    }
+}
@@ -0,0 +1,950 @@
+// SPDX-License-Identifier: MIT
+/*
+ * Copyright (c) [2023 - Present] Emily Matheys <emilymatt96@gmail.com>
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+use core::cmp::Ordering;
+use nalgebra::{Point, Scalar};
+use num_traits::{NumOps, One, Zero};
+
+use super::{push_bounded, should_visit_branch, HeapElement, SearchMode};
+use crate::{types::PolygonExtents, utils::distance_squared, BinaryHeap, Vec};
+
+/// The default maximum amount of points held by each leaf of a [`FlatKDTree`].
+pub const DEFAULT_BUCKET_SIZE: usize = 8;
+
+/// A single node of a [`FlatKDTree`], children and points are referenced by their index in the tree's arrays.
+#[derive(Clone, Debug)]
+enum FlatNode<T> {
+    Split {
+        dimension: usize,
+        value: T,
+        left: usize,
+        right: usize,
+    },
+    Leaf {
+        start: usize,
+        end: usize,
+    },
+}
+
+/// A K-Dimensional Tree, whose nodes and points are stored in contiguous arrays rather than individually allocated branches.
+/// Each leaf holds a bucket of several points, which are scanned linearly,
+/// making the tree faster to build and query than a [`KDTree`](super::KDTree), and cheap to clone or share between threads.
+///
+/// Unlike a [`KDTree`](super::KDTree), this tree cannot be modified once built, it must be rebuilt instead.
+///
+/// # Generics
+/// `T`: Either an [`f32`] or [`f64`]
+/// `N`: a const usize specifying how many dimensions should each point have.
+/// `P`: the payload type of each point, defaults to `()` for a tree of bare points.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "serde",
+    serde(
+        from = "SerializedFlatKDTree<T, N, P>",
+        into = "SerializedFlatKDTree<T, N, P>",
+        bound = "T: Copy + Default + NumOps + PartialOrd + Scalar + Zero + serde::Serialize + serde::de::DeserializeOwned, P: Clone + serde::Serialize + serde::de::DeserializeOwned"
+    )
+)]
+pub struct FlatKDTree<T, const N: usize, P = ()>
+where
+    T: Copy + Default + NumOps + PartialOrd + Scalar + Zero,
+{
+    nodes: Vec<FlatNode<T>>,
+    points: Vec<Point<T, N>>,
+    payloads: Vec<P>,
+}
+
+/// The serialized form of a [`FlatKDTree`], its nodes are rebuilt from the points and their payloads when deserializing.
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(
+    bound = "T: Scalar + serde::Serialize + serde::de::DeserializeOwned, P: serde::Serialize + serde::de::DeserializeOwned"
+)]
+struct SerializedFlatKDTree<T: Scalar, const N: usize, P> {
+    bucket_size: usize,
+    entries: Vec<(Point<T, N>, P)>,
+}
+
+#[cfg(feature = "serde")]
+impl<T, const N: usize, P> From<FlatKDTree<T, N, P>> for SerializedFlatKDTree<T, N, P>
+where
+    T: Copy + Default + NumOps + PartialOrd + Scalar + Zero,
+{
+    fn from(tree: FlatKDTree<T, N, P>) -> Self {
+        // Every node larger than the largest leaf was split, so rebuilding with it as the bucket size yields the same nodes
+        let bucket_size = tree
+            .nodes
+            .iter()
+            .filter_map(|node| match node {
+                FlatNode::Leaf { start, end } => Some(end - start),
+                FlatNode::Split { .. } => None,
+            })
+            .max()
+            .unwrap_or(DEFAULT_BUCKET_SIZE);
+        Self {
+            bucket_size,
+            entries: tree.points.into_iter().zip(tree.payloads).collect(),
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<T, const N: usize, P> From<SerializedFlatKDTree<T, N, P>> for FlatKDTree<T, N, P>
+where
+    T: Copy + Default + NumOps + PartialOrd + Scalar + Zero,
+{
+    fn from(serialized: SerializedFlatKDTree<T, N, P>) -> Self {
+        Self::with_bucket_size(serialized.entries, serialized.bucket_size)
+    }
+}
+
+impl<T, const N: usize, P> Default for FlatKDTree<T, N, P>
+where
+    T: Copy + Default + NumOps + PartialOrd + Scalar + Zero,
+{
+    fn default() -> Self {
+        Self {
+            nodes: Vec::new(),
+            points: Vec::new(),
+            payloads: Vec::new(),
+        }
+    }
+}
+
+impl<T, const N: usize> FlatKDTree<T, N, usize>
+where
+    T: Copy + Default + NumOps + PartialOrd + Scalar + Zero,
+{
+    /// Builds a tree from a point cloud, where the payload of each point is its index in `point_cloud`.
+    ///
+    /// # Arguments
+    /// * `point_cloud`: a slice of [`Point`], representing the point cloud.
+    ///
+    /// # Returns
+    /// A [`FlatKDTree`] whose payloads are indices into `point_cloud`.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            "Generate Indexed Flat Tree From Point Cloud",
+            skip_all,
+            level = "info"
+        )
+    )]
+    pub fn from_indexed(point_cloud: &[Point<T, N>]) -> Self {
+        point_cloud
+            .iter()
+            .copied()
+            .enumerate()
+            .map(|(idx, point)| (point, idx))
+            .collect()
+    }
+}
+
+impl<T, const N: usize, P> FlatKDTree<T, N, P>
+where
+    T: Copy + Default + NumOps + PartialOrd + Scalar + Zero,
+{
+    /// Builds a tree from points and their payloads, splitting each node around the median of its widest dimension.
+    ///
+    /// # Arguments
+    /// * `entries`: an [`IntoIterator`] of tuples, each containing a [`Point`] and its payload.
+    /// * `bucket_size`: the maximum amount of points held by each leaf, a value of `0` is treated as `1`.
+    ///
+    /// # Returns
+    /// A balanced [`FlatKDTree`] containing all the entries.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument("Build Flat Tree", skip_all, level = "info")
+    )]
+    pub fn with_bucket_size<I: IntoIterator<Item = (Point<T, N>, P)>>(
+        entries: I,
+        bucket_size: usize,
+    ) -> Self {
+        let mut entries = entries.into_iter().collect::<Vec<_>>();
+        let mut nodes = Vec::new();
+        if !entries.is_empty() {
+            Self::build_node(&mut entries, 0, bucket_size.max(1), &mut nodes);
+        }
+
+        let (points, payloads) = entries.into_iter().unzip();
+        Self {
+            nodes,
+            points,
+            payloads,
+        }
+    }
+
+    /// Recursively builds the node for `entries`, whose first entry is at `offset` in the tree's arrays.
+    /// Returns the index of the new node.
+    fn build_node(
+        entries: &mut [(Point<T, N>, P)],
+        offset: usize,
+        bucket_size: usize,
+        nodes: &mut Vec<FlatNode<T>>,
+    ) -> usize {
+        let node_idx = nodes.len();
+        if entries.len() <= bucket_size {
+            nodes.push(FlatNode::Leaf {
+                start: offset,
+                end: offset + entries.len(),
+            });
+            return node_idx;
+        }
+
+        // Splitting along the dimension with the widest spread keeps the leaves compact
+        let dimension = (0..N)
+            .map(|dimension| {
+                let (min, max) = entries.iter().fold(
+                    (
+                        entries[0].0.coords[dimension],
+                        entries[0].0.coords[dimension],
+                    ),
+                    |(min, max), (point, _)| {
+                        let coord = point.coords[dimension];
+                        (
+                            if coord < min { coord } else { min },
+                            if coord > max { coord } else { max },
+                        )
+                    },
+                );
+                (dimension, max - min)
+            })
+            .fold((0, T::zero()), |widest, current| {
+                if current.1 > widest.1 {
+                    current
+                } else {
+                    widest
+                }
+            })
+            .0;
+
+        let median_idx = entries.len() / 2;
+        entries.select_nth_unstable_by(median_idx, |(a, _), (b, _)| {
+            a.coords[dimension]
+                .partial_cmp(&b.coords[dimension])
+                .unwrap_or(Ordering::Equal)
+        });
+        let value = entries[median_idx].0.coords[dimension];
+
+        // Reserve this node's slot, its children are only known after they are built
+        nodes.push(FlatNode::Leaf { start: 0, end: 0 });
+        let (left_entries, right_entries) = entries.split_at_mut(median_idx);
+        let left = Self::build_node(left_entries, offset, bucket_size, nodes);
+        let right = Self::build_node(right_entries, offset + median_idx, bucket_size, nodes);
+        nodes[node_idx] = FlatNode::Split {
+            dimension,
+            value,
+            left,
+            right,
+        };
+
+        node_idx
+    }
+
+    /// Returns the amount of points in the tree.
+    pub fn len(&self) -> usize {
+        self.points.len()
+    }
+
+    /// Returns whether the tree holds no points.
+    pub fn is_empty(&self) -> bool {
+        self.points.is_empty()
+    }
+
+    /// Attempts to find the nearest point in the tree for the specified target point.
+    /// # Arguments
+    /// * `target`: a [`Point`], to search the closest point for.
+    ///
+    /// # Returns
+    /// [`None`] if the tree is empty, otherwise returns the closest [`Point`].
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument("Find Nearest Neighbour In Flat Tree", skip_all, level = "debug")
+    )]
+    pub fn nearest(&self, target: &Point<T, N>) -> Option<Point<T, N>>
+    where
+        T: One,
+    {
+        self.nearest_with_payload(target).map(|(point, _)| point)
+    }
+
+    /// Attempts to find the nearest point in the tree for the specified target point, along with its payload.
+    /// # Arguments
+    /// * `target`: a [`Point`], to search the closest point for.
+    ///
+    /// # Returns
+    /// [`None`] if the tree is empty, otherwise returns the closest [`Point`] and a reference to its payload.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            "Find Nearest Neighbour With Payload In Flat Tree",
+            skip_all,
+            level = "debug"
+        )
+    )]
+    pub fn nearest_with_payload(&self, target: &Point<T, N>) -> Option<(Point<T, N>, &P)>
+    where
+        T: One,
+    {
+        self.search_nearest(target, SearchMode::Exact)
+    }
+
+    /// Attempts to find the nearest point in the tree for the specified target point, along with its payload,
+    /// exploring the tree according to `search_mode`.
+    ///
+    /// # Arguments
+    /// * `target`: a [`Point`], to search the closest point for.
+    /// * `search_mode`: a [`SearchMode`], specifying whether the search may return an approximate nearest neighbour.
+    ///
+    /// # Returns
+    /// [`None`] if the tree is empty or `search_mode` is not [valid](SearchMode::is_valid),
+    /// otherwise returns the found [`Point`] and a reference to its payload.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument("Search Nearest Neighbour In Flat Tree", skip_all, level = "debug")
+    )]
+    pub fn search_nearest(
+        &self,
+        target: &Point<T, N>,
+        search_mode: SearchMode<T>,
+    ) -> Option<(Point<T, N>, &P)>
+    where
+        T: One,
+    {
+        if self.nodes.is_empty() {
+            return None;
+        }
+
+        let (axis_distance_factor, mut leaf_checks_left) = search_mode.pruning_parameters()?;
+        let mut best = None;
+        self.search_nearest_in_node(
+            0,
+            target,
+            axis_distance_factor,
+            &mut leaf_checks_left,
+            &mut best,
+        );
+
+        best.map(|(_, point_idx)| (self.points[point_idx], &self.payloads[point_idx]))
+    }
+
+    fn search_nearest_in_node(
+        &self,
+        node_idx: usize,
+        target: &Point<T, N>,
+        axis_distance_factor: T,
+        leaf_checks_left: &mut usize,
+        best: &mut Option<(T, usize)>,
+    ) {
+        if *leaf_checks_left == 0 {
+            return;
+        }
+
+        match self.nodes[node_idx] {
+            FlatNode::Leaf { start, end } => {
+                *leaf_checks_left -= 1;
+                for point_idx in start..end {
+                    let current_distance = distance_squared(&self.points[point_idx], target);
+                    if best.map_or(true, |(best_distance, _)| current_distance < best_distance) {
+                        *best = Some((current_distance, point_idx));
+                    }
+                }
+            }
+            FlatNode::Split {
+                dimension,
+                value,
+                left,
+                right,
+            } => {
+                let (next_branch, opposite_branch) = if target.coords[dimension] < value {
+                    (left, right)
+                } else {
+                    (right, left)
+                };
+
+                self.search_nearest_in_node(
+                    next_branch,
+                    target,
+                    axis_distance_factor,
+                    leaf_checks_left,
+                    best,
+                );
+                let axis_distance = target.coords[dimension] - value;
+                if best.is_some_and(|(best_distance, _)| {
+                    (axis_distance * axis_distance) * axis_distance_factor < best_distance
+                }) {
+                    self.search_nearest_in_node(
+                        opposite_branch,
+                        target,
+                        axis_distance_factor,
+                        leaf_checks_left,
+                        best,
+                    );
+                }
+            }
+        }
+    }
+
+    /// Finds the `k` nearest points in the tree for the specified target point.
+    ///
+    /// # Arguments
+    /// * `target`: a [`Point`], to search the closest points for.
+    /// * `k`: the maximum amount of points to return.
+    ///
+    /// # Returns
+    /// A [`Vec`] of up to `k` tuples, each containing a [`Point`] and its squared distance from `target`, sorted from nearest to farthest.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument("Find K Nearest Neighbours In Flat Tree", skip_all, level = "debug")
+    )]
+    pub fn k_nearest(&self, target: &Point<T, N>, k: usize) -> Vec<(Point<T, N>, T)> {
+        self.k_nearest_with_payload(target, k)
+            .into_iter()
+            .map(|(point, distance, _)| (point, distance))
+            .collect()
+    }
+
+    /// Finds the `k` nearest points in the tree for the specified target point, along with their payloads.
+    ///
+    /// # Arguments
+    /// * `target`: a [`Point`], to search the closest points for.
+    /// * `k`: the maximum amount of points to return.
+    ///
+    /// # Returns
+    /// A [`Vec`] of up to `k` tuples, each containing a [`Point`], its squared distance from `target` and a reference to its payload,
+    /// sorted from nearest to farthest.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            "Find K Nearest Neighbours With Payload In Flat Tree",
+            skip_all,
+            level = "debug"
+        )
+    )]
+    pub fn k_nearest_with_payload(
+        &self,
+        target: &Point<T, N>,
+        k: usize,
+    ) -> Vec<(Point<T, N>, T, &P)> {
+        if k == 0 || self.nodes.is_empty() {
+            return Vec::new();
+        }
+
+        let mut heap = BinaryHeap::with_capacity(k + 1);
+        self.k_nearest_in_node(0, target, k, &mut heap);
+
+        heap.into_sorted_vec()
+            .into_iter()
+            .map(|element| {
+                (
+                    self.points[element.item],
+                    element.distance_squared,
+                    &self.payloads[element.item],
+                )
+            })
+            .collect()
+    }
+
+    fn k_nearest_in_node(
+        &self,
+        node_idx: usize,
+        target: &Point<T, N>,
+        k: usize,
+        heap: &mut BinaryHeap<HeapElement<T, usize>>,
+    ) {
+        match self.nodes[node_idx] {
+            FlatNode::Leaf { start, end } => {
+                for point_idx in start..end {
+                    push_bounded(
+                        heap,
+                        k,
+                        HeapElement {
+                            distance_squared: distance_squared(&self.points[point_idx], target),
+                            item: point_idx,
+                        },
+                    );
+                }
+            }
+            FlatNode::Split {
+                dimension,
+                value,
+                left,
+                right,
+            } => {
+                let (next_branch, opposite_branch) = if target.coords[dimension] < value {
+                    (left, right)
+                } else {
+                    (right, left)
+                };
+
+                self.k_nearest_in_node(next_branch, target, k, heap);
+                // Only visit the opposite branch if it may contain a point closer than the worst one we currently hold
+                if should_visit_branch(heap, k, target.coords[dimension] - value) {
+                    self.k_nearest_in_node(opposite_branch, target, k, heap);
+                }
+            }
+        }
+    }
+
+    /// Lazily finds all points in the tree that are within `radius` of the specified target point.
+    ///
+    /// # Arguments
+    /// * `target`: a [`Point`], to search around.
+    /// * `radius`: the maximum distance of a point from `target`, inclusive.
+    ///
+    /// # Returns
+    /// An [`Iterator`], yielding every [`Point`] within the radius, in no particular order.
+    pub fn within_radius_iter(
+        &self,
+        target: &Point<T, N>,
+        radius: T,
+    ) -> impl Iterator<Item = Point<T, N>> + '_ {
+        self.within_radius_with_payload_iter(target, radius)
+            .map(|(point, _)| point)
+    }
+
+    /// Lazily finds all points in the tree that are within `radius` of the specified target point, along with their payloads.
+    ///
+    /// # Arguments
+    /// * `target`: a [`Point`], to search around.
+    /// * `radius`: the maximum distance of a point from `target`, inclusive.
+    ///
+    /// # Returns
+    /// An [`Iterator`], yielding every [`Point`] within the radius and a reference to its payload, in no particular order.
+    pub fn within_radius_with_payload_iter(
+        &self,
+        target: &Point<T, N>,
+        radius: T,
+    ) -> impl Iterator<Item = (Point<T, N>, &P)> + '_ {
+        let target = *target;
+        let radius_squared = radius * radius;
+        self.leaves_iter(move |dimension, value| {
+            // A branch on the other side of the splitting plane is only relevant if the plane is within the radius
+            let axis_distance = target.coords[dimension] - value;
+            let crosses_plane = (axis_distance * axis_distance) <= radius_squared;
+            (
+                target.coords[dimension] <= value || crosses_plane,
+                target.coords[dimension] >= value || crosses_plane,
+            )
+        })
+        .filter(move |(point, _)| distance_squared(point, &target) <= radius_squared)
+    }
+
+    /// Finds all points in the tree that are within `radius` of the specified target point.
+    ///
+    /// # Arguments
+    /// * `target`: a [`Point`], to search around.
+    /// * `radius`: the maximum distance of a point from `target`, inclusive.
+    ///
+    /// # Returns
+    /// A [`Vec`] of all [`Point`]s within the radius, in no particular order.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument("Find Points Within Radius In Flat Tree", skip_all, level = "debug")
+    )]
+    pub fn within_radius(&self, target: &Point<T, N>, radius: T) -> Vec<Point<T, N>> {
+        self.within_radius_iter(target, radius).collect()
+    }
+
+    /// Finds all points in the tree that are within `radius` of the specified target point, along with their payloads.
+    ///
+    /// # Arguments
+    /// * `target`: a [`Point`], to search around.
+    /// * `radius`: the maximum distance of a point from `target`, inclusive.
+    ///
+    /// # Returns
+    /// A [`Vec`] of all [`Point`]s within the radius and references to their payloads, in no particular order.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            "Find Points Within Radius With Payload In Flat Tree",
+            skip_all,
+            level = "debug"
+        )
+    )]
+    pub fn within_radius_with_payload(
+        &self,
+        target: &Point<T, N>,
+        radius: T,
+    ) -> Vec<(Point<T, N>, &P)> {
+        self.within_radius_with_payload_iter(target, radius)
+            .collect()
+    }
+
+    /// Lazily finds all points in the tree that are inside the axis-aligned box described by `extents`.
+    ///
+    /// # Arguments
+    /// * `extents`: a [`PolygonExtents`], containing the inclusive range of each dimension of the box.
+    ///
+    /// # Returns
+    /// An [`Iterator`], yielding every [`Point`] inside the box, in no particular order.
+    pub fn within_extents_iter(
+        &self,
+        extents: &PolygonExtents<T, N>,
+    ) -> impl Iterator<Item = Point<T, N>> + '_ {
+        self.within_extents_with_payload_iter(extents)
+            .map(|(point, _)| point)
+    }
+
+    /// Lazily finds all points in the tree that are inside the axis-aligned box described by `extents`, along with their payloads.
+    ///
+    /// # Arguments
+    /// * `extents`: a [`PolygonExtents`], containing the inclusive range of each dimension of the box.
+    ///
+    /// # Returns
+    /// An [`Iterator`], yielding every [`Point`] inside the box and a reference to its payload, in no particular order.
+    pub fn within_extents_with_payload_iter(
+        &self,
+        extents: &PolygonExtents<T, N>,
+    ) -> impl Iterator<Item = (Point<T, N>, &P)> + '_ {
+        let node_extents = extents.clone();
+        let point_extents = extents.clone();
+        self.leaves_iter(move |dimension, value| {
+            (
+                *node_extents[dimension].start() <= value,
+                *node_extents[dimension].end() >= value,
+            )
+        })
+        .filter(move |(point, _)| {
+            point
+                .coords
+                .iter()
+                .zip(point_extents.iter())
+                .all(|(coord, extent)| extent.contains(coord))
+        })
+    }
+
+    /// Finds all points in the tree that are inside the axis-aligned box described by `extents`.
+    ///
+    /// # Arguments
+    /// * `extents`: a [`PolygonExtents`], containing the inclusive range of each dimension of the box.
+    ///
+    /// # Returns
+    /// A [`Vec`] of all [`Point`]s inside the box, in no particular order.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument("Find Points Within Extents In Flat Tree", skip_all, level = "debug")
+    )]
+    pub fn within_extents(&self, extents: &PolygonExtents<T, N>) -> Vec<Point<T, N>> {
+        self.within_extents_iter(extents).collect()
+    }
+
+    /// Finds all points in the tree that are inside the axis-aligned box described by `extents`, along with their payloads.
+    ///
+    /// # Arguments
+    /// * `extents`: a [`PolygonExtents`], containing the inclusive range of each dimension of the box.
+    ///
+    /// # Returns
+    /// A [`Vec`] of all [`Point`]s inside the box and references to their payloads, in no particular order.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            "Find Points Within Extents With Payload In Flat Tree",
+            skip_all,
+            level = "debug"
+        )
+    )]
+    pub fn within_extents_with_payload(
+        &self,
+        extents: &PolygonExtents<T, N>,
+    ) -> Vec<(Point<T, N>, &P)> {
+        self.within_extents_with_payload_iter(extents).collect()
+    }
+
+    /// Lazily yields the points of every leaf reachable through the splits accepted by `visit_branches`,
+    /// which receives the split's dimension and value, and returns whether to visit the left and right branches.
+    fn leaves_iter<F: FnMut(usize, T) -> (bool, bool) + 'static>(
+        &self,
+        mut visit_branches: F,
+    ) -> impl Iterator<Item = (Point<T, N>, &P)> + '_ {
+        let mut stack = Vec::new();
+        if !self.nodes.is_empty() {
+            stack.push(0);
+        }
+
+        core::iter::from_fn(move || {
+            while let Some(node_idx) = stack.pop() {
+                match self.nodes[node_idx] {
+                    FlatNode::Leaf { start, end } => return Some(start..end),
+                    FlatNode::Split {
+                        dimension,
+                        value,
+                        left,
+                        right,
+                    } => {
+                        let (visit_left, visit_right) = visit_branches(dimension, value);
+                        if visit_left {
+                            stack.push(left);
+                        }
+                        if visit_right {
+                            stack.push(right);
+                        }
+                    }
+                }
+            }
+
+            None
+        })
+        .flatten()
+        .map(|point_idx| (self.points[point_idx], &self.payloads[point_idx]))
+    }
+
+    /// Allows traversal of the entire tree, calling the `func` closure on each point.
+    ///
+    /// # Arguments
+    /// * `func`: a closure of type [`FnMut`], it's only parameter is a reference of the [`Point`].
+    pub fn traverse_tree<F: FnMut(&Point<T, N>)>(&self, func: F) {
+        self.points.iter().for_each(func);
+    }
+
+    /// Allows traversal of the entire tree, calling the `func` closure on each point and its payload.
+    ///
+    /// # Arguments
+    /// * `func`: a closure of type [`FnMut`], receiving a reference of the [`Point`] and its payload.
+    pub fn traverse_tree_with_payload<F: FnMut(&Point<T, N>, &P)>(&self, mut func: F) {
+        self.points
+            .iter()
+            .zip(self.payloads.iter())
+            .for_each(|(point, payload)| func(point, payload));
+    }
+}
+
+impl<T, const N: usize> From<&[Point<T, N>]> for FlatKDTree<T, N>
+where
+    T: Copy + Default + NumOps + PartialOrd + Scalar + Zero,
+{
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument("Generate Flat Tree From Point Cloud", skip_all, level = "info")
+    )]
+    fn from(point_cloud: &[Point<T, N>]) -> Self {
+        point_cloud.iter().map(|point| (*point, ())).collect()
+    }
+}
+
+impl<T, const N: usize, P> FromIterator<(Point<T, N>, P)> for FlatKDTree<T, N, P>
+where
+    T: Copy + Default + NumOps + PartialOrd + Scalar + Zero,
+{
+    fn from_iter<I: IntoIterator<Item = (Point<T, N>, P)>>(iter: I) -> Self {
+        Self::with_bucket_size(iter, DEFAULT_BUCKET_SIZE)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use nalgebra::{Point2, Point3};
+
+    use crate::{kd_tree::KDTree, utils::point_cloud::find_closest_point, Vec};
+
+    use super::*;
+
+    fn generate_points() -> Vec<Point3<f32>> {
+        (0..500)
+            .map(|idx| {
+                let idx = idx as f32;
+                Point3::new(
+                    (idx * 7.3).sin() * 10.0,
+                    (idx * 3.1).cos() * 10.0,
+                    (idx * 1.7).sin() * 5.0,
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_empty_tree() {
+        let tree = FlatKDTree::<f32, 2>::default();
+        assert!(tree.is_empty());
+        assert!(tree.nearest(&Point2::new(0.0, 0.0)).is_none());
+        assert!(tree.k_nearest(&Point2::new(0.0, 0.0), 3).is_empty());
+        assert!(tree.within_radius(&Point2::new(0.0, 0.0), 3.0).is_empty());
+        assert!(tree.within_extents(&[-1.0..=1.0, -1.0..=1.0]).is_empty());
+
+        let tree = FlatKDTree::<f32, 2>::from([].as_slice());
+        assert!(tree.nearest(&Point2::new(0.0, 0.0)).is_none());
+    }
+
+    #[test]
+    fn compare_with_kd_tree() {
+        let points = generate_points();
+        let kd_tree = KDTree::from(points.as_slice());
+        let targets = [
+            Point3::new(0.0, 0.0, 0.0),
+            Point3::new(4.2, -3.3, 1.0),
+            Point3::new(-20.0, 15.0, -7.5),
+        ];
+
+        for bucket_size in [0, 1, 4, 16, 1000] {
+            let flat_tree = FlatKDTree::with_bucket_size(
+                points
+                    .iter()
+                    .copied()
+                    .enumerate()
+                    .map(|(idx, point)| (point, idx)),
+                bucket_size,
+            );
+            assert_eq!(flat_tree.len(), points.len());
+
+            for target in targets.iter() {
+                let (nearest, nearest_idx) = flat_tree.nearest_with_payload(target).unwrap();
+                assert_eq!(nearest, find_closest_point(target, &points));
+                assert_eq!(points[*nearest_idx], nearest);
+
+                let flat_distances = flat_tree
+                    .k_nearest(target, 10)
+                    .into_iter()
+                    .map(|(_, distance)| distance)
+                    .collect::<Vec<_>>();
+                let kd_distances = kd_tree
+                    .k_nearest(target, 10)
+                    .into_iter()
+                    .map(|(_, distance)| distance)
+                    .collect::<Vec<_>>();
+                assert_eq!(flat_distances, kd_distances);
+
+                assert_eq!(
+                    flat_tree.within_radius(target, 4.0).len(),
+                    kd_tree.within_radius(target, 4.0).len()
+                );
+            }
+
+            let extents = [-3.0..=4.0, -8.0..=0.0, -1.0..=5.0];
+            let mut flat_within_extents = flat_tree
+                .within_extents_with_payload(&extents)
+                .into_iter()
+                .map(|(_, idx)| *idx)
+                .collect::<Vec<_>>();
+            flat_within_extents.sort();
+            let mut naive_within_extents = (0..points.len())
+                .filter(|idx| {
+                    points[*idx]
+                        .coords
+                        .iter()
+                        .zip(extents.iter())
+                        .all(|(coord, extent)| extent.contains(coord))
+                })
+                .collect::<Vec<_>>();
+            naive_within_extents.sort();
+            assert_eq!(flat_within_extents, naive_within_extents);
+        }
+    }
+
+    #[test]
+    fn test_duplicate_points() {
+        let points = [Point2::new(1.0f32, 1.0); 40];
+        let tree = FlatKDTree::from_indexed(&points);
+        assert_eq!(tree.k_nearest(&Point2::new(1.0, 1.0), 50).len(), 40);
+        assert_eq!(tree.within_radius(&Point2::new(1.0, 1.0), 0.0).len(), 40);
+        assert_eq!(tree.within_extents(&[1.0..=1.0, 1.0..=1.0]).len(), 40);
+
+        let mut payload_sum = 0;
+        tree.traverse_tree_with_payload(|_, idx| payload_sum += idx);
+        assert_eq!(payload_sum, (0..40).sum::<usize>());
+    }
+
+    #[test]
+    fn test_search_nearest_error_bound() {
+        let points = generate_points();
+        let tree = FlatKDTree::from_indexed(&points);
+        let targets = (0..200)
+            .map(|idx| {
+                let idx = idx as f32;
+                Point3::new(
+                    (idx * 2.9).cos() * 12.0,
+                    (idx * 5.3).sin() * 12.0,
+                    (idx * 0.7).cos() * 6.0,
+                )
+            })
+            .collect::<Vec<_>>();
+
+        assert!(FlatKDTree::<f32, 3>::default()
+            .search_nearest(&targets[0], SearchMode::Exact)
+            .is_none());
+
+        for target in targets.iter() {
+            let exact_distance =
+                distance_squared(&find_closest_point(target, &points), target).sqrt();
+
+            let (nearest, idx) = tree.search_nearest(target, SearchMode::Exact).unwrap();
+            assert_eq!(distance_squared(&nearest, target).sqrt(), exact_distance);
+            assert_eq!(points[*idx], nearest);
+
+            for epsilon in [0.0, 0.1, 0.5, 2.0] {
+                let (nearest, _) = tree
+                    .search_nearest(target, SearchMode::Approximate { epsilon })
+                    .unwrap();
+                assert!(
+                    distance_squared(&nearest, target).sqrt()
+                        <= exact_distance * (1.0 + epsilon) + 1e-5
+                );
+            }
+
+            let (nearest, _) = tree
+                .search_nearest(target, SearchMode::MaxLeafChecks(points.len()))
+                .unwrap();
+            assert_eq!(distance_squared(&nearest, target).sqrt(), exact_distance);
+
+            // A single leaf check only scans the target's own bucket
+            let (nearest, _) = tree
+                .search_nearest(target, SearchMode::MaxLeafChecks(1))
+                .unwrap();
+            assert!(distance_squared(&nearest, target).sqrt() >= exact_distance);
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_round_trip() {
+        let points = generate_points();
+        let tree = FlatKDTree::with_bucket_size(
+            points
+                .iter()
+                .copied()
+                .enumerate()
+                .map(|(idx, point)| (point, idx)),
+            16,
+        );
+        let serialized = serde_json::to_string(&tree).unwrap();
+        let deserialized: FlatKDTree<f32, 3, usize> = serde_json::from_str(&serialized).unwrap();
+        assert_eq!(deserialized.len(), points.len());
+
+        for target in points.iter().step_by(50) {
+            assert_eq!(
+                deserialized.nearest_with_payload(target),
+                tree.nearest_with_payload(target)
+            );
+            let within_radius = |tree: &FlatKDTree<f32, 3, usize>| {
+                let mut indices = tree
+                    .within_radius_with_payload(target, 2.0)
+                    .into_iter()
+                    .map(|(_, idx)| *idx)
+                    .collect::<Vec<_>>();
+                indices.sort();
+                indices
+            };
+            assert_eq!(within_radius(&deserialized), within_radius(&tree));
+        }
+    }
+}
@@ -21,38 +21,207 @@
  * SOFTWARE.
  */
 
+use core::cmp::Ordering;
 use nalgebra::{Point, Scalar};
-use num_traits::{NumOps, Zero};
+use num_traits::{NumOps, One, Zero};
 
-use crate::{utils::distance_squared, Box};
+use crate::{types::PolygonExtents, utils::distance_squared, BinaryHeap, Box, Vec};
 
-#[derive(Clone, Debug, Default)]
-struct KDNode<T, const N: usize>
+/// A cache-friendly K-Dimensional Tree, whose nodes are stored contiguously and whose leaves hold buckets of points.
+pub mod flat;
+
+/// Specifies how thoroughly a nearest neighbour search explores the tree, trading exactness for speed.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum SearchMode<T> {
+    /// Always finds the true nearest neighbour.
+    #[default]
+    Exact,
+    /// Finds a neighbour whose distance is at most `(1 + epsilon)` times the distance of the true nearest neighbour,
+    /// by skipping branches that cannot contain a point closer than that.
+    Approximate {
+        /// The allowed relative distance error, must not be negative.
+        epsilon: T,
+    },
+    /// Finds the nearest neighbour amongst the first points checked, descending to the target's leaf first,
+    /// and stopping once the budget is spent, there is no bound on the distance error.
+    /// For a [`KDTree`] every node is a single point, and for a [`FlatKDTree`](flat::FlatKDTree) every leaf is a bucket of points.
+    /// The budget must be positive.
+    MaxLeafChecks(usize),
+}
+
+impl<T: Copy + PartialOrd + Zero> SearchMode<T> {
+    /// Checks whether a search can be run in this mode.
+    ///
+    /// # Returns
+    /// `false` if this is an [`Approximate`](SearchMode::Approximate) mode with a negative (or NaN) `epsilon`,
+    /// or a [`MaxLeafChecks`](SearchMode::MaxLeafChecks) mode with a budget of `0`, `true` otherwise.
+    pub fn is_valid(&self) -> bool {
+        match *self {
+            SearchMode::Exact => true,
+            SearchMode::Approximate { epsilon } => epsilon >= T::zero(),
+            SearchMode::MaxLeafChecks(max_leaf_checks) => max_leaf_checks > 0,
+        }
+    }
+}
+
+impl<T: Copy + NumOps + One + PartialOrd + Zero> SearchMode<T> {
+    /// Returns the factor by which squared axis distances are scaled before pruning, and the amount of leaves that may be checked,
+    /// or [`None`] if the search mode is not valid.
+    fn pruning_parameters(&self) -> Option<(T, usize)> {
+        if !self.is_valid() {
+            return None;
+        }
+
+        Some(match *self {
+            SearchMode::Exact => (T::one(), usize::MAX),
+            SearchMode::Approximate { epsilon } => {
+                let distance_factor = T::one() + epsilon;
+                (distance_factor * distance_factor, usize::MAX)
+            }
+            SearchMode::MaxLeafChecks(max_leaf_checks) => (T::one(), max_leaf_checks),
+        })
+    }
+}
+
+/// A candidate held in the heaps used by nearest neighbour searches, ordered only by its squared distance.
+pub(crate) struct HeapElement<T: PartialOrd, I> {
+    pub(crate) distance_squared: T,
+    pub(crate) item: I,
+}
+
+impl<T: PartialOrd, I> PartialEq for HeapElement<T, I> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl<T: PartialOrd, I> Eq for HeapElement<T, I> {}
+
+impl<T: PartialOrd, I> PartialOrd for HeapElement<T, I> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T: PartialOrd, I> Ord for HeapElement<T, I> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // NaN distances are treated as equal, so that they don't poison the heap
+        self.distance_squared
+            .partial_cmp(&other.distance_squared)
+            .unwrap_or(Ordering::Equal)
+    }
+}
+
+/// Pushes a candidate into a bounded max-heap of at most `k` elements, replacing the farthest candidate if the new one is closer.
+fn push_bounded<T: PartialOrd, I>(
+    heap: &mut BinaryHeap<HeapElement<T, I>>,
+    k: usize,
+    element: HeapElement<T, I>,
+) {
+    if heap.len() < k {
+        heap.push(element);
+    } else if heap
+        .peek()
+        .is_some_and(|worst| element.distance_squared < worst.distance_squared)
+    {
+        heap.pop();
+        heap.push(element);
+    }
+}
+
+/// Whether a branch whose splitting plane is `axis_distance` away from the target may still improve a bounded max-heap of `k` elements.
+fn should_visit_branch<T: Copy + NumOps + PartialOrd, I>(
+    heap: &BinaryHeap<HeapElement<T, I>>,
+    k: usize,
+    axis_distance: T,
+) -> bool {
+    heap.len() < k
+        || heap
+            .peek()
+            .is_some_and(|worst| (axis_distance * axis_distance) < worst.distance_squared)
+}
+
+#[derive(Clone, Debug)]
+struct KDNode<T, const N: usize, P>
 where
     T: Copy + Default + NumOps + PartialOrd + Scalar + Zero,
 {
     internal_data: Point<T, N>,
-    right: Option<Box<KDNode<T, N>>>,
-    left: Option<Box<KDNode<T, N>>>,
+    payload: P,
+    right: Option<Box<KDNode<T, N, P>>>,
+    left: Option<Box<KDNode<T, N, P>>>,
 }
 
-impl<T, const N: usize> KDNode<T, N>
+impl<T, const N: usize, P> KDNode<T, N, P>
 where
     T: Copy + Default + NumOps + PartialOrd + Scalar + Zero,
 {
-    fn new(data: Point<T, N>) -> Self {
+    fn new(data: Point<T, N>, payload: P) -> Self {
         Self {
             internal_data: data,
+            payload,
             left: None,
             right: None,
         }
     }
 
+    /// Builds a balanced branch by recursively splitting the entries around the median of each dimension.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument("Build Balanced Branch", skip_all, level = "trace")
+    )]
+    fn build(mut entries: Vec<(Point<T, N>, P)>, depth: usize) -> Option<Box<Self>> {
+        if entries.is_empty() {
+            return None;
+        }
+
+        let dimension_to_check = depth % N;
+        let median_idx = entries.len() / 2;
+        entries.select_nth_unstable_by(median_idx, |(a, _), (b, _)| {
+            a.coords[dimension_to_check]
+                .partial_cmp(&b.coords[dimension_to_check])
+                .unwrap_or(Ordering::Equal)
+        });
+
+        // The left branch must only hold points strictly smaller than the splitting point, same as in `insert`,
+        // so any points equal to the median are moved to the right of it.
+        let median_coord = entries[median_idx].0.coords[dimension_to_check];
+        let mut split_idx = 0;
+        for idx in 0..median_idx {
+            if entries[idx].0.coords[dimension_to_check] < median_coord {
+                entries.swap(idx, split_idx);
+                split_idx += 1;
+            }
+        }
+        entries.swap(split_idx, median_idx);
+
+        let right_entries = entries.split_off(split_idx + 1);
+        let (split_point, payload) = entries.pop()?;
+        Some(Box::new(Self {
+            internal_data: split_point,
+            payload,
+            left: Self::build(entries, depth + 1),
+            right: Self::build(right_entries, depth + 1),
+        }))
+    }
+
+    /// Moves the entries of this branch and all of its sub-branches into `entries`.
+    fn into_entries(self, entries: &mut Vec<(Point<T, N>, P)>) {
+        if let Some(left) = self.left {
+            left.into_entries(entries);
+        }
+        entries.push((self.internal_data, self.payload));
+        if let Some(right) = self.right {
+            right.into_entries(entries);
+        }
+    }
+
     #[cfg_attr(
         feature = "tracing",
         tracing::instrument("Insert New Point", skip_all, level = "trace")
     )]
-    fn insert(&mut self, data: Point<T, N>, depth: usize) {
+    fn insert(&mut self, data: Point<T, N>, payload: P, depth: usize) {
         let dimension_to_check = depth % N;
 
         let branch_to_use =
@@ -64,17 +233,93 @@ fn insert(&mut self, data: Point<T, N>, depth: usize) {
             };
 
         if let Some(branch_exists) = branch_to_use.as_mut() {
-            branch_exists.insert(data, depth + 1);
+            branch_exists.insert(data, payload, depth + 1);
         } else {
-            *branch_to_use = Some(Box::new(KDNode::new(data)))
+            *branch_to_use = Some(Box::new(KDNode::new(data, payload)))
+        }
+    }
+
+    /// Finds the node holding the smallest coordinate in `dimension` amongst this branch and all of its sub-branches.
+    fn min_node(&self, dimension: usize, depth: usize) -> &Self {
+        // The right branch can only hold larger coordinates in the dimension this branch is split by
+        let right = (depth % N != dimension)
+            .then_some(self.right.as_deref())
+            .flatten();
+
+        [self.left.as_deref(), right]
+            .into_iter()
+            .flatten()
+            .map(|branch| branch.min_node(dimension, depth + 1))
+            .fold(self, |best, candidate| {
+                if candidate.internal_data.coords[dimension] < best.internal_data.coords[dimension]
+                {
+                    candidate
+                } else {
+                    best
+                }
+            })
+    }
+
+    /// Removes the first node for which `is_match` returns `true` from the branch in `branch_slot`,
+    /// searching along the path that `data` would be inserted at.
+    /// Returns the point and payload of the removed node.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument("Remove Point", skip_all, level = "trace")
+    )]
+    fn remove(
+        branch_slot: &mut Option<Box<Self>>,
+        data: &Point<T, N>,
+        depth: usize,
+        is_match: &dyn Fn(&Self) -> bool,
+    ) -> Option<(Point<T, N>, P)> {
+        let node = branch_slot.as_mut()?;
+        if !is_match(node) {
+            let dimension_to_check = depth % N;
+            let next_branch = if data.coords[dimension_to_check]
+                < node.internal_data.coords[dimension_to_check]
+            {
+                &mut node.left
+            } else {
+                &mut node.right
+            };
+            return Self::remove(next_branch, data, depth + 1, is_match);
+        }
+
+        // The removed node is replaced by the smallest node of its right branch in its splitting dimension,
+        // which keeps every point of the right branch no smaller than it, and every point of the left branch strictly smaller.
+        // Without a right branch, the left branch takes its place, since its smallest node keeps the same guarantees.
+        let dimension_to_check = depth % N;
+        if node.right.is_none() {
+            if node.left.is_none() {
+                let node = branch_slot.take()?;
+                return Some((node.internal_data, node.payload));
+            }
+            node.right = node.left.take();
         }
+
+        let (replacement_ptr, replacement_data) = node.right.as_deref().map(|right| {
+            let replacement = right.min_node(dimension_to_check, depth + 1);
+            (replacement as *const Self, replacement.internal_data)
+        })?;
+        let (replacement_data, replacement_payload) = Self::remove(
+            &mut node.right,
+            &replacement_data,
+            depth + 1,
+            &|candidate: &Self| core::ptr::eq(candidate, replacement_ptr),
+        )?;
+
+        Some((
+            core::mem::replace(&mut node.internal_data, replacement_data),
+            core::mem::replace(&mut node.payload, replacement_payload),
+        ))
     }
 
     #[cfg_attr(
         feature = "tracing",
         tracing::instrument("Branch Nearest Neighbour", skip_all, level = "trace")
     )]
-    fn nearest(&self, target: &Point<T, N>, depth: usize) -> Option<Point<T, N>> {
+    fn nearest(&self, target: &Point<T, N>, depth: usize) -> Option<&Self> {
         let dimension_to_check = depth % N;
         let (next_branch, opposite_branch) =
             if target.coords[dimension_to_check] < self.internal_data.coords[dimension_to_check] {
@@ -86,20 +331,24 @@ fn nearest(&self, target: &Point<T, N>, depth: usize) -> Option<Point<T, N>> {
         // Start with the nearer branch, default to this branch's point
         let mut best = next_branch
             .and_then(|branch| branch.nearest(target, depth + 1))
-            .unwrap_or(self.internal_data);
+            .unwrap_or(self);
 
         let axis_distance =
             target.coords[dimension_to_check] - self.internal_data.coords[dimension_to_check];
 
-        if distance_squared(&self.internal_data, target) < distance_squared(&best, target) {
-            best = self.internal_data;
+        if distance_squared(&self.internal_data, target)
+            < distance_squared(&best.internal_data, target)
+        {
+            best = self;
         }
 
-        if (axis_distance * axis_distance) < distance_squared(&best, target) {
+        if (axis_distance * axis_distance) < distance_squared(&best.internal_data, target) {
             if let Some(opposite_best) =
                 opposite_branch.and_then(|branch| branch.nearest(target, depth + 1))
             {
-                if distance_squared(&opposite_best, target) < distance_squared(&best, target) {
+                if distance_squared(&opposite_best.internal_data, target)
+                    < distance_squared(&best.internal_data, target)
+                {
                     return Some(opposite_best);
                 }
             }
@@ -108,15 +357,109 @@ fn nearest(&self, target: &Point<T, N>, depth: usize) -> Option<Point<T, N>> {
         Some(best)
     }
 
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            "Branch Nearest Neighbour With Search Mode",
+            skip_all,
+            level = "trace"
+        )
+    )]
+    fn search_nearest<'a>(
+        &'a self,
+        target: &Point<T, N>,
+        depth: usize,
+        axis_distance_factor: T,
+        checks_left: &mut usize,
+        best: &mut Option<(T, &'a Self)>,
+    ) {
+        if *checks_left == 0 {
+            return;
+        }
+
+        let dimension_to_check = depth % N;
+        let (next_branch, opposite_branch) =
+            if target.coords[dimension_to_check] < self.internal_data.coords[dimension_to_check] {
+                (self.left.as_ref(), self.right.as_ref())
+            } else {
+                (self.right.as_ref(), self.left.as_ref())
+            };
+
+        if let Some(branch) = next_branch {
+            branch.search_nearest(target, depth + 1, axis_distance_factor, checks_left, best);
+        }
+        if *checks_left == 0 {
+            return;
+        }
+
+        *checks_left -= 1;
+        let current_distance = distance_squared(&self.internal_data, target);
+        if best.map_or(true, |(best_distance, _)| current_distance < best_distance) {
+            *best = Some((current_distance, self));
+        }
+
+        let axis_distance =
+            target.coords[dimension_to_check] - self.internal_data.coords[dimension_to_check];
+        if best.is_some_and(|(best_distance, _)| {
+            (axis_distance * axis_distance) * axis_distance_factor < best_distance
+        }) {
+            if let Some(branch) = opposite_branch {
+                branch.search_nearest(target, depth + 1, axis_distance_factor, checks_left, best);
+            }
+        }
+    }
+
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument("Branch K Nearest Neighbours", skip_all, level = "trace")
+    )]
+    fn k_nearest<'a>(
+        &'a self,
+        target: &Point<T, N>,
+        k: usize,
+        depth: usize,
+        heap: &mut BinaryHeap<HeapElement<T, &'a Self>>,
+    ) {
+        let dimension_to_check = depth % N;
+        let (next_branch, opposite_branch) =
+            if target.coords[dimension_to_check] < self.internal_data.coords[dimension_to_check] {
+                (self.left.as_ref(), self.right.as_ref())
+            } else {
+                (self.right.as_ref(), self.left.as_ref())
+            };
+
+        if let Some(branch) = next_branch {
+            branch.k_nearest(target, k, depth + 1, heap);
+        }
+
+        push_bounded(
+            heap,
+            k,
+            HeapElement {
+                distance_squared: distance_squared(&self.internal_data, target),
+                item: self,
+            },
+        );
+
+        // Only visit the opposite branch if it may contain a point closer than the worst one we currently hold
+        let axis_distance =
+            target.coords[dimension_to_check] - self.internal_data.coords[dimension_to_check];
+        if should_visit_branch(heap, k, axis_distance) {
+            if let Some(branch) = opposite_branch {
+                branch.k_nearest(target, k, depth + 1, heap);
+            }
+        }
+    }
+
     #[cfg_attr(
         feature = "tracing",
         tracing::instrument("Traverse Branch With Function", skip_all, level = "debug")
     )]
-    fn traverse_branch<F: FnMut(&Point<T, N>)>(&self, func: &mut F) {
+    fn traverse_branch<F: FnMut(&Point<T, N>, &P)>(&self, func: &mut F) {
         if let Some(left) = self.left.as_ref() {
             left.traverse_branch(func);
         }
-        func(&self.internal_data);
+        func(&self.internal_data, &self.payload);
         if let Some(right) = self.right.as_ref() {
             right.traverse_branch(func);
         }
@@ -139,15 +482,74 @@ fn traverse_branch_mut<F: FnMut(&mut Point<T, N>)>(&mut self, func: &mut F) {
 
 /// The Actual K-Dimensional Tree struct, contains it's first node.
 ///
+/// Each point in the tree carries a payload, which is returned alongside it by the `_with_payload` queries.
+/// This is usually the index of the point in its original point cloud, see [`KDTree::from_indexed`],
+/// or any per-point data such as intensity, timestamp or label.
+///
 /// # Generics
 /// `T`: Either an [`f32`] or [`f64`]
 /// `N`: a const usize specifying how many dimensions should each point have.
-#[derive(Clone, Debug, Default)]
-pub struct KDTree<T, const N: usize>
+/// `P`: the payload type of each point, defaults to `()` for a tree of bare points.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "serde",
+    serde(
+        from = "SerializedKDTree<T, N, P>",
+        into = "SerializedKDTree<T, N, P>",
+        bound = "T: Copy + Default + NumOps + PartialOrd + Scalar + Zero + serde::Serialize + serde::de::DeserializeOwned, P: Clone + serde::Serialize + serde::de::DeserializeOwned"
+    )
+)]
+pub struct KDTree<T, const N: usize, P = ()>
+where
+    T: Copy + Default + NumOps + PartialOrd + Scalar + Zero,
+{
+    root: Option<Box<KDNode<T, N, P>>>,
+}
+
+/// The serialized form of a [`KDTree`], its branches are rebuilt from the points and their payloads when deserializing.
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(
+    bound = "T: Scalar + serde::Serialize + serde::de::DeserializeOwned, P: serde::Serialize + serde::de::DeserializeOwned"
+)]
+struct SerializedKDTree<T: Scalar, const N: usize, P> {
+    entries: Vec<(Point<T, N>, P)>,
+}
+
+#[cfg(feature = "serde")]
+impl<T, const N: usize, P> From<KDTree<T, N, P>> for SerializedKDTree<T, N, P>
 where
     T: Copy + Default + NumOps + PartialOrd + Scalar + Zero,
 {
-    root: Option<KDNode<T, N>>,
+    fn from(tree: KDTree<T, N, P>) -> Self {
+        let mut entries = Vec::new();
+        if let Some(root) = tree.root {
+            root.into_entries(&mut entries);
+        }
+        Self { entries }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<T, const N: usize, P> From<SerializedKDTree<T, N, P>> for KDTree<T, N, P>
+where
+    T: Copy + Default + NumOps + PartialOrd + Scalar + Zero,
+{
+    fn from(serialized: SerializedKDTree<T, N, P>) -> Self {
+        Self {
+            root: KDNode::build(serialized.entries, 0),
+        }
+    }
+}
+
+impl<T, const N: usize, P> Default for KDTree<T, N, P>
+where
+    T: Copy + Default + NumOps + PartialOrd + Scalar + Zero,
+{
+    fn default() -> Self {
+        Self { root: None }
+    }
 }
 
 impl<T, const N: usize> KDTree<T, N>
@@ -163,11 +565,102 @@ impl<T, const N: usize> KDTree<T, N>
         tracing::instrument("Insert To Tree", skip_all, level = "debug")
     )]
     pub fn insert(&mut self, data: Point<T, N>) {
+        self.insert_with_payload(data, ());
+    }
+}
+
+impl<T, const N: usize> KDTree<T, N, usize>
+where
+    T: Copy + Default + NumOps + PartialOrd + Scalar + Zero,
+{
+    /// Builds a balanced tree from a point cloud, where the payload of each point is its index in `point_cloud`.
+    ///
+    /// # Arguments
+    /// * `point_cloud`: a slice of [`Point`], representing the point cloud.
+    ///
+    /// # Returns
+    /// A [`KDTree`] whose payloads are indices into `point_cloud`.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument("Generate Indexed Tree From Point Cloud", skip_all, level = "info")
+    )]
+    pub fn from_indexed(point_cloud: &[Point<T, N>]) -> Self {
+        point_cloud
+            .iter()
+            .copied()
+            .enumerate()
+            .map(|(idx, point)| (point, idx))
+            .collect()
+    }
+}
+
+impl<T, const N: usize, P> KDTree<T, N, P>
+where
+    T: Copy + Default + NumOps + PartialOrd + Scalar + Zero,
+{
+    /// Inserts a new data point and its payload into the tree, taking into consideration it's position.
+    ///
+    /// # Arguments
+    /// * `data`: a [`Point`], to be inserted into the tree.
+    /// * `payload`: the payload to store alongside `data`.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument("Insert To Tree With Payload", skip_all, level = "debug")
+    )]
+    pub fn insert_with_payload(&mut self, data: Point<T, N>, payload: P) {
         if let Some(root) = self.root.as_mut() {
-            root.insert(data, 0);
+            root.insert(data, payload, 0);
         } else {
-            self.root = Some(KDNode::new(data));
+            self.root = Some(Box::new(KDNode::new(data, payload)));
+        }
+    }
+
+    /// Removes a point from the tree, if it exists.
+    /// If the tree holds several identical points, only one of them is removed.
+    /// The removed point is replaced by a point from its sub-branches, without rebuilding them,
+    /// but the tree is not rebalanced, so prefer [`retain`](Self::retain) when removing many points at once.
+    ///
+    /// # Arguments
+    /// * `data`: a [`Point`], to be removed from the tree.
+    ///
+    /// # Returns
+    /// The payload of the removed point, or [`None`] if the point was not found in the tree.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument("Remove From Tree", skip_all, level = "debug")
+    )]
+    pub fn remove(&mut self, data: &Point<T, N>) -> Option<P> {
+        KDNode::remove(&mut self.root, data, 0, &|node: &KDNode<T, N, P>| {
+            node.internal_data == *data
+        })
+        .map(|(_, payload)| payload)
+    }
+
+    /// Keeps only the points for which `func` returns `true`, removing all others, and rebalances the tree.
+    ///
+    /// # Arguments
+    /// * `func`: a closure of type [`FnMut`], receiving a reference to each [`Point`] and its payload.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument("Retain Tree Points", skip_all, level = "info")
+    )]
+    pub fn retain<F: FnMut(&Point<T, N>, &P) -> bool>(&mut self, mut func: F) {
+        let mut entries = Vec::new();
+        if let Some(root) = self.root.take() {
+            root.into_entries(&mut entries);
         }
+        entries.retain(|(point, payload)| func(point, payload));
+        self.root = KDNode::build(entries, 0);
+    }
+
+    /// Rebuilds the tree so that it is balanced, which keeps queries fast after many calls to [`insert_with_payload`](Self::insert_with_payload),
+    /// as inserting points in a sorted or scan order makes the tree degenerate into a list.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument("Rebalance Tree", skip_all, level = "info")
+    )]
+    pub fn rebalance(&mut self) {
+        self.retain(|_, _| true);
     }
 
     /// Attempts to find the nearest point in the tree for the specified target point.
@@ -181,7 +674,256 @@ pub fn insert(&mut self, data: Point<T, N>) {
         tracing::instrument("Find Nearest Neighbour", skip_all, level = "debug")
     )]
     pub fn nearest(&self, target: &Point<T, N>) -> Option<Point<T, N>> {
-        self.root.as_ref().and_then(|root| root.nearest(target, 0))
+        self.nearest_with_payload(target).map(|(point, _)| point)
+    }
+
+    /// Attempts to find the nearest point in the tree for the specified target point, along with its payload.
+    /// # Arguments
+    /// * `target`: a [`Point`], to search the closest point for.
+    ///
+    /// # Returns
+    /// [`None`] if the tree is empty, otherwise returns the closest [`Point`] and a reference to its payload.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument("Find Nearest Neighbour With Payload", skip_all, level = "debug")
+    )]
+    pub fn nearest_with_payload(&self, target: &Point<T, N>) -> Option<(Point<T, N>, &P)> {
+        self.root
+            .as_ref()
+            .and_then(|root| root.nearest(target, 0))
+            .map(|node| (node.internal_data, &node.payload))
+    }
+
+    /// Attempts to find the nearest point in the tree for the specified target point, along with its payload,
+    /// exploring the tree according to `search_mode`.
+    ///
+    /// # Arguments
+    /// * `target`: a [`Point`], to search the closest point for.
+    /// * `search_mode`: a [`SearchMode`], specifying whether the search may return an approximate nearest neighbour.
+    ///
+    /// # Returns
+    /// [`None`] if the tree is empty or `search_mode` is not [valid](SearchMode::is_valid),
+    /// otherwise returns the found [`Point`] and a reference to its payload.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument("Search Nearest Neighbour", skip_all, level = "debug")
+    )]
+    pub fn search_nearest(
+        &self,
+        target: &Point<T, N>,
+        search_mode: SearchMode<T>,
+    ) -> Option<(Point<T, N>, &P)>
+    where
+        T: One,
+    {
+        let (axis_distance_factor, mut checks_left) = search_mode.pruning_parameters()?;
+        let mut best = None;
+        if let Some(root) = self.root.as_ref() {
+            root.search_nearest(target, 0, axis_distance_factor, &mut checks_left, &mut best);
+        }
+
+        best.map(|(_, node)| (node.internal_data, &node.payload))
+    }
+
+    /// Finds the `k` nearest points in the tree for the specified target point.
+    /// The candidates are kept in a bounded max-heap, and a branch is only visited if it may contain a point closer than the farthest candidate.
+    ///
+    /// # Arguments
+    /// * `target`: a [`Point`], to search the closest points for.
+    /// * `k`: the maximum amount of points to return.
+    ///
+    /// # Returns
+    /// A [`Vec`] of up to `k` tuples, each containing a [`Point`] and its squared distance from `target`, sorted from nearest to farthest.
+    /// The [`Vec`] is empty if the tree is empty or `k` is `0`, and holds every point in the tree if `k` is larger than the tree.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument("Find K Nearest Neighbours", skip_all, level = "debug")
+    )]
+    pub fn k_nearest(&self, target: &Point<T, N>, k: usize) -> Vec<(Point<T, N>, T)> {
+        self.k_nearest_with_payload(target, k)
+            .into_iter()
+            .map(|(point, distance, _)| (point, distance))
+            .collect()
+    }
+
+    /// Finds the `k` nearest points in the tree for the specified target point, along with their payloads.
+    ///
+    /// # Arguments
+    /// * `target`: a [`Point`], to search the closest points for.
+    /// * `k`: the maximum amount of points to return.
+    ///
+    /// # Returns
+    /// A [`Vec`] of up to `k` tuples, each containing a [`Point`], its squared distance from `target` and a reference to its payload,
+    /// sorted from nearest to farthest.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument("Find K Nearest Neighbours With Payload", skip_all, level = "debug")
+    )]
+    pub fn k_nearest_with_payload(
+        &self,
+        target: &Point<T, N>,
+        k: usize,
+    ) -> Vec<(Point<T, N>, T, &P)> {
+        if k == 0 {
+            return Vec::new();
+        }
+
+        let mut heap = BinaryHeap::with_capacity(k + 1);
+        if let Some(root) = self.root.as_ref() {
+            root.k_nearest(target, k, 0, &mut heap);
+        }
+
+        heap.into_sorted_vec()
+            .into_iter()
+            .map(|element| {
+                (
+                    element.item.internal_data,
+                    element.distance_squared,
+                    &element.item.payload,
+                )
+            })
+            .collect()
+    }
+
+    /// Lazily finds all points in the tree that are within `radius` of the specified target point.
+    /// Branches which are entirely outside of the radius are never visited.
+    ///
+    /// # Arguments
+    /// * `target`: a [`Point`], to search around.
+    /// * `radius`: the maximum distance of a point from `target`, inclusive.
+    ///
+    /// # Returns
+    /// An [`Iterator`], yielding every [`Point`] within the radius, in no particular order.
+    pub fn within_radius_iter(
+        &self,
+        target: &Point<T, N>,
+        radius: T,
+    ) -> impl Iterator<Item = Point<T, N>> + '_ {
+        self.within_radius_with_payload_iter(target, radius)
+            .map(|(point, _)| point)
+    }
+
+    /// Lazily finds all points in the tree that are within `radius` of the specified target point, along with their payloads.
+    ///
+    /// # Arguments
+    /// * `target`: a [`Point`], to search around.
+    /// * `radius`: the maximum distance of a point from `target`, inclusive.
+    ///
+    /// # Returns
+    /// A [`WithinRadiusIter`], yielding every [`Point`] within the radius and a reference to its payload, in no particular order.
+    pub fn within_radius_with_payload_iter(
+        &self,
+        target: &Point<T, N>,
+        radius: T,
+    ) -> WithinRadiusIter<'_, T, N, P> {
+        WithinRadiusIter {
+            stack: self.root.iter().map(|root| (root.as_ref(), 0)).collect(),
+            target: *target,
+            radius_squared: radius * radius,
+        }
+    }
+
+    /// Finds all points in the tree that are within `radius` of the specified target point.
+    ///
+    /// # Arguments
+    /// * `target`: a [`Point`], to search around.
+    /// * `radius`: the maximum distance of a point from `target`, inclusive.
+    ///
+    /// # Returns
+    /// A [`Vec`] of all [`Point`]s within the radius, in no particular order.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument("Find Points Within Radius", skip_all, level = "debug")
+    )]
+    pub fn within_radius(&self, target: &Point<T, N>, radius: T) -> Vec<Point<T, N>> {
+        self.within_radius_iter(target, radius).collect()
+    }
+
+    /// Finds all points in the tree that are within `radius` of the specified target point, along with their payloads.
+    ///
+    /// # Arguments
+    /// * `target`: a [`Point`], to search around.
+    /// * `radius`: the maximum distance of a point from `target`, inclusive.
+    ///
+    /// # Returns
+    /// A [`Vec`] of all [`Point`]s within the radius and references to their payloads, in no particular order.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument("Find Points Within Radius With Payload", skip_all, level = "debug")
+    )]
+    pub fn within_radius_with_payload(
+        &self,
+        target: &Point<T, N>,
+        radius: T,
+    ) -> Vec<(Point<T, N>, &P)> {
+        self.within_radius_with_payload_iter(target, radius)
+            .collect()
+    }
+
+    /// Lazily finds all points in the tree that are inside the axis-aligned box described by `extents`.
+    /// Branches which are entirely outside of the box are never visited.
+    ///
+    /// # Arguments
+    /// * `extents`: a [`PolygonExtents`], containing the inclusive range of each dimension of the box.
+    ///
+    /// # Returns
+    /// An [`Iterator`], yielding every [`Point`] inside the box, in no particular order.
+    pub fn within_extents_iter(
+        &self,
+        extents: &PolygonExtents<T, N>,
+    ) -> impl Iterator<Item = Point<T, N>> + '_ {
+        self.within_extents_with_payload_iter(extents)
+            .map(|(point, _)| point)
+    }
+
+    /// Lazily finds all points in the tree that are inside the axis-aligned box described by `extents`, along with their payloads.
+    ///
+    /// # Arguments
+    /// * `extents`: a [`PolygonExtents`], containing the inclusive range of each dimension of the box.
+    ///
+    /// # Returns
+    /// A [`WithinExtentsIter`], yielding every [`Point`] inside the box and a reference to its payload, in no particular order.
+    pub fn within_extents_with_payload_iter(
+        &self,
+        extents: &PolygonExtents<T, N>,
+    ) -> WithinExtentsIter<'_, T, N, P> {
+        WithinExtentsIter {
+            stack: self.root.iter().map(|root| (root.as_ref(), 0)).collect(),
+            extents: extents.clone(),
+        }
+    }
+
+    /// Finds all points in the tree that are inside the axis-aligned box described by `extents`.
+    ///
+    /// # Arguments
+    /// * `extents`: a [`PolygonExtents`], containing the inclusive range of each dimension of the box.
+    ///
+    /// # Returns
+    /// A [`Vec`] of all [`Point`]s inside the box, in no particular order.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument("Find Points Within Extents", skip_all, level = "debug")
+    )]
+    pub fn within_extents(&self, extents: &PolygonExtents<T, N>) -> Vec<Point<T, N>> {
+        self.within_extents_iter(extents).collect()
+    }
+
+    /// Finds all points in the tree that are inside the axis-aligned box described by `extents`, along with their payloads.
+    ///
+    /// # Arguments
+    /// * `extents`: a [`PolygonExtents`], containing the inclusive range of each dimension of the box.
+    ///
+    /// # Returns
+    /// A [`Vec`] of all [`Point`]s inside the box and references to their payloads, in no particular order.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument("Find Points Within Extents With Payload", skip_all, level = "debug")
+    )]
+    pub fn within_extents_with_payload(
+        &self,
+        extents: &PolygonExtents<T, N>,
+    ) -> Vec<(Point<T, N>, &P)> {
+        self.within_extents_with_payload_iter(extents).collect()
     }
 
     /// Allows traversal of the entire tree structure, calling the `func` closure on each branch's data.
@@ -193,6 +935,18 @@ pub fn nearest(&self, target: &Point<T, N>) -> Option<Point<T, N>> {
         tracing::instrument("Traverse Tree With Function", skip_all, level = "info")
     )]
     pub fn traverse_tree<F: FnMut(&Point<T, N>)>(&self, mut func: F) {
+        self.traverse_tree_with_payload(|point, _| func(point));
+    }
+
+    /// Allows traversal of the entire tree structure, calling the `func` closure on each branch's data and payload.
+    ///
+    /// # Arguments
+    /// * `func`: a closure of type [`FnMut`], receiving a reference of the branch's [`Point`] and its payload.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument("Traverse Tree With Payload Function", skip_all, level = "info")
+    )]
+    pub fn traverse_tree_with_payload<F: FnMut(&Point<T, N>, &P)>(&self, mut func: F) {
         if let Some(root) = self.root.as_ref() {
             root.traverse_branch(&mut func);
         }
@@ -213,6 +967,113 @@ pub fn traverse_tree_mut<F: FnMut(&mut Point<T, N>)>(&mut self, mut func: F) {
     }
 }
 
+/// An iterator over all points of a [`KDTree`] within a radius of a target point, along with their payloads,
+/// see [`KDTree::within_radius_with_payload_iter`].
+///
+/// # Generics
+/// `T`: Either an [`f32`] or [`f64`]
+/// `N`: a const usize specifying how many dimensions should each point have.
+/// `P`: the payload type of each point.
+#[derive(Debug)]
+pub struct WithinRadiusIter<'a, T, const N: usize, P>
+where
+    T: Copy + Default + NumOps + PartialOrd + Scalar + Zero,
+{
+    stack: Vec<(&'a KDNode<T, N, P>, usize)>,
+    target: Point<T, N>,
+    radius_squared: T,
+}
+
+impl<'a, T, const N: usize, P> Iterator for WithinRadiusIter<'a, T, N, P>
+where
+    T: Copy + Default + NumOps + PartialOrd + Scalar + Zero,
+{
+    type Item = (Point<T, N>, &'a P);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some((node, depth)) = self.stack.pop() {
+            let dimension_to_check = depth % N;
+            let target_coord = self.target.coords[dimension_to_check];
+            let node_coord = node.internal_data.coords[dimension_to_check];
+
+            // A branch on the other side of the splitting plane is only relevant if the plane is within the radius
+            let axis_distance = target_coord - node_coord;
+            let crosses_plane = (axis_distance * axis_distance) <= self.radius_squared;
+            if let Some(left) = node.left.as_deref() {
+                if target_coord < node_coord || crosses_plane {
+                    self.stack.push((left, depth + 1));
+                }
+            }
+            if let Some(right) = node.right.as_deref() {
+                if target_coord >= node_coord || crosses_plane {
+                    self.stack.push((right, depth + 1));
+                }
+            }
+
+            if distance_squared(&node.internal_data, &self.target) <= self.radius_squared {
+                return Some((node.internal_data, &node.payload));
+            }
+        }
+
+        None
+    }
+}
+
+/// An iterator over all points of a [`KDTree`] inside an axis-aligned box, along with their payloads,
+/// see [`KDTree::within_extents_with_payload_iter`].
+///
+/// # Generics
+/// `T`: Either an [`f32`] or [`f64`]
+/// `N`: a const usize specifying how many dimensions should each point have.
+/// `P`: the payload type of each point.
+#[derive(Debug)]
+pub struct WithinExtentsIter<'a, T, const N: usize, P>
+where
+    T: Copy + Default + NumOps + PartialOrd + Scalar + Zero,
+{
+    stack: Vec<(&'a KDNode<T, N, P>, usize)>,
+    extents: PolygonExtents<T, N>,
+}
+
+impl<'a, T, const N: usize, P> Iterator for WithinExtentsIter<'a, T, N, P>
+where
+    T: Copy + Default + NumOps + PartialOrd + Scalar + Zero,
+{
+    type Item = (Point<T, N>, &'a P);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some((node, depth)) = self.stack.pop() {
+            let dimension_to_check = depth % N;
+            let extent = &self.extents[dimension_to_check];
+            let node_coord = node.internal_data.coords[dimension_to_check];
+
+            // The left branch only holds smaller coordinates, and the right branch only holds larger or equal ones
+            if let Some(left) = node.left.as_deref() {
+                if *extent.start() < node_coord {
+                    self.stack.push((left, depth + 1));
+                }
+            }
+            if let Some(right) = node.right.as_deref() {
+                if *extent.end() >= node_coord {
+                    self.stack.push((right, depth + 1));
+                }
+            }
+
+            if node
+                .internal_data
+                .coords
+                .iter()
+                .zip(self.extents.iter())
+                .all(|(coord, extent)| extent.contains(coord))
+            {
+                return Some((node.internal_data, &node.payload));
+            }
+        }
+
+        None
+    }
+}
+
 impl<T, const N: usize> From<&[Point<T, N>]> for KDTree<T, N>
 where
     T: Copy + Default + NumOps + PartialOrd + Scalar + Zero,
@@ -222,13 +1083,22 @@ impl<T, const N: usize> From<&[Point<T, N>]> for KDTree<T, N>
         tracing::instrument("Generate Tree From Point Cloud", skip_all, level = "info")
     )]
     fn from(point_cloud: &[Point<T, N>]) -> Self {
-        point_cloud
-            .iter()
-            .copied()
-            .fold(Self::default(), |mut tree, current_point| {
-                tree.insert(current_point);
-                tree
-            })
+        point_cloud.iter().map(|point| (*point, ())).collect()
+    }
+}
+
+impl<T, const N: usize, P> FromIterator<(Point<T, N>, P)> for KDTree<T, N, P>
+where
+    T: Copy + Default + NumOps + PartialOrd + Scalar + Zero,
+{
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument("Generate Tree From Payload Iterator", skip_all, level = "info")
+    )]
+    fn from_iter<I: IntoIterator<Item = (Point<T, N>, P)>>(iter: I) -> Self {
+        Self {
+            root: KDNode::build(iter.into_iter().collect(), 0),
+        }
     }
 }
 
@@ -357,6 +1227,380 @@ fn compare_nearest_with_naive_version() {
         assert_eq!(closest_points_naive, closest_point_kd);
     }
 
+    #[test]
+    fn test_k_nearest() {
+        // Test an empty tree
+        {
+            let tree = KDTree::<f32, 2>::default();
+            assert!(tree.k_nearest(&Point2::new(0.0, 0.0), 3).is_empty())
+        }
+
+        let tree = generate_tree();
+        assert!(tree.k_nearest(&Point3::new(0.0, 0.0, 0.0), 0).is_empty());
+
+        let k_nearest = tree.k_nearest(&Point3::new(1.32, 2.7, 0.2), 2);
+        assert_eq!(k_nearest.len(), 2);
+        assert_eq!(k_nearest[0].0, Point3::new(1.3, 2.5, 0.5));
+        assert_eq!(k_nearest[1].0, Point3::new(0.0, 2.0, 1.0));
+        assert!((k_nearest[0].1 - 0.1304).abs() < 1e-5);
+
+        // Asking for more points than there are returns the entire tree, sorted
+        let k_nearest = tree.k_nearest(&Point3::new(1.32, 2.7, 0.2), 10);
+        assert_eq!(k_nearest.len(), 4);
+        assert_eq!(k_nearest[3].0, Point3::new(-2.1, 0.2, -0.2));
+    }
+
+    #[test]
+    fn compare_k_nearest_with_naive_version() {
+        let points = (0..200)
+            .map(|idx| {
+                let idx = idx as f32;
+                Point3::new(
+                    (idx * 7.3).sin() * 10.0,
+                    (idx * 3.1).cos() * 10.0,
+                    (idx * 1.7).sin() * 5.0,
+                )
+            })
+            .collect::<Vec<_>>();
+        let tree = KDTree::from(points.as_slice());
+
+        for target in [
+            Point3::new(0.0, 0.0, 0.0),
+            Point3::new(4.2, -3.3, 1.0),
+            Point3::new(-20.0, 15.0, -7.5),
+        ] {
+            let mut naive = points
+                .iter()
+                .map(|point| distance_squared(point, &target))
+                .collect::<Vec<_>>();
+            naive.sort_by(|a, b| a.partial_cmp(b).unwrap());
+
+            let k_nearest = tree.k_nearest(&target, 15);
+            assert_eq!(k_nearest.len(), 15);
+            for ((point, distance), naive_distance) in k_nearest.iter().zip(naive.iter()) {
+                assert_eq!(*distance, distance_squared(point, &target));
+                assert_eq!(distance, naive_distance);
+            }
+        }
+    }
+
+    #[test]
+    fn test_within_radius() {
+        // Test an empty tree
+        {
+            let tree = KDTree::<f32, 2>::default();
+            assert!(tree.within_radius(&Point2::new(0.0, 0.0), 5.0).is_empty())
+        }
+
+        let tree = generate_tree();
+        let mut within_radius = tree.within_radius(&Point3::new(0.5, 2.0, 1.0), 1.5);
+        within_radius.sort_by(|a, b| a.x.partial_cmp(&b.x).unwrap());
+        assert_eq!(
+            within_radius,
+            [Point3::new(0.0, 2.0, 1.0), Point3::new(1.3, 2.5, 0.5)]
+        );
+
+        // The radius is inclusive
+        assert_eq!(
+            tree.within_radius_iter(&Point3::new(0.0, 2.0, 0.0), 1.0)
+                .collect::<Vec<_>>(),
+            [Point3::new(0.0, 2.0, 1.0)]
+        );
+    }
+
+    #[test]
+    fn test_within_extents() {
+        // Test an empty tree
+        {
+            let tree = KDTree::<f32, 2>::default();
+            assert!(tree.within_extents(&[-1.0..=1.0, -1.0..=1.0]).is_empty())
+        }
+
+        let tree = generate_tree();
+        let mut within_extents = tree.within_extents(&[-1.0..=2.0, 1.0..=5.0, 0.0..=2.5]);
+        within_extents.sort_by(|a, b| a.x.partial_cmp(&b.x).unwrap());
+        assert_eq!(
+            within_extents,
+            [
+                Point3::new(-1.0, 4.0, 2.5),
+                Point3::new(0.0, 2.0, 1.0),
+                Point3::new(1.3, 2.5, 0.5)
+            ]
+        );
+
+        assert_eq!(
+            tree.within_extents_iter(&[-3.0..=-2.0, 0.0..=1.0, -1.0..=0.0])
+                .collect::<Vec<_>>(),
+            [Point3::new(-2.1, 0.2, -0.2)]
+        );
+    }
+
+    #[test]
+    fn compare_range_queries_with_naive_version() {
+        let points = (0..200)
+            .map(|idx| {
+                let idx = idx as f32;
+                Point3::new(
+                    (idx * 7.3).sin() * 10.0,
+                    (idx * 3.1).cos() * 10.0,
+                    (idx * 1.7).sin() * 5.0,
+                )
+            })
+            .collect::<Vec<_>>();
+        let tree = KDTree::from(points.as_slice());
+
+        let target = Point3::new(1.0, -2.0, 0.5);
+        let within_radius = tree.within_radius(&target, 6.0);
+        let naive_within_radius = points
+            .iter()
+            .filter(|point| distance_squared(point, &target) <= 36.0)
+            .count();
+        assert_eq!(within_radius.len(), naive_within_radius);
+        assert!(within_radius
+            .iter()
+            .all(|point| distance_squared(point, &target) <= 36.0));
+
+        let extents = [-3.0..=4.0, -8.0..=0.0, -1.0..=5.0];
+        let within_extents = tree.within_extents(&extents);
+        let naive_within_extents = points
+            .iter()
+            .filter(|point| {
+                point
+                    .coords
+                    .iter()
+                    .zip(extents.iter())
+                    .all(|(coord, extent)| extent.contains(coord))
+            })
+            .count();
+        assert_eq!(within_extents.len(), naive_within_extents);
+    }
+
+    fn branch_depth<T, const N: usize, P>(branch: Option<&KDNode<T, N, P>>) -> usize
+    where
+        T: Copy + Default + NumOps + PartialOrd + Scalar + Zero,
+    {
+        branch.map_or(0, |node| {
+            1 + branch_depth(node.left.as_deref()).max(branch_depth(node.right.as_deref()))
+        })
+    }
+
+    #[test]
+    fn test_balanced_build() {
+        // Sorted input would create a list-like tree if inserted one by one
+        let points = (0..1023)
+            .map(|idx| Point2::new(idx as f32, (2000 - idx) as f32))
+            .collect::<Vec<_>>();
+
+        let tree = KDTree::from(points.as_slice());
+        assert_eq!(branch_depth(tree.root.as_deref()), 10);
+
+        let mut count = 0;
+        tree.traverse_tree(|_| count += 1);
+        assert_eq!(count, points.len());
+
+        for point in points.iter().step_by(37) {
+            assert_eq!(tree.nearest(point), Some(*point));
+        }
+
+        // Duplicate coordinates must still respect the branch ordering
+        let duplicates = [Point2::new(1.0f32, 0.0); 10];
+        let tree = KDTree::from(duplicates.as_slice());
+        assert_eq!(tree.k_nearest(&Point2::new(1.0, 0.0), 20).len(), 10);
+        assert_eq!(tree.within_extents(&[1.0..=1.0, 0.0..=0.0]).len(), 10);
+    }
+
+    #[test]
+    fn test_rebalance() {
+        let mut tree = KDTree::default();
+        for idx in 0..100 {
+            tree.insert(Point3::new(idx as f32, idx as f32, idx as f32));
+        }
+        assert_eq!(branch_depth(tree.root.as_deref()), 100);
+
+        tree.rebalance();
+        assert_eq!(branch_depth(tree.root.as_deref()), 7);
+        assert_eq!(
+            tree.nearest(&Point3::new(41.2, 41.0, 40.9)),
+            Some(Point3::new(41.0, 41.0, 41.0))
+        );
+
+        let mut empty_tree = KDTree::<f32, 2>::default();
+        empty_tree.rebalance();
+        assert!(empty_tree.root.is_none());
+    }
+
+    #[test]
+    fn test_payload_queries() {
+        let points = [
+            Point2::new(0.0f32, 0.0),
+            Point2::new(1.0, 1.0),
+            Point2::new(-2.0, 0.5),
+            Point2::new(3.0, -1.0),
+        ];
+        let tree = KDTree::from_indexed(&points);
+
+        assert_eq!(
+            tree.nearest_with_payload(&Point2::new(2.8, -0.7)),
+            Some((Point2::new(3.0, -1.0), &3))
+        );
+        assert_eq!(
+            tree.k_nearest_with_payload(&Point2::new(0.1, 0.1), 2)
+                .into_iter()
+                .map(|(_, _, idx)| *idx)
+                .collect::<Vec<_>>(),
+            [0, 1]
+        );
+
+        let mut within_radius = tree
+            .within_radius_with_payload(&Point2::new(0.0, 0.0), 1.5)
+            .into_iter()
+            .map(|(_, idx)| *idx)
+            .collect::<Vec<_>>();
+        within_radius.sort();
+        assert_eq!(within_radius, [0, 1]);
+
+        let within_extents = tree.within_extents_with_payload(&[-3.0..=-1.0, 0.0..=1.0]);
+        assert_eq!(within_extents, [(Point2::new(-2.0, 0.5), &2)]);
+
+        let mut payload_sum = 0;
+        tree.traverse_tree_with_payload(|point, idx| {
+            assert_eq!(points[*idx], *point);
+            payload_sum += idx;
+        });
+        assert_eq!(payload_sum, 6);
+
+        // Any payload type may be used
+        let labelled_tree = points
+            .iter()
+            .zip(["origin", "up", "left", "right"])
+            .map(|(point, label)| (*point, label))
+            .collect::<KDTree<f32, 2, &str>>();
+        assert_eq!(
+            labelled_tree.nearest_with_payload(&Point2::new(-1.5, 0.0)),
+            Some((Point2::new(-2.0, 0.5), &"left"))
+        );
+    }
+
+    #[test]
+    fn test_remove() {
+        let points = (0..50)
+            .map(|idx| Point3::new(idx as f32, (idx * 7 % 50) as f32, (idx % 5) as f32))
+            .collect::<Vec<_>>();
+        let mut tree = KDTree::from_indexed(&points);
+
+        assert_eq!(tree.remove(&Point3::new(100.0, 0.0, 0.0)), None);
+        for (idx, point) in points.iter().enumerate().step_by(3) {
+            assert_eq!(tree.remove(point), Some(idx));
+            assert_eq!(tree.remove(point), None);
+        }
+
+        // Removed points should no longer be found, while every other point still should
+        for (idx, point) in points.iter().enumerate() {
+            let (nearest, nearest_idx) = tree.nearest_with_payload(point).unwrap();
+            if idx % 3 == 0 {
+                assert_ne!(nearest, *point);
+            } else {
+                assert_eq!((nearest, *nearest_idx), (*point, idx));
+            }
+        }
+
+        // Identical points are removed one at a time, each along with its own payload
+        let mut tree = (0..30)
+            .map(|idx| (Point2::new((idx % 3) as f32, (idx % 2) as f32), idx))
+            .collect::<KDTree<f32, 2, usize>>();
+        let mut removed = (0..30)
+            .filter_map(|idx| tree.remove(&Point2::new((idx % 3) as f32, (idx % 2) as f32)))
+            .collect::<Vec<_>>();
+        removed.sort();
+        assert_eq!(removed, (0..30).collect::<Vec<_>>());
+        assert!(tree.root.is_none());
+
+        // Removing the root of a single point tree empties it
+        let mut tree = KDTree::default();
+        tree.insert(Point2::new(1.0f32, 2.0));
+        assert_eq!(tree.remove(&Point2::new(1.0, 2.0)), Some(()));
+        assert!(tree.root.is_none());
+    }
+
+    #[test]
+    fn test_retain() {
+        let points = (0..100)
+            .map(|idx| Point2::new(idx as f32, -idx as f32))
+            .collect::<Vec<_>>();
+        let mut tree = KDTree::from_indexed(&points);
+        tree.retain(|point, idx| point.x < 80.0 && idx % 2 == 0);
+
+        let mut remaining = Vec::new();
+        tree.traverse_tree_with_payload(|_, idx| remaining.push(*idx));
+        remaining.sort();
+        assert_eq!(remaining, (0..80).step_by(2).collect::<Vec<_>>());
+        assert_eq!(branch_depth(tree.root.as_deref()), 6);
+        assert_eq!(
+            tree.nearest_with_payload(&Point2::new(95.0, -95.0)),
+            Some((Point2::new(78.0, -78.0), &78))
+        );
+    }
+
+    #[test]
+    fn test_search_nearest_error_bound() {
+        let points = (0..1000)
+            .map(|idx| {
+                let idx = idx as f64;
+                Point3::new(
+                    (idx * 7.3).sin() * 10.0,
+                    (idx * 3.1).cos() * 10.0,
+                    (idx * 1.7).sin() * 5.0,
+                )
+            })
+            .collect::<Vec<_>>();
+        let tree = KDTree::from_indexed(&points);
+        let targets = (0..200)
+            .map(|idx| {
+                let idx = idx as f64;
+                Point3::new(
+                    (idx * 2.9).cos() * 12.0,
+                    (idx * 5.3).sin() * 12.0,
+                    (idx * 0.7).cos() * 6.0,
+                )
+            })
+            .collect::<Vec<_>>();
+
+        assert!(KDTree::<f64, 3>::default()
+            .search_nearest(&targets[0], SearchMode::Exact)
+            .is_none());
+
+        for target in targets.iter() {
+            let exact_distance =
+                distance_squared(&find_closest_point(target, &points), target).sqrt();
+
+            let (nearest, idx) = tree.search_nearest(target, SearchMode::Exact).unwrap();
+            assert_eq!(distance_squared(&nearest, target).sqrt(), exact_distance);
+            assert_eq!(points[*idx], nearest);
+
+            for epsilon in [0.0, 0.1, 0.5, 2.0] {
+                let (nearest, _) = tree
+                    .search_nearest(target, SearchMode::Approximate { epsilon })
+                    .unwrap();
+                assert!(
+                    distance_squared(&nearest, target).sqrt()
+                        <= exact_distance * (1.0 + epsilon) + 1e-9
+                );
+            }
+
+            // A budget large enough to visit the entire tree is an exact search
+            let (nearest, _) = tree
+                .search_nearest(target, SearchMode::MaxLeafChecks(points.len()))
+                .unwrap();
+            assert_eq!(distance_squared(&nearest, target).sqrt(), exact_distance);
+            assert!(tree
+                .search_nearest(target, SearchMode::MaxLeafChecks(0))
+                .is_none());
+            assert!(tree
+                .search_nearest(target, SearchMode::Approximate { epsilon: -1.5 })
+                .is_none());
+        }
+    }
+
     #[test]
     fn test_traverse_tree() {
         let tree = generate_tree();
@@ -381,4 +1625,39 @@ fn test_traverse_tree_mut() {
             assert_eq!(point.z, 1.0);
         });
     }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_round_trip() {
+        let tree = generate_tree();
+        let serialized = serde_json::to_string(&tree).unwrap();
+        let deserialized: KDTree<f32, 3> = serde_json::from_str(&serialized).unwrap();
+
+        // The tree is rebuilt balanced, so only the set of points is preserved, not their layout
+        let sort_points = |tree: &KDTree<f32, 3>| {
+            let mut points = Vec::new();
+            tree.traverse_tree(|point| points.push(*point));
+            points.sort_by(|a, b| {
+                a.coords
+                    .as_slice()
+                    .partial_cmp(b.coords.as_slice())
+                    .unwrap()
+            });
+            points
+        };
+        assert_eq!(sort_points(&tree), sort_points(&deserialized));
+        assert_eq!(
+            deserialized.nearest(&Point3::new(1.32, 2.7, 0.2)),
+            Some(Point3::new(1.3, 2.5, 0.5))
+        );
+
+        let deserialized: KDTree<f64, 2, usize> = serde_json::from_str(
+            r#"{"entries":[[[3.0,3.0],0],[[0.0,0.0],1],[[1.0,2.0],2],[[-1.0,5.0],3]]}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            deserialized.nearest_with_payload(&Point2::new(0.9, 1.8)),
+            Some((Point2::new(1.0, 2.0), &2))
+        );
+    }
 }
@@ -26,7 +26,6 @@
 #![deny(rustdoc::broken_intra_doc_links)]
 #![deny(rustdoc::private_intra_doc_links)]
 #![cfg_attr(not(feature = "std"), no_std)]
-
 #![doc = include_str!("../../../README.md")]
 
 #[cfg(not(feature = "std"))]
@@ -35,18 +34,45 @@
 extern crate core;
 
 #[cfg(feature = "std")]
-use std::{array, boxed::Box, collections::HashMap, fmt::Debug, iter::Sum, marker, ops, vec::Vec};
+use std::{
+    array,
+    boxed::Box,
+    collections::{BinaryHeap, HashMap},
+    fmt::Debug,
+    iter::Sum,
+    marker, ops,
+    vec::Vec,
+};
 
 #[cfg(not(feature = "std"))]
 use {
-    alloc::{boxed::Box, collections::BTreeMap as HashMap, vec::Vec},
+    alloc::{
+        boxed::Box,
+        collections::{BTreeMap as HashMap, BinaryHeap},
+        vec::Vec,
+    },
     core::{array, fmt::Debug, iter::Sum, marker, ops},
 };
 
+/// Iterates over a slice in parallel when the `rayon` feature is enabled, or serially otherwise.
+/// Call sites must import `rayon::prelude::*` under the `rayon` feature, so that adapters such as `zip` resolve to their parallel versions.
+macro_rules! maybe_par_iter {
+    ($slice:expr) => {{
+        #[cfg(feature = "rayon")]
+        let iter = rayon::iter::IntoParallelRefIterator::par_iter($slice);
+        #[cfg(not(feature = "rayon"))]
+        let iter = $slice.iter();
+        iter
+    }};
+}
+
 /// An Iterative Closest Point algorithm, useful in matching Point Clouds.
 /// Contains a 2D implementation when using the `2d` feature, and a 3D implementation when using the `3d` feature.
 pub mod icp;
 
+/// A Normal Distributions Transform algorithm, useful in matching Point Clouds, especially sparse ones.
+pub mod ndt;
+
 ///A module containing common and interfacing structs and types.
 pub mod types;
 
@@ -65,11 +91,24 @@
 /// A K-Dimensional Tree data structure, useful for various geo-spatial computations.
 pub mod kd_tree;
 
+/// A region-subdividing tree, known as a quadtree in 2D and an octree in 3D.
+pub mod octree;
+
+/// A sparse voxel grid, hashing the points of a point cloud by the voxel containing them.
+pub mod voxel_grid;
+
 /// Implementation of the Haversine formula, which calculate the distance and bearing between two points
 pub mod haversine;
 
 /// A Collection of pathfinding algorithms
 pub mod pathfinding;
 
+/// Readers and writers for common point cloud file formats.
+#[cfg(feature = "io")]
+pub mod io;
+
+/// Metrics and dataset loaders for evaluating the accuracy of estimated trajectories.
+pub mod evaluation;
+
 /// Various utility functions that are commonly used by these algorithms.
 pub mod utils;
@@ -0,0 +1,601 @@
+// SPDX-License-Identifier: MIT
+/*
+ * Copyright (c) [2023 - Present] Emily Matheys <emilymatt96@gmail.com>
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+use crate::{
+    array,
+    types::{AbstractIsometry, IsometryAbstractor, SameSizeMat},
+    utils::{
+        point_cloud::{calculate_voxel_coordinates, transform_point_cloud},
+        symmetric_eigen_decomposition,
+    },
+    voxel_grid::{neighbouring_voxels, VoxelGrid},
+    HashMap,
+};
+use nalgebra::{AbstractRotation, Isometry, Normed, Point, RealField, SVector, Scalar};
+use num_traits::{AsPrimitive, Zero};
+use types::{NDTConfiguration, NDTError, NDTResult, NDTSuccess};
+
+/// Structs in use as part of the public API of the NDT algorithm.
+pub mod types;
+
+/// The smallest allowed ratio between a cell's smallest and largest covariance eigenvalues,
+/// this prevents cells whose points lie on a line or a plane from having a singular covariance.
+const MIN_EIGENVALUE_RATIO: f32 = 0.01;
+
+/// A shorthand for a small pose increment in `N` dimensions, see [`AbstractIsometry::Increment`].
+type Increment<T, const N: usize> = <IsometryAbstractor<T, N> as AbstractIsometry<T, N>>::Increment;
+
+/// A shorthand for a square matrix over a pose increment in `N` dimensions, see [`AbstractIsometry::IncrementMatrix`].
+type IncrementMatrix<T, const N: usize> =
+    <IsometryAbstractor<T, N> as AbstractIsometry<T, N>>::IncrementMatrix;
+
+/// A single cell of the target point cloud, represented by the normal distribution of the points inside it.
+#[derive(Clone, Copy, Debug)]
+struct NDTCell<T: Scalar, const N: usize> {
+    /// The mean of the points inside the cell.
+    mean: Point<T, N>,
+    /// The inverse of the covariance of the points inside the cell.
+    inverse_covariance: SameSizeMat<T, N>,
+}
+
+/// Verifies that the input point clouds and configuration allow for the algorithm to run.
+///
+/// # Arguments
+/// * `points_a`: A slice of [`Point`], representing the source point cloud.
+/// * `points_b`: A slice of [`Point`], representing the target point cloud.
+/// * `config`: a reference to an [`NDTConfiguration`], specifying the behaviour of the algorithm.
+///
+/// # Generics
+/// * `T`: Either an [`f32`] or [`f64`].
+/// * `R`: The rotation type of the algorithm's resulting transform.
+/// * `N`: A const usize, specifying the amount of dimensions in the points.
+///
+/// # Returns
+/// An empty [`Ok`] if the algorithm can run, or an [`NDTError`] explaining what went wrong.
+fn validate_input<T, R, const N: usize>(
+    points_a: &[Point<T, N>],
+    points_b: &[Point<T, N>],
+    config: &NDTConfiguration<T>,
+) -> Result<(), NDTError<T, R, N>>
+where
+    T: Copy + RealField,
+    R: AbstractRotation<T, N>,
+{
+    if points_a.is_empty() {
+        return Err(NDTError::EmptySource);
+    }
+
+    if points_b.is_empty() {
+        return Err(NDTError::EmptyTarget);
+    }
+
+    let invalid_configuration = |reason| Err(NDTError::InvalidConfiguration { reason });
+    if config.max_iterations == 0 {
+        return invalid_configuration("Must have more than one iteration");
+    }
+
+    if config.resolution <= T::zero() {
+        return invalid_configuration("Resolution must be positive");
+    }
+
+    if config.transformation_epsilon <= T::default_epsilon() {
+        return invalid_configuration("Transformation epsilon too low, convergence impossible");
+    }
+
+    if config.max_step_size <= T::zero() {
+        return invalid_configuration("Max step size must be positive");
+    }
+
+    if config.min_points_per_cell <= N {
+        return invalid_configuration("Cells must contain more points than dimensions");
+    }
+
+    Ok(())
+}
+
+/// Divides the target point cloud into cells, and estimates the normal distribution of the points inside each one.
+///
+/// # Arguments
+/// * `points`: a slice of [`Point`], representing the target point cloud.
+/// * `config`: a reference to an [`NDTConfiguration`], specifying the resolution and the minimal amount of points per cell.
+///
+/// # Generics
+/// * `T`: Either an [`f32`] or [`f64`].
+/// * `N`: A const usize, specifying the amount of dimensions in the points.
+///
+/// # Returns
+/// A [`HashMap`], mapping the integer coordinates of each cell to its normal distribution, cells with too few points are omitted.
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument("Build NDT Cells", skip_all, level = "debug")
+)]
+fn build_cells<T, const N: usize>(
+    points: &[Point<T, N>],
+    config: &NDTConfiguration<T>,
+) -> HashMap<[isize; N], NDTCell<T, N>>
+where
+    T: AsPrimitive<isize> + Copy + Default + RealField,
+    f32: AsPrimitive<T>,
+    usize: AsPrimitive<T>,
+{
+    VoxelGrid::from_points(points, config.resolution)
+        .statistics_iter()
+        .filter(|(_, statistics)| statistics.num_points >= config.min_points_per_cell)
+        .filter_map(|(cell_coordinates, statistics)| {
+            let (eigenvalues, eigenvectors) = symmetric_eigen_decomposition(statistics.covariance);
+
+            // Eigenvalues are sorted in ascending order, a cell whose points are all identical has no distribution
+            let max_eigenvalue = eigenvalues[N - 1];
+            if max_eigenvalue <= T::default_epsilon() {
+                return None;
+            }
+
+            let min_eigenvalue = max_eigenvalue * MIN_EIGENVALUE_RATIO.as_();
+            let inverse_eigenvalues = SVector::<T, N>::from(array::from_fn(|idx| {
+                T::one() / eigenvalues[idx].max(min_eigenvalue)
+            }));
+            Some((
+                cell_coordinates,
+                NDTCell {
+                    mean: statistics.centroid,
+                    inverse_covariance: eigenvectors
+                        * SameSizeMat::from_diagonal(&inverse_eigenvalues)
+                        * eigenvectors.transpose(),
+                },
+            ))
+        })
+        .collect()
+}
+
+/// Calculates the score of the transformed source points, along with its gradient and hessian with respect to a small pose increment.
+/// Each point is scored against the cell containing it and every cell adjacent to it, which widens the algorithm's basin of convergence.
+///
+/// # Arguments
+/// * `transformed_points_a`: a slice of [`Point`], representing the source point cloud, transformed by the current [`Isometry`].
+/// * `cells`: a reference to a [`HashMap`] of the target point cloud's cells, see [`build_cells`].
+/// * `resolution`: the size of each cell.
+///
+/// # Generics
+/// * `T`: Either an [`f32`] or [`f64`].
+/// * `N`: A const usize, either `2` or `3`.
+///
+/// # Returns
+/// A tuple of
+/// * `T`, the sum of the likelihood of each transformed point under the cell which fits it best, so at most `1` per point.
+/// * An [`Increment`](AbstractIsometry::Increment), the gradient of the negative score.
+/// * An [`IncrementMatrix`](AbstractIsometry::IncrementMatrix), the hessian of the negative score.
+/// * An [`IncrementMatrix`](AbstractIsometry::IncrementMatrix), a positive semi-definite approximation of the hessian,
+///   used when the hessian is not positive definite.
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument("Calculate NDT Score Derivatives", skip_all, level = "debug")
+)]
+fn calculate_score_derivatives<T, const N: usize>(
+    transformed_points_a: &[Point<T, N>],
+    cells: &HashMap<[isize; N], NDTCell<T, N>>,
+    resolution: T,
+) -> (
+    T,
+    Increment<T, N>,
+    IncrementMatrix<T, N>,
+    IncrementMatrix<T, N>,
+)
+where
+    T: AsPrimitive<isize> + Copy + RealField,
+    IsometryAbstractor<T, N>: AbstractIsometry<T, N>,
+{
+    let mut score = T::zero();
+    let mut gradient = Increment::<T, N>::zero();
+    let mut hessian = IncrementMatrix::<T, N>::zero();
+    let mut approximate_hessian = hessian;
+    for transformed_point_a in transformed_points_a {
+        let cell_coordinates = calculate_voxel_coordinates(transformed_point_a, resolution);
+
+        let mut best_likelihood = T::zero();
+        for neighbour_coordinates in neighbouring_voxels(&cell_coordinates) {
+            let Some(cell) = cells.get(&neighbour_coordinates) else {
+                continue;
+            };
+
+            let distance_from_mean = transformed_point_a - cell.mean;
+            let weighted_distance = cell.inverse_covariance * distance_from_mean;
+            let likelihood =
+                (-distance_from_mean.dot(&weighted_distance) / (T::one() + T::one())).exp();
+
+            let (point_hessian, point_gradient) = IsometryAbstractor::<T, N>::normal_equations(
+                transformed_point_a,
+                &cell.inverse_covariance,
+                &weighted_distance,
+            );
+            // The outer product of the point's gradient with itself
+            let (gradient_outer_product, _) = IsometryAbstractor::<T, N>::normal_equations(
+                transformed_point_a,
+                &(weighted_distance * weighted_distance.transpose()),
+                &weighted_distance,
+            );
+            if likelihood > best_likelihood {
+                best_likelihood = likelihood;
+            }
+            gradient = gradient + point_gradient * likelihood;
+            hessian = hessian + (point_hessian - gradient_outer_product) * likelihood;
+            approximate_hessian = approximate_hessian + point_hessian * likelihood;
+        }
+        // Adjacent cells still shape the derivatives, but the score only reflects how well each point fits its best cell
+        score += best_likelihood;
+    }
+
+    (score, gradient, hessian, approximate_hessian)
+}
+
+/// A free-form version of the NDT function, allowing for any input and output, under the constraints of the function.
+/// The target point cloud is divided into cells, each represented by the normal distribution of the points inside it,
+/// and the transform is optimized using Newton steps, to maximize the likelihood of the source points under these distributions.
+/// This does not require finding nearest neighbours, and tends to be more robust than ICP for sparse point clouds.
+///
+/// # Arguments
+/// * `points_a`: A slice of [`Point`], representing the source point cloud.
+/// * `points_b`: A slice of [`Point`], representing the target point cloud.
+/// * `config`: an [`NDTConfiguration`], specifying the behaviour of the algorithm.
+///
+/// # Generics
+/// * `T`: Either [`prim@f32`] or [`prim@f64`]
+/// * `N`: a usize, either `2` or `3`
+///
+/// # Returns
+/// An [`NDTSuccess`] struct with an [`Isometry`] transform with a `T` precision, or an [`NDTError`] explaining what went wrong.
+///
+/// [^convergence_note]: This does not guarantee that the transformation is correct, only that no further benefit can be gained by running another iteration.
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument("Full NDT Algorithm", skip_all, level = "info")
+)]
+pub fn ndt<T, const N: usize>(
+    points_a: &[Point<T, N>],
+    points_b: &[Point<T, N>],
+    config: NDTConfiguration<T>,
+) -> NDTResult<T, N>
+where
+    T: AsPrimitive<isize> + Copy + Default + RealField,
+    f32: AsPrimitive<T>,
+    usize: AsPrimitive<T>,
+    IsometryAbstractor<T, N>: AbstractIsometry<T, N>,
+{
+    ndt_with_initial_guess(points_a, points_b, Isometry::identity(), config)
+}
+
+/// A free-form version of the NDT function, starting from a provided transform rather than the identity transform.
+///
+/// # Arguments
+/// * `points_a`: A slice of [`Point`], representing the source point cloud.
+/// * `points_b`: A slice of [`Point`], representing the target point cloud.
+/// * `initial_guess`: An [`Isometry`], representing the estimated transform between `points_a` and `points_b`.
+/// * `config`: an [`NDTConfiguration`], specifying the behaviour of the algorithm.
+///
+/// # Generics
+/// * `T`: Either [`prim@f32`] or [`prim@f64`]
+/// * `N`: a usize, either `2` or `3`
+///
+/// # Returns
+/// An [`NDTSuccess`] struct with an [`Isometry`] transform with a `T` precision, or an [`NDTError`] explaining what went wrong.
+/// Note that the resulting transform includes `initial_guess`, and is not relative to it.
+///
+/// [^convergence_note]: This does not guarantee that the transformation is correct, only that no further benefit can be gained by running another iteration.
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument("Full NDT Algorithm With Initial Guess", skip_all, level = "info")
+)]
+pub fn ndt_with_initial_guess<T, const N: usize>(
+    points_a: &[Point<T, N>],
+    points_b: &[Point<T, N>],
+    initial_guess: Isometry<T, <IsometryAbstractor<T, N> as AbstractIsometry<T, N>>::RotType, N>,
+    config: NDTConfiguration<T>,
+) -> NDTResult<T, N>
+where
+    T: AsPrimitive<isize> + Copy + Default + RealField,
+    f32: AsPrimitive<T>,
+    usize: AsPrimitive<T>,
+    IsometryAbstractor<T, N>: AbstractIsometry<T, N>,
+{
+    validate_input(points_a, points_b, &config)?;
+
+    let cells = build_cells(points_b, &config);
+    if cells.is_empty() {
+        return Err(NDTError::NoValidCells);
+    }
+    log::trace!("Built {} target cells", cells.len());
+
+    let mut current_transform = initial_guess;
+    let mut current_score = T::zero();
+    for iteration_num in 0..config.max_iterations {
+        log::trace!(
+            "Running iteration number {iteration_num}/{}",
+            config.max_iterations
+        );
+
+        let transformed_points = transform_point_cloud(points_a, current_transform);
+        let (score, gradient, hessian, approximate_hessian) =
+            calculate_score_derivatives(&transformed_points, &cells, config.resolution);
+        current_score = score / points_a.len().as_();
+        log::trace!("Current score: {current_score}");
+
+        // The hessian is only guaranteed to be positive definite close to the solution
+        let step = IsometryAbstractor::<T, N>::solve_increment(&hessian, &gradient)
+            .or_else(|| {
+                IsometryAbstractor::<T, N>::solve_increment(&approximate_hessian, &gradient)
+            })
+            .ok_or(NDTError::DegenerateGeometry)?;
+
+        let step_size = step.norm();
+        let step = if step_size > config.max_step_size {
+            step * (config.max_step_size / step_size)
+        } else {
+            step
+        };
+        current_transform = IsometryAbstractor::<T, N>::apply_increment(&current_transform, &step);
+
+        if step_size < config.transformation_epsilon {
+            log::trace!(
+                "Converged after {iteration_num} iterations with a score of {current_score}"
+            );
+            return Ok(NDTSuccess {
+                transform: current_transform,
+                score: current_score,
+                iteration_num,
+            });
+        }
+    }
+
+    Err(NDTError::DidNotConverge {
+        last_transform: current_transform,
+        last_score: current_score,
+        iterations: config.max_iterations,
+    })
+}
+
+#[cfg(feature = "pregenerated")]
+macro_rules! impl_ndt_algorithm {
+    ($precision:expr, $doc:tt, $nd:expr, $rot_type:expr) => {
+        ::paste::paste! {
+            #[doc = "An NDT algorithm in " $nd "D space."]
+            #[doc = "# Arguments"]
+            #[doc = "* `points_a`: A slice of [`Point`], representing the source point cloud."]
+            #[doc = "* `points_b`: A slice of [`Point`], representing the target point cloud."]
+            #[doc = "* `config`: an [`NDTConfiguration`], specifying the behaviour of the algorithm."]
+            #[doc = ""]
+            #[doc = "# Returns"]
+            #[doc = "An [`NDTSuccess`] struct with an [`Isometry`] transform with an `" $precision "` precision, or an [`NDTError`] explaining what went wrong."]
+            #[doc = ""]
+            #[doc = "[^convergence_note]: This does not guarantee that the transformation is correct, only that no further benefit can be gained by running another iteration."]
+            pub fn [<ndt_$nd d>](points_a: &[Point<$precision, $nd>],
+                points_b: &[Point<$precision, $nd>],
+                config: NDTConfiguration<$precision>) -> Result<NDTSuccess<$precision, $rot_type<$precision>, $nd>, NDTError<$precision, $rot_type<$precision>, $nd>> {
+                    super::ndt(points_a, points_b, config)
+            }
+
+            #[doc = "An NDT algorithm in " $nd "D space, starting from a provided transform rather than the identity transform."]
+            #[doc = "# Arguments"]
+            #[doc = "* `points_a`: A slice of [`Point`], representing the source point cloud."]
+            #[doc = "* `points_b`: A slice of [`Point`], representing the target point cloud."]
+            #[doc = "* `initial_guess`: An [`Isometry`], representing the estimated transform between `points_a` and `points_b`."]
+            #[doc = "* `config`: an [`NDTConfiguration`], specifying the behaviour of the algorithm."]
+            #[doc = ""]
+            #[doc = "# Returns"]
+            #[doc = "An [`NDTSuccess`] struct with an [`Isometry`] transform with an `" $precision "` precision, or an [`NDTError`] explaining what went wrong."]
+            #[doc = ""]
+            #[doc = "[^convergence_note]: This does not guarantee that the transformation is correct, only that no further benefit can be gained by running another iteration."]
+            pub fn [<ndt_$nd d_with_initial_guess>](points_a: &[Point<$precision, $nd>],
+                points_b: &[Point<$precision, $nd>],
+                initial_guess: Isometry<$precision, $rot_type<$precision>, $nd>,
+                config: NDTConfiguration<$precision>) -> Result<NDTSuccess<$precision, $rot_type<$precision>, $nd>, NDTError<$precision, $rot_type<$precision>, $nd>> {
+                    super::ndt_with_initial_guess(points_a, points_b, initial_guess, config)
+            }
+        }
+    };
+
+    ($precision:expr, doc $doc:tt) => {
+        ::paste::paste! {
+            #[doc = "A " $doc "-precision implementation of an NDT algorithm"]
+            pub mod [<$doc _precision>] {
+                use nalgebra::{Isometry, Point, UnitComplex, UnitQuaternion};
+                use super::types::{NDTConfiguration, NDTError, NDTSuccess};
+
+                impl_ndt_algorithm!($precision, $doc, 2, UnitComplex);
+                impl_ndt_algorithm!($precision, $doc, 3, UnitQuaternion);
+            }
+        }
+    }
+}
+
+#[cfg(feature = "pregenerated")]
+impl_ndt_algorithm!(f32, doc single);
+#[cfg(feature = "pregenerated")]
+impl_ndt_algorithm!(f64, doc double);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        utils::point_cloud::{calculate_point_cloud_covariance, generate_corner_3d},
+        Vec,
+    };
+    use nalgebra::{Isometry2, Isometry3, Point2, Vector2, Vector3};
+
+    // A rectangular room, with a pillar in one of its corners to break its symmetry
+    fn generate_room_2d() -> Vec<Point2<f64>> {
+        (0..200)
+            .flat_map(|idx| {
+                let offset = idx as f64 * 0.05;
+                [
+                    Point2::new(offset, 0.0),
+                    Point2::new(offset, 8.0),
+                    Point2::new(0.0, offset * 0.8),
+                    Point2::new(10.0, offset * 0.8),
+                ]
+            })
+            .chain((0..40).flat_map(|idx| {
+                let offset = idx as f64 * 0.05;
+                [Point2::new(7.0 + offset, 2.0), Point2::new(7.0, offset)]
+            }))
+            .collect()
+    }
+
+    #[test]
+    fn test_build_cells() {
+        let points = [
+            Point2::new(0.1, 0.1),
+            Point2::new(0.9, 0.1),
+            Point2::new(0.5, 0.9),
+            Point2::new(1.5, 1.5),
+        ];
+        let config = NDTConfiguration::builder()
+            .with_min_points_per_cell(3)
+            .build();
+
+        // The last point is alone in its cell, which is omitted
+        let cells = build_cells(&points, &config);
+        assert_eq!(cells.len(), 1);
+        let cell = cells.get(&[0, 0]).unwrap();
+        assert!((cell.mean - Point2::new(0.5, 1.1 / 3.0)).norm() < 1e-12);
+        assert!(
+            (cell.inverse_covariance * calculate_point_cloud_covariance(&points[..3])
+                - SameSizeMat::<f64, 2>::identity())
+            .norm()
+                < 1e-9
+        );
+    }
+
+    #[test]
+    fn test_score_derivatives() {
+        let points = (0..40)
+            .flat_map(|x| (0..20).map(move |y| Point2::new(x as f64 * 0.05, y as f64 * 0.05)))
+            .collect::<Vec<_>>();
+        let cells = build_cells(&points, &NDTConfiguration::builder().build());
+        assert_eq!(cells.len(), 2);
+
+        // A point at the mean of its cell fits it perfectly, and is not rewarded for the adjacent cell as well
+        let mean = cells.get(&[0, 0]).unwrap().mean;
+        let (score, ..) = calculate_score_derivatives(&[mean], &cells, 1.0);
+        assert!((score - 1.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_ndt_errors() {
+        let points = generate_room_2d();
+        let config_builder = NDTConfiguration::builder();
+
+        let res = double_precision::ndt_2d(&[], &points, config_builder.build());
+        assert_eq!(res.unwrap_err(), NDTError::EmptySource);
+
+        let res = double_precision::ndt_2d(&points, &[], config_builder.build());
+        assert_eq!(res.unwrap_err(), NDTError::EmptyTarget);
+
+        let res = double_precision::ndt_2d(
+            &points,
+            &points,
+            config_builder.with_resolution(0.0).build(),
+        );
+        assert_eq!(
+            res.unwrap_err(),
+            NDTError::InvalidConfiguration {
+                reason: "Resolution must be positive"
+            }
+        );
+
+        let res = double_precision::ndt_2d(
+            &points,
+            &points,
+            config_builder.with_min_points_per_cell(2).build(),
+        );
+        assert_eq!(
+            res.unwrap_err(),
+            NDTError::InvalidConfiguration {
+                reason: "Cells must contain more points than dimensions"
+            }
+        );
+
+        // Every point is in a cell of its own
+        let res = double_precision::ndt_2d(
+            &points,
+            &points,
+            config_builder.with_resolution(0.001).build(),
+        );
+        assert_eq!(res.unwrap_err(), NDTError::NoValidCells);
+    }
+
+    #[test]
+    fn test_ndt_2d() {
+        let points = generate_room_2d();
+        let isom = Isometry2::new(Vector2::new(0.3, -0.2), 0.05);
+        let points_transformed = transform_point_cloud(&points, isom);
+
+        let res = double_precision::ndt_2d(
+            &points,
+            &points_transformed,
+            NDTConfiguration::builder().build(),
+        );
+        assert!(res.is_ok());
+        let res = res.unwrap();
+        assert!((res.transform.translation.vector - isom.translation.vector).norm() < 0.05);
+        assert!((res.transform.rotation.angle() - isom.rotation.angle()).abs() < 0.01);
+        assert!(res.score > 0.5);
+    }
+
+    #[test]
+    fn test_ndt_3d() {
+        let points = generate_corner_3d(50, 0.1);
+        let isom = Isometry3::new(
+            Vector3::new(0.2, -0.15, 0.1),
+            Vector3::new(0.02, -0.03, 0.05),
+        );
+        let points_transformed = transform_point_cloud(&points, isom);
+
+        let res = double_precision::ndt_3d(
+            &points,
+            &points_transformed,
+            NDTConfiguration::builder().build(),
+        );
+        assert!(res.is_ok());
+        let res = res.unwrap();
+        assert!((res.transform.translation.vector - isom.translation.vector).norm() < 0.05);
+        assert!(res.transform.rotation.angle_to(&isom.rotation) < 0.01);
+    }
+
+    #[test]
+    fn test_ndt_3d_with_initial_guess() {
+        let points = generate_corner_3d(50, 0.1);
+        let isom = Isometry3::new(Vector3::new(2.0, -1.5, 1.0), Vector3::new(0.2, -0.3, 0.5));
+        let points_transformed = transform_point_cloud(&points, isom);
+
+        // A rough estimate should be enough to get over the large motion
+        let res = double_precision::ndt_3d_with_initial_guess(
+            &points,
+            &points_transformed,
+            Isometry3::new(Vector3::new(1.8, -1.4, 1.1), Vector3::new(0.2, -0.3, 0.5)),
+            NDTConfiguration::builder().build(),
+        );
+        assert!(res.is_ok());
+        let res = res.unwrap();
+        assert!((res.transform.translation.vector - isom.translation.vector).norm() < 0.05);
+        assert!(res.transform.rotation.angle_to(&isom.rotation) < 0.01);
+    }
+}
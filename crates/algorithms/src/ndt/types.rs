@@ -0,0 +1,289 @@
+// SPDX-License-Identifier: MIT
+/*
+ * Copyright (c) [2023 - Present] Emily Matheys <emilymatt96@gmail.com>
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+use crate::{
+    types::{AbstractIsometry, IsometryAbstractor},
+    Debug,
+};
+use core::fmt::{Display, Formatter};
+use nalgebra::{AbstractRotation, Isometry, Scalar};
+use num_traits::AsPrimitive;
+
+/// Contains the resulting transform, the resulting score, and the number of iterations taken for a successful NDT convergence.
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct NDTSuccess<T: Scalar, R: AbstractRotation<T, N>, const N: usize> {
+    /// An isometric matrix, containing the translation and rotation between the point sets.
+    /// In 2D space, its rotation component would be a [`UnitComplex`](nalgebra::UnitComplex), in 3D space it would be a [`UnitQuaternion`](nalgebra::UnitQuaternion).
+    pub transform: Isometry<T, R, N>,
+    /// The mean likelihood of the transformed source points, each under the target's normal distribution which fits it best,
+    /// between `0` and `1`, where a higher score means the source points fit the target point cloud better.
+    pub score: T,
+    /// The amount of iterations passed until convergence.
+    pub iteration_num: usize,
+}
+
+/// Describes why an NDT algorithm could not return a transform.
+#[derive(Clone, Debug)]
+pub enum NDTError<T: Scalar, R: AbstractRotation<T, N>, const N: usize> {
+    /// The source point cloud contains no points.
+    EmptySource,
+    /// The target point cloud contains no points.
+    EmptyTarget,
+    /// The provided [`NDTConfiguration`] does not allow the algorithm to run, or to ever converge.
+    InvalidConfiguration {
+        /// Explains which part of the configuration is invalid.
+        reason: &'static str,
+    },
+    /// None of the target point cloud's cells contain enough points to estimate a normal distribution,
+    /// this usually means the resolution is too fine for the point cloud's density.
+    NoValidCells,
+    /// No source point falls near any of the target's cells, or their geometry does not constrain every degree of freedom of the transform.
+    DegenerateGeometry,
+    /// The algorithm ran out of iterations before converging.
+    /// The best transform found so far is still returned, as it may be good enough for some purposes.
+    DidNotConverge {
+        /// The transform calculated in the last iteration.
+        last_transform: Isometry<T, R, N>,
+        /// The score of the last iteration.
+        last_score: T,
+        /// The amount of iterations that were run.
+        iterations: usize,
+    },
+}
+
+impl<T, R, const N: usize> PartialEq for NDTError<T, R, N>
+where
+    T: Scalar,
+    R: AbstractRotation<T, N>,
+    Isometry<T, R, N>: PartialEq,
+{
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::EmptySource, Self::EmptySource)
+            | (Self::EmptyTarget, Self::EmptyTarget)
+            | (Self::NoValidCells, Self::NoValidCells)
+            | (Self::DegenerateGeometry, Self::DegenerateGeometry) => true,
+            (
+                Self::InvalidConfiguration { reason },
+                Self::InvalidConfiguration {
+                    reason: other_reason,
+                },
+            ) => reason == other_reason,
+            (
+                Self::DidNotConverge {
+                    last_transform,
+                    last_score,
+                    iterations,
+                },
+                Self::DidNotConverge {
+                    last_transform: other_last_transform,
+                    last_score: other_last_score,
+                    iterations: other_iterations,
+                },
+            ) => {
+                last_transform == other_last_transform
+                    && last_score == other_last_score
+                    && iterations == other_iterations
+            }
+            _ => false,
+        }
+    }
+}
+
+impl<T, R, const N: usize> Display for NDTError<T, R, N>
+where
+    T: Scalar + Display,
+    R: AbstractRotation<T, N>,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::EmptySource => write!(f, "Source point cloud is empty"),
+            Self::EmptyTarget => write!(f, "Target point cloud is empty"),
+            Self::InvalidConfiguration { reason } => write!(f, "Invalid configuration: {reason}"),
+            Self::NoValidCells => write!(f, "No target cell contains enough points"),
+            Self::DegenerateGeometry => {
+                write!(f, "Degenerate geometry, could not solve for a transform")
+            }
+            Self::DidNotConverge {
+                last_score,
+                iterations,
+                ..
+            } => write!(
+                f,
+                "Could not converge after {iterations} iterations, last score was {last_score}"
+            ),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T, R, const N: usize> std::error::Error for NDTError<T, R, N>
+where
+    T: Scalar + Display,
+    R: AbstractRotation<T, N> + Debug,
+{
+}
+
+/// A shorthand way of specifying the result of an NDT algorithm in `N` dimensions,
+/// where the rotation type of the transform is chosen according to `N`.
+pub type NDTResult<T, const N: usize> = Result<
+    NDTSuccess<T, <IsometryAbstractor<T, N> as AbstractIsometry<T, N>>::RotType, N>,
+    NDTError<T, <IsometryAbstractor<T, N> as AbstractIsometry<T, N>>::RotType, N>,
+>;
+
+/// A struct specifying configuration options for an NDT algorithm.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct NDTConfiguration<T> {
+    /// The size of each cell the target point cloud is divided into.
+    pub(crate) resolution: T,
+    /// The amount of iterations before giving up and exiting the algorithm.
+    pub(crate) max_iterations: usize,
+    /// The algorithm is considered converged when the size of a step is smaller than this value.
+    pub(crate) transformation_epsilon: T,
+    /// The maximal size of a single step, larger steps are scaled down to this size.
+    pub(crate) max_step_size: T,
+    /// The minimal amount of target points a cell must contain for a normal distribution to be estimated from it.
+    pub(crate) min_points_per_cell: usize,
+}
+
+impl<T: 'static + Copy> NDTConfiguration<T>
+where
+    f32: AsPrimitive<T>,
+{
+    /// Returns a builder for the configuration struct.
+    ///
+    /// # Returns
+    /// An [`NDTConfigurationBuilder`].
+    pub fn builder() -> NDTConfigurationBuilder<T> {
+        NDTConfigurationBuilder {
+            _internal: NDTConfiguration {
+                resolution: 1.0.as_(),
+                max_iterations: 35,
+                transformation_epsilon: 0.001.as_(),
+                max_step_size: 0.5.as_(),
+                min_points_per_cell: 5,
+            },
+        }
+    }
+}
+
+/// A Builder-pattern struct for safely constructing an [`NDTConfiguration`] struct.
+#[derive(Clone, Debug)]
+pub struct NDTConfigurationBuilder<T> {
+    _internal: NDTConfiguration<T>,
+}
+
+impl<T: Copy> NDTConfigurationBuilder<T> {
+    /// The size of each cell the target point cloud is divided into,
+    /// larger cells allow for larger initial offsets, while smaller cells allow for a more precise result.
+    ///
+    /// # Arguments
+    /// * `resolution`: The size of each cell, must be positive.
+    ///
+    /// # Returns
+    /// A copy of self, with the updated parameters
+    pub fn with_resolution(&self, resolution: T) -> Self {
+        Self {
+            _internal: NDTConfiguration {
+                resolution,
+                ..self._internal
+            },
+        }
+    }
+
+    /// The amount of iterations before giving up and exiting the algorithm.
+    ///
+    /// # Arguments
+    /// * `max_iterations`: The maximum number of iterations to allow.
+    ///
+    /// # Returns
+    /// A copy of self, with the updated parameters
+    pub fn with_max_iterations(&self, max_iterations: usize) -> Self {
+        Self {
+            _internal: NDTConfiguration {
+                max_iterations,
+                ..self._internal
+            },
+        }
+    }
+
+    /// The algorithm is considered converged when the size of a step is smaller than this value.
+    ///
+    /// # Arguments
+    /// * `transformation_epsilon`: The minimal step size, anything below will return a convergence.
+    ///
+    /// # Returns
+    /// A copy of self, with the updated parameters
+    pub fn with_transformation_epsilon(&self, transformation_epsilon: T) -> Self {
+        Self {
+            _internal: NDTConfiguration {
+                transformation_epsilon,
+                ..self._internal
+            },
+        }
+    }
+
+    /// The maximal size of a single step, larger steps are scaled down to this size,
+    /// this prevents the algorithm from overshooting when far from the solution.
+    ///
+    /// # Arguments
+    /// * `max_step_size`: The maximal step size, must be positive.
+    ///
+    /// # Returns
+    /// A copy of self, with the updated parameters
+    pub fn with_max_step_size(&self, max_step_size: T) -> Self {
+        Self {
+            _internal: NDTConfiguration {
+                max_step_size,
+                ..self._internal
+            },
+        }
+    }
+
+    /// The minimal amount of target points a cell must contain for a normal distribution to be estimated from it,
+    /// cells with fewer points are ignored.
+    ///
+    /// # Arguments
+    /// * `min_points_per_cell`: The minimal amount of points, must be larger than the amount of dimensions.
+    ///
+    /// # Returns
+    /// A copy of self, with the updated parameters
+    pub fn with_min_points_per_cell(&self, min_points_per_cell: usize) -> Self {
+        Self {
+            _internal: NDTConfiguration {
+                min_points_per_cell,
+                ..self._internal
+            },
+        }
+    }
+
+    /// Generates an [`NDTConfiguration`] from the struct currently contained by the builder
+    ///
+    /// # Returns
+    /// An [`NDTConfiguration`], note that this does not consume the builder, leaving it intact for another use.
+    pub fn build(&self) -> NDTConfiguration<T> {
+        self._internal.clone()
+    }
+}
@@ -0,0 +1,993 @@
+// SPDX-License-Identifier: MIT
+/*
+ * Copyright (c) [2023 - Present] Emily Matheys <emilymatt96@gmail.com>
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+use core::cmp::Reverse;
+use nalgebra::{Point, Scalar};
+use num_traits::{NumOps, One, Zero};
+
+use crate::{array, kd_tree::HeapElement, types::PolygonExtents, BinaryHeap, Vec};
+
+/// The default maximal amount of points held by a leaf before it is subdivided.
+pub const DEFAULT_BUCKET_SIZE: usize = 8;
+
+/// The axis-aligned region covered by a node, from its minimal corner to its maximal corner.
+#[derive(Clone, Copy, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+struct NodeBounds<T: Scalar, const N: usize> {
+    min: Point<T, N>,
+    max: Point<T, N>,
+}
+
+impl<T, const N: usize> NodeBounds<T, N>
+where
+    T: Copy + Default + NumOps + One + PartialOrd + Scalar + Zero,
+{
+    fn center(&self) -> Point<T, N> {
+        let two = T::one() + T::one();
+        Point::from(array::from_fn(|idx| (self.min[idx] + self.max[idx]) / two))
+    }
+
+    fn contains(&self, point: &Point<T, N>) -> bool {
+        (0..N).all(|idx| self.min[idx] <= point[idx] && point[idx] <= self.max[idx])
+    }
+
+    /// Each bit of the child index specifies whether the child is in the upper half of the matching dimension,
+    /// points exactly on the center belong to the upper half.
+    fn child_index(&self, point: &Point<T, N>) -> usize {
+        let center = self.center();
+        (0..N)
+            .filter(|&idx| point[idx] >= center[idx])
+            .fold(0, |child_idx, idx| child_idx | (1 << idx))
+    }
+
+    fn child(&self, child_idx: usize) -> Self {
+        let center = self.center();
+        let is_upper = |idx: usize| child_idx & (1 << idx) != 0;
+        Self {
+            min: Point::from(array::from_fn(|idx| {
+                if is_upper(idx) {
+                    center[idx]
+                } else {
+                    self.min[idx]
+                }
+            })),
+            max: Point::from(array::from_fn(|idx| {
+                if is_upper(idx) {
+                    self.max[idx]
+                } else {
+                    center[idx]
+                }
+            })),
+        }
+    }
+
+    /// A node may only be subdivided if its children would be no smaller than `min_leaf_size` in every dimension,
+    /// and strictly smaller than the node itself, which might not hold once the center can no longer be represented.
+    fn can_subdivide(&self, min_leaf_size: T) -> bool {
+        let center = self.center();
+        (0..N).all(|idx| {
+            self.min[idx] < center[idx]
+                && center[idx] < self.max[idx]
+                && self.max[idx] - self.min[idx] >= min_leaf_size + min_leaf_size
+        })
+    }
+
+    fn distance_squared(&self, point: &Point<T, N>) -> T {
+        (0..N).fold(T::zero(), |distance_squared, idx| {
+            let axis_distance = if point[idx] < self.min[idx] {
+                self.min[idx] - point[idx]
+            } else if point[idx] > self.max[idx] {
+                point[idx] - self.max[idx]
+            } else {
+                T::zero()
+            };
+            distance_squared + axis_distance * axis_distance
+        })
+    }
+
+    fn intersects(&self, extents: &PolygonExtents<T, N>) -> bool {
+        (0..N).all(|idx| {
+            self.min[idx] <= *extents[idx].end() && *extents[idx].start() <= self.max[idx]
+        })
+    }
+
+    fn to_extents(self) -> PolygonExtents<T, N> {
+        array::from_fn(|idx| self.min[idx]..=self.max[idx])
+    }
+}
+
+#[derive(Clone, Debug)]
+enum OrthtreeNode<T: Scalar, const N: usize, P> {
+    Leaf(Vec<(Point<T, N>, P)>),
+    /// Holds exactly `2^N` children, see [`NodeBounds::child_index`].
+    Branch(Vec<OrthtreeNode<T, N, P>>),
+}
+
+impl<T, const N: usize, P> OrthtreeNode<T, N, P>
+where
+    T: Copy + Default + NumOps + One + PartialOrd + Scalar + Zero,
+{
+    fn len(&self) -> usize {
+        match self {
+            Self::Leaf(entries) => entries.len(),
+            Self::Branch(children) => children.iter().map(Self::len).sum(),
+        }
+    }
+
+    fn into_entries(self, entries: &mut Vec<(Point<T, N>, P)>) {
+        match self {
+            Self::Leaf(leaf_entries) => entries.extend(leaf_entries),
+            Self::Branch(children) => children
+                .into_iter()
+                .for_each(|child| child.into_entries(entries)),
+        }
+    }
+
+    fn insert(
+        &mut self,
+        bounds: &NodeBounds<T, N>,
+        entry: (Point<T, N>, P),
+        min_leaf_size: T,
+        bucket_size: usize,
+    ) {
+        match self {
+            Self::Branch(children) => {
+                let child_idx = bounds.child_index(&entry.0);
+                children[child_idx].insert(
+                    &bounds.child(child_idx),
+                    entry,
+                    min_leaf_size,
+                    bucket_size,
+                );
+            }
+            Self::Leaf(entries) => {
+                entries.push(entry);
+                if entries.len() > bucket_size && bounds.can_subdivide(min_leaf_size) {
+                    let entries = core::mem::take(entries);
+                    *self = Self::Branch((0..1 << N).map(|_| Self::Leaf(Vec::new())).collect());
+                    for entry in entries {
+                        self.insert(bounds, entry, min_leaf_size, bucket_size);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Returns the leaf which would contain the point, along with its bounds.
+    fn leaf_mut(
+        &mut self,
+        bounds: NodeBounds<T, N>,
+        point: &Point<T, N>,
+    ) -> (&mut Vec<(Point<T, N>, P)>, NodeBounds<T, N>) {
+        match self {
+            Self::Leaf(entries) => (entries, bounds),
+            Self::Branch(children) => {
+                let child_idx = bounds.child_index(point);
+                children[child_idx].leaf_mut(bounds.child(child_idx), point)
+            }
+        }
+    }
+
+    /// Removes a point from the subtree, merging a branch back into a leaf once its children can fit in a single bucket.
+    fn remove(
+        &mut self,
+        bounds: &NodeBounds<T, N>,
+        point: &Point<T, N>,
+        bucket_size: usize,
+    ) -> Option<P> {
+        match self {
+            Self::Leaf(entries) => {
+                let entry_idx = entries.iter().position(|(data, _)| data == point)?;
+                Some(entries.swap_remove(entry_idx).1)
+            }
+            Self::Branch(children) => {
+                let child_idx = bounds.child_index(point);
+                let payload =
+                    children[child_idx].remove(&bounds.child(child_idx), point, bucket_size)?;
+
+                if children.iter().all(|child| matches!(child, Self::Leaf(_)))
+                    && self.len() <= bucket_size
+                {
+                    let mut entries = Vec::new();
+                    core::mem::replace(self, Self::Leaf(Vec::new())).into_entries(&mut entries);
+                    *self = Self::Leaf(entries);
+                }
+
+                Some(payload)
+            }
+        }
+    }
+}
+
+/// A region-subdividing tree, splitting its region into `2^N` equal children whenever a leaf holds too many points.
+/// Commonly known as a quadtree in 2D and an octree in 3D, see [`Quadtree`] and [`Octree`].
+///
+/// Unlike a [`KDTree`](crate::kd_tree::KDTree), its structure depends only on its region and not on the insertion order,
+/// which makes it well suited for dynamic maps and level-of-detail queries.
+///
+/// Each point in the tree carries a payload, which is returned alongside it by the `_with_payload` queries.
+/// Leaves at the minimal leaf size can also serve as occupancy cells, see [`Orthtree::insert_or_merge`].
+///
+/// # Generics
+/// `T`: Either an [`f32`] or [`f64`]
+/// `N`: a const usize specifying how many dimensions should each point have.
+/// `P`: the payload type of each point, defaults to `()` for a tree of bare points.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "serde",
+    serde(
+        from = "SerializedOrthtree<T, N, P>",
+        into = "SerializedOrthtree<T, N, P>",
+        bound = "T: Copy + Default + NumOps + One + PartialOrd + Scalar + Zero + serde::Serialize + serde::de::DeserializeOwned, P: Clone + serde::Serialize + serde::de::DeserializeOwned"
+    )
+)]
+pub struct Orthtree<T, const N: usize, P = ()>
+where
+    T: Copy + Default + NumOps + One + PartialOrd + Scalar + Zero,
+{
+    root: OrthtreeNode<T, N, P>,
+    bounds: NodeBounds<T, N>,
+    min_leaf_size: T,
+    bucket_size: usize,
+    len: usize,
+}
+
+/// The serialized form of an [`Orthtree`], its nodes are rebuilt from the region, the leaf limits and the points when deserializing.
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(
+    bound = "T: Scalar + serde::Serialize + serde::de::DeserializeOwned, P: serde::Serialize + serde::de::DeserializeOwned"
+)]
+struct SerializedOrthtree<T: Scalar, const N: usize, P> {
+    extents: NodeBounds<T, N>,
+    min_leaf_size: T,
+    bucket_size: usize,
+    entries: Vec<(Point<T, N>, P)>,
+}
+
+#[cfg(feature = "serde")]
+impl<T, const N: usize, P> From<Orthtree<T, N, P>> for SerializedOrthtree<T, N, P>
+where
+    T: Copy + Default + NumOps + One + PartialOrd + Scalar + Zero,
+{
+    fn from(tree: Orthtree<T, N, P>) -> Self {
+        let mut entries = Vec::with_capacity(tree.len);
+        tree.root.into_entries(&mut entries);
+        Self {
+            extents: tree.bounds,
+            min_leaf_size: tree.min_leaf_size,
+            bucket_size: tree.bucket_size,
+            entries,
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<T, const N: usize, P> From<SerializedOrthtree<T, N, P>> for Orthtree<T, N, P>
+where
+    T: Copy + Default + NumOps + One + PartialOrd + Scalar + Zero,
+{
+    fn from(serialized: SerializedOrthtree<T, N, P>) -> Self {
+        let mut tree = Self::with_bucket_size(
+            &serialized.extents.to_extents(),
+            serialized.min_leaf_size,
+            serialized.bucket_size,
+        );
+        for (point, payload) in serialized.entries {
+            tree.insert_with_payload(point, payload);
+        }
+        tree
+    }
+}
+
+/// An [`Orthtree`] of 2D points, splitting each region into 4 quadrants.
+pub type Quadtree<T, P = ()> = Orthtree<T, 2, P>;
+
+/// An [`Orthtree`] of 3D points, splitting each region into 8 octants.
+pub type Octree<T, P = ()> = Orthtree<T, 3, P>;
+
+impl<T, const N: usize> Orthtree<T, N>
+where
+    T: Copy + Default + NumOps + One + PartialOrd + Scalar + Zero,
+{
+    /// Inserts a new point into the tree.
+    ///
+    /// # Arguments
+    /// * `data`: a [`Point`], to be inserted into the tree.
+    ///
+    /// # Returns
+    /// `true` if the point was inserted, or `false` if it lies outside the region of the tree.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument("Insert To Orthtree", skip_all, level = "debug")
+    )]
+    pub fn insert(&mut self, data: Point<T, N>) -> bool {
+        self.insert_with_payload(data, ())
+    }
+}
+
+impl<T, const N: usize, P> Orthtree<T, N, P>
+where
+    T: Copy + Default + NumOps + One + PartialOrd + Scalar + Zero,
+{
+    /// Creates an empty tree covering a region, whose leaves are subdivided once they hold more than [`DEFAULT_BUCKET_SIZE`] points.
+    ///
+    /// # Arguments
+    /// * `extents`: a [`PolygonExtents`], containing the inclusive range of each dimension of the region.
+    /// * `min_leaf_size`: the minimal size of a leaf in every dimension, leaves are never subdivided below this size.
+    ///   A non-positive size is clamped to the largest dimension of the region, so that the root is never subdivided.
+    ///
+    /// # Returns
+    /// An empty [`Orthtree`].
+    pub fn new(extents: &PolygonExtents<T, N>, min_leaf_size: T) -> Self {
+        Self::with_bucket_size(extents, min_leaf_size, DEFAULT_BUCKET_SIZE)
+    }
+
+    /// Creates an empty tree covering a region, whose leaves are subdivided once they hold more than `bucket_size` points.
+    ///
+    /// # Arguments
+    /// * `extents`: a [`PolygonExtents`], containing the inclusive range of each dimension of the region.
+    /// * `min_leaf_size`: the minimal size of a leaf in every dimension, leaves are never subdivided below this size.
+    ///   A non-positive size is clamped to the largest dimension of the region, so that the root is never subdivided.
+    /// * `bucket_size`: the maximal amount of points in a leaf which is larger than `min_leaf_size`, clamped to at least 1.
+    ///
+    /// # Returns
+    /// An empty [`Orthtree`].
+    pub fn with_bucket_size(
+        extents: &PolygonExtents<T, N>,
+        min_leaf_size: T,
+        bucket_size: usize,
+    ) -> Self {
+        let bounds = NodeBounds {
+            min: Point::from(array::from_fn(|idx| *extents[idx].start())),
+            max: Point::from(array::from_fn(|idx| *extents[idx].end())),
+        };
+        let min_leaf_size =
+            if min_leaf_size > T::zero() {
+                min_leaf_size
+            } else {
+                (0..N).map(|idx| bounds.max[idx] - bounds.min[idx]).fold(
+                    T::zero(),
+                    |largest, size| {
+                        if size > largest {
+                            size
+                        } else {
+                            largest
+                        }
+                    },
+                )
+            };
+
+        Self {
+            root: OrthtreeNode::Leaf(Vec::new()),
+            bounds,
+            min_leaf_size,
+            bucket_size: bucket_size.max(1),
+            len: 0,
+        }
+    }
+
+    /// Returns the region covered by the tree.
+    pub fn extents(&self) -> PolygonExtents<T, N> {
+        self.bounds.to_extents()
+    }
+
+    /// Returns the amount of points in the tree.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if the tree contains no points.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Inserts a new point into the tree, along with its payload.
+    ///
+    /// # Arguments
+    /// * `data`: a [`Point`], to be inserted into the tree.
+    /// * `payload`: the payload to associate with the point.
+    ///
+    /// # Returns
+    /// `true` if the point was inserted, or `false` if it lies outside the region of the tree.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument("Insert To Orthtree With Payload", skip_all, level = "debug")
+    )]
+    pub fn insert_with_payload(&mut self, data: Point<T, N>, payload: P) -> bool {
+        if !self.bounds.contains(&data) {
+            return false;
+        }
+
+        self.root.insert(
+            &self.bounds,
+            (data, payload),
+            self.min_leaf_size,
+            self.bucket_size,
+        );
+        self.len += 1;
+        true
+    }
+
+    /// Inserts a new point into the tree, unless the leaf containing it is already at the minimal leaf size and holds a point,
+    /// in which case `payload` is merged into the payload of that leaf's first point instead.
+    /// This turns the smallest leaves into occupancy cells, whose payloads accumulate observations such as hit counts or log-odds.
+    ///
+    /// # Arguments
+    /// * `data`: a [`Point`], to be inserted into the tree.
+    /// * `payload`: the payload to associate with the point, or to merge into the existing payload.
+    /// * `merge`: a closure merging a new payload into an existing one.
+    ///
+    /// # Returns
+    /// `true` if the point was inserted or merged, or `false` if it lies outside the region of the tree.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument("Insert Or Merge To Orthtree", skip_all, level = "debug")
+    )]
+    pub fn insert_or_merge<F>(&mut self, data: Point<T, N>, payload: P, merge: F) -> bool
+    where
+        F: FnOnce(&mut P, P),
+    {
+        if !self.bounds.contains(&data) {
+            return false;
+        }
+
+        let (entries, leaf_bounds) = self.root.leaf_mut(self.bounds, &data);
+        match entries.first_mut() {
+            Some((_, existing_payload)) if !leaf_bounds.can_subdivide(self.min_leaf_size) => {
+                merge(existing_payload, payload);
+                true
+            }
+            _ => self.insert_with_payload(data, payload),
+        }
+    }
+
+    /// Removes a point from the tree, merging subdivided regions back together once they fit in a single leaf.
+    ///
+    /// # Arguments
+    /// * `data`: the [`Point`] to remove, if the tree contains several identical points only one of them is removed.
+    ///
+    /// # Returns
+    /// The payload of the removed point, or [`None`] if the point is not in the tree.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument("Remove From Orthtree", skip_all, level = "debug")
+    )]
+    pub fn remove(&mut self, data: &Point<T, N>) -> Option<P> {
+        if !self.bounds.contains(data) {
+            return None;
+        }
+
+        let payload = self.root.remove(&self.bounds, data, self.bucket_size)?;
+        self.len -= 1;
+        Some(payload)
+    }
+
+    /// Visits every leaf whose bounds are accepted by `visit_node`, skipping entire regions whose bounds are rejected.
+    fn for_each_leaf<'a, V, L>(&'a self, mut visit_node: V, mut visit_leaf: L)
+    where
+        V: FnMut(&NodeBounds<T, N>) -> bool,
+        L: FnMut(&'a [(Point<T, N>, P)]),
+    {
+        let mut stack = Vec::from([(&self.root, self.bounds)]);
+        while let Some((node, bounds)) = stack.pop() {
+            if !visit_node(&bounds) {
+                continue;
+            }
+
+            match node {
+                OrthtreeNode::Leaf(entries) => visit_leaf(entries),
+                OrthtreeNode::Branch(children) => stack.extend(
+                    children
+                        .iter()
+                        .enumerate()
+                        .map(|(child_idx, child)| (child, bounds.child(child_idx))),
+                ),
+            }
+        }
+    }
+
+    /// Attempts to find the closest point in the tree to the target point.
+    ///
+    /// # Arguments
+    /// * `target`: a [`Point`], to search the closest point for.
+    ///
+    /// # Returns
+    /// [`None`] if the tree is empty, otherwise returns the closest [`Point`].
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument("Find Nearest Neighbour In Orthtree", skip_all, level = "debug")
+    )]
+    pub fn nearest(&self, target: &Point<T, N>) -> Option<Point<T, N>> {
+        self.nearest_with_payload(target).map(|(point, _)| point)
+    }
+
+    /// Attempts to find the closest point in the tree to the target point, along with its payload.
+    /// Regions are visited in order of their distance from the target, until no unvisited region can contain a closer point.
+    ///
+    /// # Arguments
+    /// * `target`: a [`Point`], to search the closest point for.
+    ///
+    /// # Returns
+    /// [`None`] if the tree is empty, otherwise returns the closest [`Point`] and a reference to its payload.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            "Find Nearest Neighbour In Orthtree With Payload",
+            skip_all,
+            level = "debug"
+        )
+    )]
+    pub fn nearest_with_payload(&self, target: &Point<T, N>) -> Option<(Point<T, N>, &P)> {
+        let mut best: Option<(T, &(Point<T, N>, P))> = None;
+        let mut heap = BinaryHeap::from([Reverse(HeapElement {
+            distance_squared: self.bounds.distance_squared(target),
+            item: (&self.root, self.bounds),
+        })]);
+
+        while let Some(Reverse(HeapElement {
+            distance_squared,
+            item: (node, bounds),
+        })) = heap.pop()
+        {
+            if best.is_some_and(|(best_distance, _)| best_distance <= distance_squared) {
+                break;
+            }
+
+            match node {
+                OrthtreeNode::Leaf(entries) => {
+                    for entry in entries {
+                        let entry_distance = crate::utils::distance_squared(&entry.0, target);
+                        if best.map_or(true, |(best_distance, _)| entry_distance < best_distance) {
+                            best = Some((entry_distance, entry));
+                        }
+                    }
+                }
+                OrthtreeNode::Branch(children) => {
+                    heap.extend(children.iter().enumerate().map(|(child_idx, child)| {
+                        let child_bounds = bounds.child(child_idx);
+                        Reverse(HeapElement {
+                            distance_squared: child_bounds.distance_squared(target),
+                            item: (child, child_bounds),
+                        })
+                    }))
+                }
+            }
+        }
+
+        best.map(|(_, (point, payload))| (*point, payload))
+    }
+
+    /// Finds all points in the tree that are within `radius` of the specified target point.
+    ///
+    /// # Arguments
+    /// * `target`: a [`Point`], to search around.
+    /// * `radius`: the maximum distance of a point from `target`, inclusive.
+    ///
+    /// # Returns
+    /// A [`Vec`] of all [`Point`]s within the radius, in no particular order.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument("Find Points Within Radius In Orthtree", skip_all, level = "debug")
+    )]
+    pub fn within_radius(&self, target: &Point<T, N>, radius: T) -> Vec<Point<T, N>> {
+        self.within_radius_with_payload(target, radius)
+            .into_iter()
+            .map(|(point, _)| point)
+            .collect()
+    }
+
+    /// Finds all points in the tree that are within `radius` of the specified target point, along with their payloads.
+    ///
+    /// # Arguments
+    /// * `target`: a [`Point`], to search around.
+    /// * `radius`: the maximum distance of a point from `target`, inclusive.
+    ///
+    /// # Returns
+    /// A [`Vec`] of all [`Point`]s within the radius and references to their payloads, in no particular order.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            "Find Points Within Radius In Orthtree With Payload",
+            skip_all,
+            level = "debug"
+        )
+    )]
+    pub fn within_radius_with_payload(
+        &self,
+        target: &Point<T, N>,
+        radius: T,
+    ) -> Vec<(Point<T, N>, &P)> {
+        let radius_squared = radius * radius;
+        let mut results = Vec::new();
+        self.for_each_leaf(
+            |bounds| bounds.distance_squared(target) <= radius_squared,
+            |entries| {
+                results.extend(
+                    entries
+                        .iter()
+                        .filter(|(point, _)| {
+                            crate::utils::distance_squared(point, target) <= radius_squared
+                        })
+                        .map(|(point, payload)| (*point, payload)),
+                )
+            },
+        );
+        results
+    }
+
+    /// Finds all points in the tree that are inside the axis-aligned box described by `extents`.
+    ///
+    /// # Arguments
+    /// * `extents`: a [`PolygonExtents`], containing the inclusive range of each dimension of the box.
+    ///
+    /// # Returns
+    /// A [`Vec`] of all [`Point`]s inside the box, in no particular order.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument("Find Points Within Extents In Orthtree", skip_all, level = "debug")
+    )]
+    pub fn within_extents(&self, extents: &PolygonExtents<T, N>) -> Vec<Point<T, N>> {
+        self.within_extents_with_payload(extents)
+            .into_iter()
+            .map(|(point, _)| point)
+            .collect()
+    }
+
+    /// Finds all points in the tree that are inside the axis-aligned box described by `extents`, along with their payloads.
+    ///
+    /// # Arguments
+    /// * `extents`: a [`PolygonExtents`], containing the inclusive range of each dimension of the box.
+    ///
+    /// # Returns
+    /// A [`Vec`] of all [`Point`]s inside the box and references to their payloads, in no particular order.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            "Find Points Within Extents In Orthtree With Payload",
+            skip_all,
+            level = "debug"
+        )
+    )]
+    pub fn within_extents_with_payload(
+        &self,
+        extents: &PolygonExtents<T, N>,
+    ) -> Vec<(Point<T, N>, &P)> {
+        let mut results = Vec::new();
+        self.for_each_leaf(
+            |bounds| bounds.intersects(extents),
+            |entries| {
+                results.extend(
+                    entries
+                        .iter()
+                        .filter(|(point, _)| (0..N).all(|idx| extents[idx].contains(&point[idx])))
+                        .map(|(point, payload)| (*point, payload)),
+                )
+            },
+        );
+        results
+    }
+
+    /// Summarizes the tree at a given depth, for level-of-detail purposes.
+    /// Regions deeper than `depth` are represented by their ancestor at `depth`, while shallower leaves represent themselves.
+    ///
+    /// # Arguments
+    /// * `depth`: the depth to summarize the tree at, where the root is at depth `0`.
+    ///
+    /// # Returns
+    /// A [`Vec`] of the [`PolygonExtents`] of every non-empty region at `depth`, along with the amount of points inside it.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument("Orthtree Level Of Detail", skip_all, level = "debug")
+    )]
+    pub fn level_of_detail(&self, depth: usize) -> Vec<(PolygonExtents<T, N>, usize)> {
+        let mut results = Vec::new();
+        let mut stack = Vec::from([(&self.root, self.bounds, 0)]);
+        while let Some((node, bounds, node_depth)) = stack.pop() {
+            match node {
+                OrthtreeNode::Branch(children) if node_depth < depth => stack.extend(
+                    children
+                        .iter()
+                        .enumerate()
+                        .map(|(child_idx, child)| (child, bounds.child(child_idx), node_depth + 1)),
+                ),
+                _ => {
+                    let num_points = node.len();
+                    if num_points > 0 {
+                        results.push((bounds.to_extents(), num_points));
+                    }
+                }
+            }
+        }
+        results
+    }
+
+    /// Allows the user to traverse all points and payloads in the tree.
+    ///
+    /// # Arguments
+    /// * `func`: a closure which is called for every point in the tree, along with its payload.
+    pub fn traverse_tree_with_payload<F>(&self, mut func: F)
+    where
+        F: FnMut(&Point<T, N>, &P),
+    {
+        self.for_each_leaf(
+            |_| true,
+            |entries| {
+                entries
+                    .iter()
+                    .for_each(|(point, payload)| func(point, payload))
+            },
+        );
+    }
+}
+
+#[cfg(feature = "pregenerated")]
+macro_rules! impl_orthtree_types {
+    ($prec:expr, doc $doc:tt) => {
+        ::paste::paste! {
+            #[doc = "Pre-generated " $doc "-precision quadtree and octree types."]
+            pub mod [<$doc _precision>] {
+                #[doc = "A " $doc "-precision [`Quadtree`](super::Quadtree), whose points carry payloads of type `P`."]
+                pub type Quadtree<P = ()> = super::Quadtree<$prec, P>;
+
+                #[doc = "A " $doc "-precision [`Octree`](super::Octree), whose points carry payloads of type `P`."]
+                pub type Octree<P = ()> = super::Octree<$prec, P>;
+            }
+        }
+    };
+}
+
+#[cfg(feature = "pregenerated")]
+impl_orthtree_types!(f32, doc single);
+#[cfg(feature = "pregenerated")]
+impl_orthtree_types!(f64, doc double);
+
+#[cfg(test)]
+mod tests {
+    use nalgebra::{Point2, Point3};
+
+    use crate::{
+        utils::point_cloud::{find_closest_point, generate_point_cloud},
+        Vec,
+    };
+
+    use super::*;
+
+    fn generate_octree(points: &[Point3<f32>]) -> Octree<f32, usize> {
+        let mut octree =
+            Octree::with_bucket_size(&[-10.0..=10.0, -10.0..=10.0, -10.0..=10.0], 0.5, 4);
+        for (idx, point) in points.iter().enumerate() {
+            assert!(octree.insert_with_payload(*point, idx));
+        }
+        octree
+    }
+
+    fn sorted(mut points: Vec<Point3<f32>>) -> Vec<Point3<f32>> {
+        points.sort_by(|a, b| {
+            a.coords
+                .as_slice()
+                .partial_cmp(b.coords.as_slice())
+                .unwrap()
+        });
+        points
+    }
+
+    #[test]
+    fn test_insert() {
+        let mut quadtree = Quadtree::new(&[0.0..=4.0, 0.0..=4.0], 1.0);
+        assert!(quadtree.is_empty());
+        assert!(quadtree.insert(Point2::new(1.0, 1.0)));
+        assert!(quadtree.insert(Point2::new(4.0, 4.0)));
+        assert!(!quadtree.insert(Point2::new(4.5, 1.0)));
+        assert_eq!(quadtree.len(), 2);
+        assert_eq!(quadtree.extents(), [0.0..=4.0, 0.0..=4.0]);
+    }
+
+    #[test]
+    fn test_min_leaf_size() {
+        // Identical points can never be separated, subdivision must stop at the minimal leaf size
+        let mut quadtree = Quadtree::with_bucket_size(&[0.0..=8.0, 0.0..=8.0], 2.0, 1);
+        for _ in 0..10 {
+            quadtree.insert(Point2::new(1.0, 1.0));
+        }
+        assert_eq!(quadtree.len(), 10);
+        assert_eq!(
+            quadtree.level_of_detail(usize::MAX),
+            [([0.0..=2.0, 0.0..=2.0], 10)]
+        );
+
+        // A non-positive minimal leaf size never subdivides the root
+        let mut quadtree = Quadtree::<f64>::with_bucket_size(&[0.0..=2.0, 0.0..=2.0], 0.0, 2);
+        for _ in 0..3 {
+            quadtree.insert(Point2::new(1.0, 1.0));
+        }
+        assert_eq!(quadtree.len(), 3);
+        assert_eq!(quadtree.level_of_detail(usize::MAX).len(), 1);
+
+        // Subdivision stops once the children would be no smaller than their parent
+        let mut quadtree =
+            Quadtree::<f32>::with_bucket_size(&[0.0..=2.0, 0.0..=2.0], f32::MIN_POSITIVE, 2);
+        for _ in 0..3 {
+            quadtree.insert(Point2::new(1.0, 1.0));
+        }
+        assert_eq!(quadtree.len(), 3);
+        assert_eq!(
+            quadtree.nearest(&Point2::new(1.0, 1.0)),
+            Some(Point2::new(1.0, 1.0))
+        );
+    }
+
+    #[test]
+    fn test_remove() {
+        let points = generate_point_cloud(100, [-10.0..=10.0, -10.0..=10.0, -10.0..=10.0]);
+        let mut octree = generate_octree(&points);
+
+        assert!(octree.remove(&Point3::new(20.0, 0.0, 0.0)).is_none());
+        assert!(octree.remove(&Point3::new(0.0, 0.0, 0.0)).is_none());
+        for (idx, point) in points.iter().enumerate().skip(4) {
+            assert_eq!(octree.remove(point), Some(idx));
+        }
+        assert_eq!(octree.len(), 4);
+        assert_eq!(
+            sorted(octree.within_extents(&octree.extents())),
+            sorted(points[..4].to_vec())
+        );
+
+        // The remaining points fit in a single leaf again
+        assert_eq!(octree.level_of_detail(usize::MAX).len(), 1);
+    }
+
+    #[test]
+    fn test_nearest() {
+        assert!(Quadtree::<f32>::new(&[0.0..=1.0, 0.0..=1.0], 0.1)
+            .nearest(&Point2::new(0.5, 0.5))
+            .is_none());
+
+        let points = generate_point_cloud(500, [-10.0..=10.0, -10.0..=10.0, -10.0..=10.0]);
+        let octree = generate_octree(&points);
+        for target in generate_point_cloud(50, [-15.0..=15.0, -15.0..=15.0, -15.0..=15.0]) {
+            let (nearest, idx) = octree.nearest_with_payload(&target).unwrap();
+            assert_eq!(nearest, find_closest_point(&target, &points));
+            assert_eq!(points[*idx], nearest);
+        }
+    }
+
+    #[test]
+    fn test_range_queries() {
+        let points = generate_point_cloud(500, [-10.0..=10.0, -10.0..=10.0, -10.0..=10.0]);
+        let octree = generate_octree(&points);
+
+        let target = Point3::new(1.0, -2.0, 0.5);
+        assert_eq!(
+            sorted(octree.within_radius(&target, 4.0)),
+            sorted(
+                points
+                    .iter()
+                    .copied()
+                    .filter(|point| (point - target).norm() <= 4.0)
+                    .collect()
+            )
+        );
+
+        let extents = [-3.0..=2.0, 0.0..=10.0, -10.0..=-4.0];
+        assert_eq!(
+            sorted(octree.within_extents(&extents)),
+            sorted(
+                points
+                    .iter()
+                    .copied()
+                    .filter(|point| (0..3).all(|idx| extents[idx].contains(&point[idx])))
+                    .collect()
+            )
+        );
+        assert!(octree
+            .within_extents_with_payload(&extents)
+            .into_iter()
+            .all(|(point, idx)| points[*idx] == point));
+    }
+
+    #[test]
+    fn test_insert_or_merge() {
+        let mut quadtree = Quadtree::with_bucket_size(&[0.0..=4.0, 0.0..=4.0], 1.0, 1);
+        let observations = [
+            Point2::new(0.2, 0.2),
+            Point2::new(0.8, 0.6),
+            Point2::new(0.5, 0.1),
+            Point2::new(3.5, 3.5),
+        ];
+        for observation in observations {
+            assert!(quadtree.insert_or_merge(observation, 1, |hits, new_hits| *hits += new_hits));
+        }
+
+        // Points inserted before their leaf reached the minimal size are kept apart, later ones are merged into the first of them
+        let mut hits = Vec::new();
+        quadtree
+            .traverse_tree_with_payload(|point, hits_in_cell| hits.push((*point, *hits_in_cell)));
+        hits.sort_by(|a, b| a.0.x.partial_cmp(&b.0.x).unwrap());
+        assert_eq!(
+            hits,
+            [
+                (Point2::new(0.2, 0.2), 2),
+                (Point2::new(0.8, 0.6), 1),
+                (Point2::new(3.5, 3.5), 1)
+            ]
+        );
+        assert!(
+            !quadtree.insert_or_merge(Point2::new(5.0, 0.0), 1, |hits, new_hits| *hits += new_hits)
+        );
+    }
+
+    #[test]
+    fn test_level_of_detail() {
+        let mut quadtree = Quadtree::with_bucket_size(&[0.0..=4.0, 0.0..=4.0], 1.0, 1);
+        for point in [
+            Point2::new(0.5, 0.5),
+            Point2::new(1.5, 1.5),
+            Point2::new(3.0, 3.0),
+        ] {
+            quadtree.insert(point);
+        }
+
+        assert_eq!(quadtree.level_of_detail(0), [([0.0..=4.0, 0.0..=4.0], 3)]);
+
+        let mut level_one = quadtree.level_of_detail(1);
+        level_one.sort_by(|a, b| a.0[0].start().partial_cmp(b.0[0].start()).unwrap());
+        assert_eq!(
+            level_one,
+            [([0.0..=2.0, 0.0..=2.0], 2), ([2.0..=4.0, 2.0..=4.0], 1)]
+        );
+        assert_eq!(quadtree.level_of_detail(2).len(), 3);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_round_trip() {
+        let points = generate_point_cloud(100, array::from_fn(|_| -9.0..=9.0));
+        let octree = generate_octree(&points);
+        let serialized = serde_json::to_string(&octree).unwrap();
+        let deserialized: Octree<f32, usize> = serde_json::from_str(&serialized).unwrap();
+
+        assert_eq!(deserialized.len(), octree.len());
+        assert_eq!(deserialized.extents(), octree.extents());
+        let target = Point3::new(0.3, -1.2, 4.5);
+        assert_eq!(
+            deserialized.nearest_with_payload(&target),
+            octree.nearest_with_payload(&target)
+        );
+
+        // The leaf limits are clamped and points outside the region are dropped, as if the tree was built by hand
+        let mut deserialized: Quadtree<f64, usize> = serde_json::from_str(
+            r#"{"extents":{"min":[0.0,0.0],"max":[4.0,4.0]},"min_leaf_size":0.0,"bucket_size":0,"entries":[[[1.0,1.0],0],[[3.0,3.0],1],[[5.0,5.0],2]]}"#,
+        )
+        .unwrap();
+        assert_eq!(deserialized.len(), 2);
+        assert_eq!(
+            deserialized.nearest_with_payload(&Point2::new(2.9, 3.2)),
+            Some((Point2::new(3.0, 3.0), &1))
+        );
+        assert_eq!(deserialized.remove(&Point2::new(1.0, 1.0)), Some(0));
+        assert!(deserialized.insert_with_payload(Point2::new(2.0, 2.0), 3));
+        assert_eq!(deserialized.len(), 2);
+    }
+}
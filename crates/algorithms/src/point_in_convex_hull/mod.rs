@@ -20,3 +20,509 @@
  * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
  * SOFTWARE.
  */
+
+use nalgebra::{Point, Point2, Point3, RealField, SVector, Vector2};
+use num_traits::{AsPrimitive, Bounded};
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+
+use crate::{utils::calculate_polygon_extents, Vec};
+
+/// A half-space, made of an outward facing normal and an offset,
+/// a point is inside the half-space when its dot product with the normal is no larger than the offset.
+type HalfSpace<T, const N: usize> = (SVector<T, N>, T);
+
+#[inline]
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument("Is Point In Half-Spaces", skip_all, level = "trace")
+)]
+fn is_point_in_half_spaces<T, const N: usize>(
+    point: &Point<T, N>,
+    half_spaces: &[HalfSpace<T, N>],
+) -> bool
+where
+    T: Copy + RealField,
+{
+    half_spaces
+        .iter()
+        .all(|(normal, offset)| normal.dot(&point.coords) <= *offset)
+}
+
+/// Calculates the half-planes bounding a convex polygon, one for each of its edges.
+/// Returns [`None`] if the polygon has no area, in which case no point can be inside it.
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument("Calculate Convex Hull Half-Planes", skip_all, level = "debug")
+)]
+fn calculate_half_planes<T>(hull: &[Point2<T>]) -> Option<Vec<HalfSpace<T, 2>>>
+where
+    T: Copy + RealField,
+{
+    let edges = || (0..hull.len()).map(|idx| (hull[idx], hull[(idx + 1) % hull.len()]));
+
+    // The sign of the polygon's area specifies its winding, positive for counter-clockwise and negative for clockwise
+    let double_area = edges().fold(T::zero(), |acc, (vertex1, vertex2)| {
+        acc + (vertex1.x * vertex2.y - vertex2.x * vertex1.y)
+    });
+    if hull.len() < 3 || double_area == T::zero() {
+        return None;
+    }
+
+    Some(
+        edges()
+            .map(|(vertex1, vertex2)| {
+                // Perpendicular to the edge, to its right, which is outwards for a counter-clockwise polygon
+                let mut normal = Vector2::new(vertex2.y - vertex1.y, vertex1.x - vertex2.x);
+                if double_area < T::zero() {
+                    normal = -normal;
+                }
+                (normal, normal.dot(&vertex1.coords))
+            })
+            .collect(),
+    )
+}
+
+/// Calculates the half-spaces bounding a convex polyhedron, one for each of its facets.
+/// Returns [`None`] if the polyhedron is made of fewer than 4 facets, in which case it has no volume.
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument("Calculate Convex Hull Half-Spaces", skip_all, level = "debug")
+)]
+fn calculate_half_spaces<T>(
+    vertices: &[Point3<T>],
+    facets: &[[usize; 3]],
+) -> Option<Vec<HalfSpace<T, 3>>>
+where
+    T: Copy + RealField,
+    usize: AsPrimitive<T>,
+{
+    if facets.len() < 4 {
+        return None;
+    }
+
+    // The centeroid of any set of points lies inside their convex hull, and is used to orient each facet's normal outwards
+    let centeroid = vertices
+        .iter()
+        .fold(SVector::<T, 3>::zeros(), |acc, vertex| acc + vertex.coords)
+        / vertices.len().as_();
+
+    Some(
+        facets
+            .iter()
+            .map(|&[idx1, idx2, idx3]| {
+                let vertex = vertices[idx1];
+                let mut normal = (vertices[idx2] - vertex).cross(&(vertices[idx3] - vertex));
+                if normal.dot(&(centeroid - vertex.coords)) > T::zero() {
+                    normal = -normal;
+                }
+                (normal, normal.dot(&vertex.coords))
+            })
+            .collect(),
+    )
+}
+
+/// Checks each point against precomputed half-spaces, skipping points outside the extents of the hull's vertices.
+fn are_multiple_points_in_half_spaces<T, const N: usize>(
+    points: &[Point<T, N>],
+    vertices: &[Point<T, N>],
+    half_spaces: Option<Vec<HalfSpace<T, N>>>,
+) -> Vec<bool>
+where
+    T: Bounded + Copy + RealField,
+{
+    let Some(half_spaces) = half_spaces else {
+        return points.iter().map(|_| false).collect();
+    };
+    let hull_extents = calculate_polygon_extents(vertices);
+
+    maybe_par_iter!(points)
+        .map(|current_point| {
+            hull_extents
+                .iter()
+                .zip(current_point.coords.iter())
+                .all(|(extent_for_dimension, coord)| extent_for_dimension.contains(coord))
+                && is_point_in_half_spaces(current_point, &half_spaces)
+        })
+        .collect()
+}
+
+/// Check if the provided point is within the provided convex polygon, by checking it against the half-plane of each edge.
+/// Points on the boundary of the polygon are considered to be within it.
+///
+/// # Arguments
+/// * `point`: A reference to a [`Point2`].
+/// * `hull`: A slice of [`Point2`]s representing the vertices of the convex polygon, ordered either clockwise or counter-clockwise.
+///
+/// # Generics:
+/// * `T`: Either an [`prim@f32`] or [`prim@f64`]
+///
+/// # Returns
+/// A boolean value, specifying if the point is within the convex polygon, always `false` if the polygon has no area.
+///
+/// # Warnings
+/// * The polygon must be convex, use [`is_single_point_in_polygon`](crate::point_in_polygon::is_single_point_in_polygon) otherwise.
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument("Is Point In 2D Convex Hull", skip_all, level = "debug")
+)]
+pub fn is_single_point_in_convex_hull_2d<T>(point: &Point2<T>, hull: &[Point2<T>]) -> bool
+where
+    T: Copy + RealField,
+{
+    calculate_half_planes(hull)
+        .is_some_and(|half_planes| is_point_in_half_spaces(point, &half_planes))
+}
+
+/// This function will run the [`is_single_point_in_convex_hull_2d`] for each on of the points given, and the provided convex polygon,
+/// But calculates the half-planes of the polygon only once, and pre-calculates its extents to quickly reject points far from it.
+///
+/// # Arguments
+/// * `points`: A slice of [`Point2`].
+/// * `hull`: A slice of [`Point2`]s representing the vertices of the convex polygon, ordered either clockwise or counter-clockwise.
+///
+/// # Generics:
+/// * `T`: Either an [`prim@f32`] or [`prim@f64`]
+///
+/// # Returns
+/// A [`Vec`] of booleans, with the same size as `points`, containing the result for each point.
+///
+/// # Warnings
+/// * The polygon must be convex, use [`are_multiple_points_in_polygon`](crate::point_in_polygon::are_multiple_points_in_polygon) otherwise.
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument("Are Points In 2D Convex Hull", skip_all, level = "info")
+)]
+pub fn are_multiple_points_in_convex_hull_2d<T>(
+    points: &[Point2<T>],
+    hull: &[Point2<T>],
+) -> Vec<bool>
+where
+    T: Bounded + Copy + RealField,
+{
+    are_multiple_points_in_half_spaces(points, hull, calculate_half_planes(hull))
+}
+
+/// Check if the provided point is within the provided convex polyhedron, by checking it against the plane of each facet.
+/// Points on the boundary of the polyhedron are considered to be within it.
+///
+/// # Arguments
+/// * `point`: A reference to a [`Point3`].
+/// * `vertices`: A slice of [`Point3`]s, containing the vertices of the convex polyhedron, and optionally points inside it.
+/// * `facets`: A slice of triangular facets, each made of the indices of its three vertices in `vertices`, in any winding order.
+///
+/// # Generics:
+/// * `T`: Either an [`prim@f32`] or [`prim@f64`]
+///
+/// # Returns
+/// A boolean value, specifying if the point is within the convex polyhedron, always `false` if it has fewer than 4 facets.
+///
+/// # Warnings
+/// * The polyhedron must be convex and closed, and every facet index must be within the bounds of `vertices`.
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument("Is Point In 3D Convex Hull", skip_all, level = "debug")
+)]
+pub fn is_single_point_in_convex_hull_3d<T>(
+    point: &Point3<T>,
+    vertices: &[Point3<T>],
+    facets: &[[usize; 3]],
+) -> bool
+where
+    T: Copy + RealField,
+    usize: AsPrimitive<T>,
+{
+    calculate_half_spaces(vertices, facets)
+        .is_some_and(|half_spaces| is_point_in_half_spaces(point, &half_spaces))
+}
+
+/// This function will run the [`is_single_point_in_convex_hull_3d`] for each on of the points given, and the provided convex polyhedron,
+/// But calculates the facet normals of the polyhedron only once, and pre-calculates its extents to quickly reject points far from it.
+///
+/// # Arguments
+/// * `points`: A slice of [`Point3`].
+/// * `vertices`: A slice of [`Point3`]s, containing the vertices of the convex polyhedron, and optionally points inside it.
+/// * `facets`: A slice of triangular facets, each made of the indices of its three vertices in `vertices`, in any winding order.
+///
+/// # Generics:
+/// * `T`: Either an [`prim@f32`] or [`prim@f64`]
+///
+/// # Returns
+/// A [`Vec`] of booleans, with the same size as `points`, containing the result for each point.
+///
+/// # Warnings
+/// * The polyhedron must be convex and closed, and every facet index must be within the bounds of `vertices`.
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument("Are Points In 3D Convex Hull", skip_all, level = "info")
+)]
+pub fn are_multiple_points_in_convex_hull_3d<T>(
+    points: &[Point3<T>],
+    vertices: &[Point3<T>],
+    facets: &[[usize; 3]],
+) -> Vec<bool>
+where
+    T: Bounded + Copy + RealField,
+    usize: AsPrimitive<T>,
+{
+    are_multiple_points_in_half_spaces(points, vertices, calculate_half_spaces(vertices, facets))
+}
+
+#[cfg(feature = "pregenerated")]
+macro_rules! impl_p_i_c_h_algorithm {
+    ($prec:expr, doc $doc:tt) => {
+        ::paste::paste! {
+            #[doc = "A " $doc "-precision implementation of a point-in-convex-hull algorithm."]
+            pub mod [<$doc _precision>] {
+                use nalgebra::{Point2, Point3};
+                use crate::Vec;
+
+                #[doc = "Check if the provided point is within the provided convex polygon, by checking it against the half-plane of each edge."]
+                #[doc = ""]
+                #[doc = "# Arguments"]
+                #[doc = "* `point`: A reference to a [`Point2`]."]
+                #[doc = "* `hull`: A slice of [`Point2`]s representing the vertices of the convex polygon, ordered either clockwise or counter-clockwise."]
+                #[doc = "# Returns"]
+                #[doc = "A boolean value, specifying if the point is within the convex polygon, always `false` if the polygon has no area."]
+                pub fn is_single_point_in_convex_hull_2d(point: &Point2<$prec>, hull: &[Point2<$prec>]) -> bool {
+                    super::is_single_point_in_convex_hull_2d(point, hull)
+                }
+
+                #[doc = "This function will run the [`is_single_point_in_convex_hull_2d`] for each on of the points given, and the provided convex polygon,"]
+                #[doc = "But calculates the half-planes of the polygon only once, and pre-calculates its extents to quickly reject points far from it."]
+                #[doc = ""]
+                #[doc = "# Arguments"]
+                #[doc = "* `points`: A slice of [`Point2`]."]
+                #[doc = "* `hull`: A slice of [`Point2`]s representing the vertices of the convex polygon, ordered either clockwise or counter-clockwise."]
+                #[doc = ""]
+                #[doc = "# Returns"]
+                #[doc = "A [`Vec`](crate::Vec) of booleans, with the same size as `points`, containing the result for each point."]
+                pub fn are_multiple_points_in_convex_hull_2d(
+                    points: &[Point2<$prec>],
+                    hull: &[Point2<$prec>],
+                ) -> Vec<bool> {
+                    super::are_multiple_points_in_convex_hull_2d(points, hull)
+                }
+
+                #[doc = "Check if the provided point is within the provided convex polyhedron, by checking it against the plane of each facet."]
+                #[doc = ""]
+                #[doc = "# Arguments"]
+                #[doc = "* `point`: A reference to a [`Point3`]."]
+                #[doc = "* `vertices`: A slice of [`Point3`]s, containing the vertices of the convex polyhedron, and optionally points inside it."]
+                #[doc = "* `facets`: A slice of triangular facets, each made of the indices of its three vertices in `vertices`, in any winding order."]
+                #[doc = "# Returns"]
+                #[doc = "A boolean value, specifying if the point is within the convex polyhedron, always `false` if it has fewer than 4 facets."]
+                pub fn is_single_point_in_convex_hull_3d(
+                    point: &Point3<$prec>,
+                    vertices: &[Point3<$prec>],
+                    facets: &[[usize; 3]],
+                ) -> bool {
+                    super::is_single_point_in_convex_hull_3d(point, vertices, facets)
+                }
+
+                #[doc = "This function will run the [`is_single_point_in_convex_hull_3d`] for each on of the points given, and the provided convex polyhedron,"]
+                #[doc = "But calculates the facet normals of the polyhedron only once, and pre-calculates its extents to quickly reject points far from it."]
+                #[doc = ""]
+                #[doc = "# Arguments"]
+                #[doc = "* `points`: A slice of [`Point3`]."]
+                #[doc = "* `vertices`: A slice of [`Point3`]s, containing the vertices of the convex polyhedron, and optionally points inside it."]
+                #[doc = "* `facets`: A slice of triangular facets, each made of the indices of its three vertices in `vertices`, in any winding order."]
+                #[doc = ""]
+                #[doc = "# Returns"]
+                #[doc = "A [`Vec`](crate::Vec) of booleans, with the same size as `points`, containing the result for each point."]
+                pub fn are_multiple_points_in_convex_hull_3d(
+                    points: &[Point3<$prec>],
+                    vertices: &[Point3<$prec>],
+                    facets: &[[usize; 3]],
+                ) -> Vec<bool> {
+                    super::are_multiple_points_in_convex_hull_3d(points, vertices, facets)
+                }
+            }
+        }
+    };
+}
+
+#[cfg(feature = "pregenerated")]
+impl_p_i_c_h_algorithm!(f32, doc single);
+#[cfg(feature = "pregenerated")]
+impl_p_i_c_h_algorithm!(f64, doc double);
+
+#[cfg(all(test, feature = "pregenerated"))]
+mod tests {
+    use nalgebra::{Point2, Point3};
+
+    use crate::Vec;
+
+    use super::*;
+
+    fn get_hull_2d_for_tests() -> Vec<Point2<f32>> {
+        // Counter-clockwise
+        Vec::from([
+            Point2::new(0.0, 0.0),
+            Point2::new(2.0, -1.0),
+            Point2::new(4.0, 1.0),
+            Point2::new(3.0, 3.0),
+            Point2::new(0.5, 2.5),
+        ])
+    }
+
+    fn get_hull_3d_for_tests() -> (Vec<Point3<f32>>, Vec<[usize; 3]>) {
+        // A cube spanning 0 to 1 on each axis, with an extra point inside it, and facets wound inconsistently
+        let vertices = Vec::from([
+            Point3::new(0.0, 0.0, 0.0),
+            Point3::new(1.0, 0.0, 0.0),
+            Point3::new(1.0, 1.0, 0.0),
+            Point3::new(0.0, 1.0, 0.0),
+            Point3::new(0.0, 0.0, 1.0),
+            Point3::new(1.0, 0.0, 1.0),
+            Point3::new(1.0, 1.0, 1.0),
+            Point3::new(0.0, 1.0, 1.0),
+            Point3::new(0.5, 0.5, 0.5),
+        ]);
+        let facets = Vec::from([
+            [0, 1, 2],
+            [0, 3, 2],
+            [4, 5, 6],
+            [4, 7, 6],
+            [0, 1, 5],
+            [0, 5, 4],
+            [3, 2, 6],
+            [3, 7, 6],
+            [0, 3, 7],
+            [0, 4, 7],
+            [1, 2, 6],
+            [1, 6, 5],
+        ]);
+        (vertices, facets)
+    }
+
+    #[test]
+    fn test_is_single_point_in_convex_hull_2d() {
+        let hull = get_hull_2d_for_tests();
+        assert!(single_precision::is_single_point_in_convex_hull_2d(
+            &Point2::new(2.0, 1.0),
+            &hull
+        ));
+        // Inside the extents of the hull, but outside of it
+        assert!(!single_precision::is_single_point_in_convex_hull_2d(
+            &Point2::new(3.8, -0.5),
+            &hull
+        ));
+        // On a vertex
+        assert!(single_precision::is_single_point_in_convex_hull_2d(
+            &Point2::new(0.0, 0.0),
+            &hull
+        ));
+
+        // The winding of the hull should not matter
+        let reversed_hull = hull.iter().rev().copied().collect::<Vec<_>>();
+        assert!(single_precision::is_single_point_in_convex_hull_2d(
+            &Point2::new(2.0, 1.0),
+            &reversed_hull
+        ));
+        assert!(!single_precision::is_single_point_in_convex_hull_2d(
+            &Point2::new(3.8, -0.5),
+            &reversed_hull
+        ));
+
+        // Degenerate hulls have no inside
+        assert!(!single_precision::is_single_point_in_convex_hull_2d(
+            &Point2::new(0.5, 0.5),
+            &[Point2::new(0.0, 0.0), Point2::new(1.0, 1.0)]
+        ));
+        assert!(!single_precision::is_single_point_in_convex_hull_2d(
+            &Point2::new(0.5, 0.5),
+            &[
+                Point2::new(0.0, 0.0),
+                Point2::new(1.0, 1.0),
+                Point2::new(2.0, 2.0)
+            ]
+        ));
+    }
+
+    #[test]
+    fn test_multiple_points_in_convex_hull_2d() {
+        let hull = get_hull_2d_for_tests();
+        let points = [
+            Point2::new(2.0, 1.0),   // Inside
+            Point2::new(3.8, -0.5),  // Outside, within extents
+            Point2::new(10.0, 10.0), // Outside extents
+            Point2::new(1.0, 2.0),   // Inside
+        ];
+
+        let result = single_precision::are_multiple_points_in_convex_hull_2d(&points, &hull);
+        assert_eq!(result, Vec::from([true, false, false, true]));
+        assert_eq!(
+            result,
+            points
+                .iter()
+                .map(|point| single_precision::is_single_point_in_convex_hull_2d(point, &hull))
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(
+            single_precision::are_multiple_points_in_convex_hull_2d(&points, &hull[..2]),
+            Vec::from([false; 4])
+        );
+    }
+
+    #[test]
+    fn test_is_single_point_in_convex_hull_3d() {
+        let (vertices, facets) = get_hull_3d_for_tests();
+        assert!(single_precision::is_single_point_in_convex_hull_3d(
+            &Point3::new(0.2, 0.7, 0.4),
+            &vertices,
+            &facets
+        ));
+        assert!(single_precision::is_single_point_in_convex_hull_3d(
+            &Point3::new(1.0, 0.5, 0.5),
+            &vertices,
+            &facets
+        ));
+        assert!(!single_precision::is_single_point_in_convex_hull_3d(
+            &Point3::new(0.5, 0.5, 1.1),
+            &vertices,
+            &facets
+        ));
+        assert!(!single_precision::is_single_point_in_convex_hull_3d(
+            &Point3::new(-0.1, 0.5, 0.5),
+            &vertices,
+            &facets
+        ));
+        assert!(!single_precision::is_single_point_in_convex_hull_3d(
+            &Point3::new(0.5, 0.5, 0.5),
+            &vertices,
+            &facets[..3]
+        ));
+    }
+
+    #[test]
+    fn test_multiple_points_in_convex_hull_3d() {
+        // A tetrahedron
+        let vertices = [
+            Point3::new(0.0, 0.0, 0.0),
+            Point3::new(1.0, 0.0, 0.0),
+            Point3::new(0.0, 1.0, 0.0),
+            Point3::new(0.0, 0.0, 1.0),
+        ];
+        let facets = [[0, 1, 2], [0, 1, 3], [0, 2, 3], [1, 2, 3]];
+        let points = [
+            Point3::new(0.1, 0.1, 0.1), // Inside
+            Point3::new(0.5, 0.5, 0.5), // Outside the slanted facet, within extents
+            Point3::new(0.2, 0.2, 0.5), // Inside
+            Point3::new(2.0, 0.0, 0.0), // Outside extents
+        ];
+
+        let result =
+            double_precision::are_multiple_points_in_convex_hull_3d(&points, &vertices, &facets);
+        assert_eq!(result, Vec::from([true, false, true, false]));
+        assert_eq!(
+            result,
+            points
+                .iter()
+                .map(|point| double_precision::is_single_point_in_convex_hull_3d(
+                    point, &vertices, &facets
+                ))
+                .collect::<Vec<_>>()
+        );
+    }
+}
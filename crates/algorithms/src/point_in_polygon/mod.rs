@@ -23,6 +23,8 @@
 
 use nalgebra::{Point2, RealField, Vector2};
 use num_traits::{AsPrimitive, Bounded};
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
 
 use crate::{utils::calculate_polygon_extents, Vec};
 
@@ -44,12 +46,11 @@ fn does_ray_intersect_polygon_segment<T>(
         && point.y <= vertex1.y.max(vertex2.y)
         && point.x <= vertex1.x.max(vertex2.x)
     {
-        let origin_x = (vertex1.y != vertex2.y)
-            .then(|| {
-                (point.y - vertex1.y) * (vertex2.x - vertex1.x) / (vertex2.y - vertex1.y)
-                    + vertex1.x
-            })
-            .unwrap_or(point.x);
+        let origin_x = if vertex1.y != vertex2.y {
+            (point.y - vertex1.y) * (vertex2.x - vertex1.x) / (vertex2.y - vertex1.y) + vertex1.x
+        } else {
+            point.x
+        };
 
         if vertex1.x == vertex2.x || point.x <= origin_x {
             return true;
@@ -116,8 +117,7 @@ pub fn are_multiple_points_in_polygon<T>(points: &[Point2<T>], polygon: &[Point2
 {
     let polygon_extents = calculate_polygon_extents(polygon);
 
-    points
-        .iter()
+    maybe_par_iter!(points)
         .map(|current_point| {
             // Verify that each coordinate is within the bounds of the polygon, will save a lot of computational load for large polygons
             polygon_extents
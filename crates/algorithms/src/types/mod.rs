@@ -21,11 +21,18 @@
  * SOFTWARE.
  */
 
-use crate::{marker::PhantomData, ops::RangeInclusive, utils::verify_rotation_matrix_determinant};
+use crate::{
+    marker::PhantomData,
+    ops::{Add, Mul, RangeInclusive, Sub},
+    utils::verify_rotation_matrix_determinant,
+    Debug,
+};
 use nalgebra::{
-    AbstractRotation, ArrayStorage, Const, Isometry, Matrix, Point, RealField, UnitComplex,
-    UnitQuaternion,
+    AbstractRotation, ArrayStorage, Const, Isometry, Matrix, Matrix2x3, Matrix3, Matrix3x6,
+    Matrix6, Normed, Point, RealField, SVector, Translation, UnitComplex, UnitQuaternion, Vector2,
+    Vector3, Vector6,
 };
+use num_traits::Zero;
 
 /// A shorthand way of specifying a symmetrical [`Matrix`](Matrix) of `N` size.
 /// Kind of similiar to nalgebra's [`SquareMatrix`](nalgebra::SquareMatrix) but simpler for our usecase
@@ -41,7 +48,28 @@ pub struct IsometryAbstractor<T: RealField, const N: usize> {
 /// without having to constrain every function by a million traits.
 pub trait AbstractIsometry<T: RealField, const N: usize> {
     /// This type is a placeholder for either [`UnitComplex`] or [`UnitQuaternion`] depending on number of dimensions.
-    type RotType: AbstractRotation<T, N> + Copy;
+    type RotType: AbstractRotation<T, N> + Copy + MaybeSend + MaybeSync;
+
+    /// A small pose increment, with a component for each degree of freedom of the transform,
+    /// ordered with its rotational components first, followed by its translational components.
+    /// This is a [`Vector3`] in 2D space (a single rotation and two translations), and a [`Vector6`] in 3D space (three rotations and three translations).
+    type Increment: Copy
+        + Debug
+        + PartialEq
+        + Zero
+        + Add<Output = Self::Increment>
+        + Mul<T, Output = Self::Increment>
+        + Normed<Norm = T>;
+
+    /// A square matrix with a row and a column for each component of an [`Increment`](AbstractIsometry::Increment),
+    /// this is a [`Matrix3`] in 2D space, and a [`Matrix6`] in 3D space.
+    type IncrementMatrix: Copy
+        + Debug
+        + PartialEq
+        + Zero
+        + Add<Output = Self::IncrementMatrix>
+        + Sub<Output = Self::IncrementMatrix>
+        + Mul<T, Output = Self::IncrementMatrix>;
 
     /// This function receives the old transform, the centeroids of both point clouds, and the covariance rotation mat
     /// It then performs [`SVD`](nalgebra::SVD) on the covariance matrix, and uses the resulting matrics
@@ -53,6 +81,29 @@ fn update_transform(
         mean_b: Point<T, N>,
         rot_mat: &SameSizeMat<T, N>,
     ) -> Isometry<T, Self::RotType, N>;
+
+    /// This function calculates the contribution of an already transformed point to the normal equations of a small pose increment,
+    /// applied on top of the current transform. These are `J^T * weight * J` and `J^T * residual`,
+    /// where `J` is the `N` by [`Increment`](AbstractIsometry::Increment) jacobian of the point with respect to the increment.
+    fn normal_equations(
+        point: &Point<T, N>,
+        weight: &SameSizeMat<T, N>,
+        residual: &SVector<T, N>,
+    ) -> (Self::IncrementMatrix, Self::Increment);
+
+    /// This function solves the normal equations `hessian * increment = -gradient` using a Cholesky decomposition,
+    /// and returns [`None`] if `hessian` is not positive definite, meaning the increment is not constrained in every direction.
+    fn solve_increment(
+        hessian: &Self::IncrementMatrix,
+        gradient: &Self::Increment,
+    ) -> Option<Self::Increment>;
+
+    /// This function receives the old transform, and a small pose increment.
+    /// It then constructs a new transform from the increment, which is multiplied by the old transform, and the result is returned
+    fn apply_increment(
+        old_transform: &Isometry<T, Self::RotType, N>,
+        increment: &Self::Increment,
+    ) -> Isometry<T, Self::RotType, N>;
 }
 
 impl<T> AbstractIsometry<T, 2> for IsometryAbstractor<T, 2>
@@ -60,6 +111,8 @@ impl<T> AbstractIsometry<T, 2> for IsometryAbstractor<T, 2>
     T: Copy + RealField,
 {
     type RotType = UnitComplex<T>;
+    type Increment = Vector3<T>;
+    type IncrementMatrix = Matrix3<T>;
 
     #[cfg_attr(
         feature = "tracing",
@@ -78,6 +131,35 @@ fn update_transform(
         Isometry::from_parts(translation.into(), Self::RotType::from_matrix(&rotation))
             * old_transform
     }
+
+    fn normal_equations(
+        point: &Point<T, 2>,
+        weight: &SameSizeMat<T, 2>,
+        residual: &SVector<T, 2>,
+    ) -> (Self::IncrementMatrix, Self::Increment) {
+        let jacobian = Matrix2x3::new(-point.y, T::one(), T::zero(), point.x, T::zero(), T::one());
+        (
+            jacobian.transpose() * weight * jacobian,
+            jacobian.transpose() * residual,
+        )
+    }
+
+    fn solve_increment(
+        hessian: &Self::IncrementMatrix,
+        gradient: &Self::Increment,
+    ) -> Option<Self::Increment> {
+        Some(hessian.cholesky()?.solve(&-gradient))
+    }
+
+    fn apply_increment(
+        old_transform: &Isometry<T, Self::RotType, 2>,
+        increment: &Self::Increment,
+    ) -> Isometry<T, Self::RotType, 2> {
+        Isometry::from_parts(
+            Translation::from(Vector2::new(increment[1], increment[2])),
+            UnitComplex::new(increment[0]),
+        ) * old_transform
+    }
 }
 
 impl<T> AbstractIsometry<T, 3> for IsometryAbstractor<T, 3>
@@ -85,6 +167,8 @@ impl<T> AbstractIsometry<T, 3> for IsometryAbstractor<T, 3>
     T: Copy + RealField,
 {
     type RotType = UnitQuaternion<T>;
+    type Increment = Vector6<T>;
+    type IncrementMatrix = Matrix6<T>;
 
     #[cfg_attr(
         feature = "tracing",
@@ -103,7 +187,108 @@ fn update_transform(
         Isometry::from_parts(translation.into(), Self::RotType::from_matrix(&rotation))
             * old_transform
     }
+
+    fn normal_equations(
+        point: &Point<T, 3>,
+        weight: &SameSizeMat<T, 3>,
+        residual: &SVector<T, 3>,
+    ) -> (Self::IncrementMatrix, Self::Increment) {
+        let jacobian = Matrix3x6::from_row_slice(&[
+            T::zero(),
+            point.z,
+            -point.y,
+            T::one(),
+            T::zero(),
+            T::zero(),
+            -point.z,
+            T::zero(),
+            point.x,
+            T::zero(),
+            T::one(),
+            T::zero(),
+            point.y,
+            -point.x,
+            T::zero(),
+            T::zero(),
+            T::zero(),
+            T::one(),
+        ]);
+        (
+            jacobian.transpose() * weight * jacobian,
+            jacobian.transpose() * residual,
+        )
+    }
+
+    fn solve_increment(
+        hessian: &Self::IncrementMatrix,
+        gradient: &Self::Increment,
+    ) -> Option<Self::Increment> {
+        Some(hessian.cholesky()?.solve(&-gradient))
+    }
+
+    fn apply_increment(
+        old_transform: &Isometry<T, Self::RotType, 3>,
+        increment: &Self::Increment,
+    ) -> Isometry<T, Self::RotType, 3> {
+        Isometry::from_parts(
+            Translation::from(Vector3::new(increment[3], increment[4], increment[5])),
+            UnitQuaternion::from_scaled_axis(Vector3::new(
+                increment[0],
+                increment[1],
+                increment[2],
+            )),
+        ) * old_transform
+    }
 }
 
+/// A marker trait requiring [`Send`] only when the `rayon` feature is enabled, and implemented for every type otherwise.
+/// This allows bounds to only demand thread safety when an algorithm actually runs in parallel.
+#[cfg(feature = "rayon")]
+pub trait MaybeSend: Send {}
+#[cfg(feature = "rayon")]
+impl<T: Send> MaybeSend for T {}
+
+/// A marker trait requiring [`Send`] only when the `rayon` feature is enabled, and implemented for every type otherwise.
+/// This allows bounds to only demand thread safety when an algorithm actually runs in parallel.
+#[cfg(not(feature = "rayon"))]
+pub trait MaybeSend {}
+#[cfg(not(feature = "rayon"))]
+impl<T> MaybeSend for T {}
+
+/// A marker trait requiring [`Sync`] only when the `rayon` feature is enabled, and implemented for every type otherwise.
+/// This allows bounds to only demand thread safety when an algorithm actually runs in parallel.
+#[cfg(feature = "rayon")]
+pub trait MaybeSync: Sync {}
+#[cfg(feature = "rayon")]
+impl<T: Sync> MaybeSync for T {}
+
+/// A marker trait requiring [`Sync`] only when the `rayon` feature is enabled, and implemented for every type otherwise.
+/// This allows bounds to only demand thread safety when an algorithm actually runs in parallel.
+#[cfg(not(feature = "rayon"))]
+pub trait MaybeSync {}
+#[cfg(not(feature = "rayon"))]
+impl<T> MaybeSync for T {}
+
 /// A type which is simply an `N` length array of [`RangeInclusive`]s, representing the minimum and maximum coordinates for each dimension.
 pub type PolygonExtents<T, const N: usize> = [RangeInclusive<T>; N];
+
+/// Specifies how the neighbourhood of a point is gathered, for algorithms that operate on local point neighbourhoods.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum NeighbourhoodSearch<T> {
+    /// The `k` nearest points, including the point itself.
+    KNearest(usize),
+    /// All points within the given radius of the point, including the point itself.
+    Radius(T),
+}
+
+/// The estimated surface normal of a point, along with the curvature of its neighbourhood.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PointNormal<T: RealField, const N: usize> {
+    /// The unit normal vector, oriented towards the viewpoint.
+    pub normal: SVector<T, N>,
+    /// The surface variation of the neighbourhood, `λ0 / Σλ`, where `λ0` is the smallest eigenvalue of its covariance.
+    /// `0` means the neighbourhood is perfectly flat(or straight, in 2D).
+    pub curvature: T,
+}
@@ -26,7 +26,9 @@
     ops::RangeInclusive,
     types::{PolygonExtents, SameSizeMat},
 };
-use nalgebra::{Const, DimMin, Point, RealField, Scalar};
+use nalgebra::{
+    Const, DMatrix, DimMin, Matrix2, Matrix3, Point, RealField, Scalar, SymmetricEigen,
+};
 use num_traits::{Bounded, NumOps};
 
 /// Various utility functions regarding point clouds of 2 or 3 dimensions.
@@ -104,11 +106,70 @@ pub(crate) fn verify_rotation_matrix_determinant<T, const N: usize>(
     u * v_t
 }
 
+/// Calculates the eigenvalues and eigenvectors of a symmetric matrix, using nalgebra's [`SymmetricEigen`].
+/// The decomposition is performed on a fixed-size copy of the matrix in 2D and 3D, as the dimension bounds of [`SymmetricEigen`]
+/// cannot be satisfied for a generic `N`, and on a dynamically sized copy for any other size.
+/// This is mostly useful for covariance matrices, where the eigenvectors describe the principal axes of a set of points.
+///
+/// # Arguments
+/// * `mat`: A symmetric [`SameSizeMat`], only symmetric matrices are supported.
+///
+/// # Generics
+/// * `T`: Either an [`f32`] or [`f64`].
+/// * `N`: A const usize, specifying the size of the matrix.
+///
+/// # Returns
+/// A tuple of
+/// * An array of the eigenvalues, sorted in ascending order.
+/// * A [`SameSizeMat`], whose columns are the normalized eigenvectors, in the same order as the eigenvalues.
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument("Symmetric Eigen Decomposition", skip_all, level = "trace")
+)]
+pub(crate) fn symmetric_eigen_decomposition<T, const N: usize>(
+    mat: SameSizeMat<T, N>,
+) -> ([T; N], SameSizeMat<T, N>)
+where
+    T: Copy + RealField,
+{
+    // Copies the matrix into a matrix of a type which satisfies the bounds of SymmetricEigen, and the results back out of it
+    macro_rules! decompose {
+        ($matrix:expr) => {{
+            let SymmetricEigen {
+                eigenvalues,
+                eigenvectors,
+            } = SymmetricEigen::new($matrix);
+            (
+                array::from_fn::<T, N, _>(|idx| eigenvalues[idx]),
+                SameSizeMat::<T, N>::from_fn(|row, col| eigenvectors[(row, col)]),
+            )
+        }};
+    }
+
+    let (eigenvalues, eigenvectors) = match N {
+        2 => decompose!(Matrix2::from_fn(|row, col| mat[(row, col)])),
+        3 => decompose!(Matrix3::from_fn(|row, col| mat[(row, col)])),
+        _ => decompose!(DMatrix::from_column_slice(N, N, mat.as_slice())),
+    };
+
+    let mut order: [usize; N] = array::from_fn(|idx| idx);
+    order.sort_by(|&a, &b| {
+        eigenvalues[a]
+            .partial_cmp(&eigenvalues[b])
+            .unwrap_or(core::cmp::Ordering::Equal)
+    });
+
+    (
+        array::from_fn(|idx| eigenvalues[order[idx]]),
+        SameSizeMat::from_fn(|row, col| eigenvectors[(row, order[col])]),
+    )
+}
+
 #[cfg(test)]
 pub(crate) mod tests {
     use super::*;
     use crate::Vec;
-    use nalgebra::{Matrix2, Point, Point2, Point3};
+    use nalgebra::{Matrix2, Matrix3, Point, Point2, Point3, Vector2};
 
     #[test]
     fn test_calculate_polygon_extents() {
@@ -170,4 +231,27 @@ fn test_verify_rotation_matrix_determinant() {
         assert_eq!(func_dot.m12, -regular_dot.m12);
         assert_eq!(func_dot.m22, -regular_dot.m22);
     }
+
+    #[test]
+    fn test_symmetric_eigen_decomposition() {
+        let mat = Matrix3::<f64>::new(2.0, 1.0, 0.0, 1.0, 2.0, 0.0, 0.0, 0.0, 5.0);
+        let (eigenvalues, eigenvectors) = symmetric_eigen_decomposition(mat);
+
+        // Eigenvalues are sorted in ascending order
+        for (eigenvalue, expected) in eigenvalues.iter().zip([1.0, 3.0, 5.0]) {
+            assert!((eigenvalue - expected).abs() < 1e-10);
+        }
+
+        // Each column must satisfy the eigen equation
+        for (idx, eigenvalue) in eigenvalues.iter().enumerate() {
+            let eigenvector = eigenvectors.column(idx);
+            assert!((mat * eigenvector - eigenvector * *eigenvalue).norm() < 1e-10);
+            assert!((eigenvector.norm() - 1.0).abs() < 1e-10);
+        }
+
+        let mat = Matrix2::<f64>::new(4.0, 0.0, 0.0, 1.0);
+        let (eigenvalues, eigenvectors) = symmetric_eigen_decomposition(mat);
+        assert_eq!(eigenvalues, [1.0, 4.0]);
+        assert_eq!(eigenvectors.column(0).abs(), Vector2::new(0.0, 1.0));
+    }
 }
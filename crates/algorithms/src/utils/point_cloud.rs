@@ -21,11 +21,20 @@
  * SOFTWARE.
  */
 
-use crate::{array, utils::distance_squared, HashMap, Vec};
+use crate::{
+    array,
+    kd_tree::KDTree,
+    types::{MaybeSync, NeighbourhoodSearch, PointNormal, SameSizeMat},
+    utils::{distance_squared, symmetric_eigen_decomposition},
+    voxel_grid::VoxelGrid,
+    Vec,
+};
 use nalgebra::{
     AbstractRotation, ClosedAdd, ClosedDiv, ComplexField, Isometry, Point, RealField, Scalar,
 };
 use num_traits::{AsPrimitive, Bounded, NumOps, Zero};
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
 
 /// Calculates the mean(centeroid) of the point cloud.
 ///
@@ -61,6 +70,249 @@ pub fn calculate_point_cloud_center<T, const N: usize>(points: &[Point<T, N>]) -
         / points.len().as_()
 }
 
+/// Calculates the covariance matrix of a point cloud, describing how its points are spread around its centeroid.
+///
+/// # Arguments
+/// * `points`: a slice of [`Point`], representing the point cloud.
+///
+/// # Generics
+/// * `T`: Either an [`f32`] or [`f64`].
+/// * `N`: A const usize, specifying the amount of dimensions in the points.
+///
+/// # Returns
+/// A [`SameSizeMat`] of size `N` by `N`, containing the covariance matrix, or a zeroed matrix if the point cloud is empty.
+#[inline]
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument("Calculate Covariance Matrix", skip_all, level = "trace")
+)]
+pub(crate) fn calculate_point_cloud_covariance<T, const N: usize>(
+    points: &[Point<T, N>],
+) -> SameSizeMat<T, N>
+where
+    T: Copy + RealField,
+    usize: AsPrimitive<T>,
+{
+    if points.is_empty() {
+        return SameSizeMat::zeros();
+    }
+
+    let mean = calculate_point_cloud_center(points);
+    points
+        .iter()
+        .fold(SameSizeMat::zeros(), |covariance, point| {
+            let distance_from_centeroid = point - mean;
+            covariance + distance_from_centeroid * distance_from_centeroid.transpose()
+        })
+        / points.len().as_()
+}
+
+/// Estimates the surface normal of every point in the point cloud, using a principal component analysis of each point's neighbourhood.
+/// In 2D the normal is perpendicular to the local line, and in 3D it is perpendicular to the local surface.
+///
+/// # Arguments
+/// * `points`: a slice of [`Point`], representing the point cloud.
+/// * `neighbourhood`: a [`NeighbourhoodSearch`], specifying which points are considered neighbours of each point.
+/// * `viewpoint`: a [`Point`] which all normals will be oriented towards, usually the sensor's origin.
+///
+/// # Generics
+/// * `T`: Either an [`f32`] or [`f64`].
+/// * `N`: A const usize, either `2` or `3`.
+///
+/// # Returns
+/// A [`Vec`] with a [`PointNormal`] for each point in `points`, in the same order,
+/// or [`None`] for points whose neighbourhood holds fewer than `N` points, as a normal cannot be determined for them.
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument("Estimate Point Cloud Normals", skip_all, level = "info")
+)]
+pub fn estimate_normals<T, const N: usize>(
+    points: &[Point<T, N>],
+    neighbourhood: NeighbourhoodSearch<T>,
+    viewpoint: &Point<T, N>,
+) -> Vec<Option<PointNormal<T, N>>>
+where
+    T: Copy + Default + RealField,
+    usize: AsPrimitive<T>,
+{
+    let kd_tree = KDTree::from(points);
+    points
+        .iter()
+        .map(|point| {
+            let neighbours = match neighbourhood {
+                NeighbourhoodSearch::KNearest(k) => kd_tree
+                    .k_nearest(point, k)
+                    .into_iter()
+                    .map(|(neighbour, _)| neighbour)
+                    .collect::<Vec<_>>(),
+                NeighbourhoodSearch::Radius(radius) => kd_tree.within_radius(point, radius),
+            };
+            if neighbours.len() < N {
+                return None;
+            }
+
+            let (eigenvalues, eigenvectors) =
+                symmetric_eigen_decomposition(calculate_point_cloud_covariance(&neighbours));
+            let eigenvalue_sum = eigenvalues
+                .iter()
+                .fold(T::zero(), |acc, eigenvalue| acc + *eigenvalue);
+
+            // The eigenvector of the smallest eigenvalue is the direction in which the neighbourhood varies the least
+            let mut normal = eigenvectors.column(0).into_owned();
+            if normal.dot(&(viewpoint - point)) < T::zero() {
+                normal = -normal;
+            }
+
+            Some(PointNormal {
+                normal,
+                curvature: if eigenvalue_sum > T::zero() {
+                    eigenvalues[0] / eigenvalue_sum
+                } else {
+                    T::zero()
+                },
+            })
+        })
+        .collect()
+}
+
+/// Collects the points marked as inliers by `inlier_mask`, returning them along with the mask.
+fn filter_point_cloud<T, const N: usize>(
+    points: &[Point<T, N>],
+    inlier_mask: Vec<bool>,
+) -> (Vec<Point<T, N>>, Vec<bool>)
+where
+    T: Scalar,
+{
+    let inliers = points
+        .iter()
+        .zip(inlier_mask.iter())
+        .filter(|(_, is_inlier)| **is_inlier)
+        .map(|(point, _)| point.clone())
+        .collect();
+    (inliers, inlier_mask)
+}
+
+/// Removes points whose neighbourhood is sparser than that of most points in the point cloud.
+/// For each point, the mean distance to its `k` nearest neighbours is calculated,
+/// and points whose mean distance exceeds the global mean of these distances by more than `std_ratio` standard deviations are removed.
+///
+/// # Arguments
+/// * `points`: a slice of [`Point`], representing the point cloud.
+/// * `k`: the amount of nearest neighbours to consider for each point, not including the point itself.
+/// * `std_ratio`: the amount of standard deviations above the global mean distance, beyond which a point is considered an outlier.
+///
+/// # Generics
+/// * `T`: Either an [`f32`] or [`f64`].
+/// * `N`: A const usize, representing the number of dimensions in the points.
+///
+/// # Returns
+/// A tuple of
+/// * A [`Vec`] of [`Point`], containing the inliers in their original order.
+/// * A [`Vec`] of [`bool`], with the same size as `points`, specifying whether each point is an inlier.
+///
+/// # Warnings
+/// * If `k` is `0`, all points are considered inliers.
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument("Statistical Outlier Removal", skip_all, level = "info")
+)]
+pub fn statistical_outlier_removal<T, const N: usize>(
+    points: &[Point<T, N>],
+    k: usize,
+    std_ratio: T,
+) -> (Vec<Point<T, N>>, Vec<bool>)
+where
+    T: Copy + Default + RealField,
+    usize: AsPrimitive<T>,
+{
+    if k == 0 || points.is_empty() {
+        return filter_point_cloud(points, points.iter().map(|_| true).collect());
+    }
+
+    let kd_tree = KDTree::from(points);
+    let mean_distances = points
+        .iter()
+        .map(|point| {
+            // The closest point is always the point itself, or an identical one
+            let neighbours = kd_tree.k_nearest(point, k + 1);
+            let num_neighbours = neighbours.len() - 1;
+            if num_neighbours == 0 {
+                return T::zero();
+            }
+
+            neighbours
+                .into_iter()
+                .skip(1)
+                .fold(T::zero(), |acc, (_, distance_squared)| {
+                    acc + distance_squared.sqrt()
+                })
+                / num_neighbours.as_()
+        })
+        .collect::<Vec<_>>();
+
+    let num_points: T = points.len().as_();
+    let global_mean = mean_distances
+        .iter()
+        .fold(T::zero(), |acc, distance| acc + *distance)
+        / num_points;
+    let global_std = (mean_distances.iter().fold(T::zero(), |acc, distance| {
+        acc + (*distance - global_mean) * (*distance - global_mean)
+    }) / num_points)
+        .sqrt();
+
+    let threshold = global_mean + std_ratio * global_std;
+    filter_point_cloud(
+        points,
+        mean_distances
+            .into_iter()
+            .map(|distance| distance <= threshold)
+            .collect(),
+    )
+}
+
+/// Removes points which have fewer than `min_neighbours` other points within `radius` of them.
+///
+/// # Arguments
+/// * `points`: a slice of [`Point`], representing the point cloud.
+/// * `radius`: the radius around each point in which to count neighbours, inclusive.
+/// * `min_neighbours`: the minimal amount of neighbours a point must have to be considered an inlier, not including the point itself.
+///
+/// # Generics
+/// * `T`: Either an [`f32`] or [`f64`].
+/// * `N`: A const usize, representing the number of dimensions in the points.
+///
+/// # Returns
+/// A tuple of
+/// * A [`Vec`] of [`Point`], containing the inliers in their original order.
+/// * A [`Vec`] of [`bool`], with the same size as `points`, specifying whether each point is an inlier.
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument("Radius Outlier Removal", skip_all, level = "info")
+)]
+pub fn radius_outlier_removal<T, const N: usize>(
+    points: &[Point<T, N>],
+    radius: T,
+    min_neighbours: usize,
+) -> (Vec<Point<T, N>>, Vec<bool>)
+where
+    T: Copy + Default + RealField,
+{
+    let kd_tree = KDTree::from(points);
+    filter_point_cloud(
+        points,
+        points
+            .iter()
+            .map(|point| {
+                // The point itself is always within the radius
+                kd_tree
+                    .within_radius_iter(point, radius)
+                    .nth(min_neighbours)
+                    .is_some()
+            })
+            .collect(),
+    )
+}
+
 /// Finds the closest matching target point to the passed source point.
 ///
 /// # Arguments
@@ -85,23 +337,53 @@ pub fn find_closest_point<T, const N: usize>(
     point: &Point<T, N>,
     all_points: &[Point<T, N>],
 ) -> Point<T, N>
+where
+    T: Bounded + Copy + Default + NumOps + PartialOrd + Scalar,
+{
+    all_points[find_closest_point_index(point, all_points)]
+}
+
+/// Finds the index of the closest matching target point to the passed source point.
+///
+/// # Arguments
+/// * `point`: A [`Point`], for which to find the closest point.
+/// * `all_points`: A slice of [`Point`], representing the target point cloud.
+///
+/// # Generics
+/// * `T`: Either an [`f32`] or [`f64`].
+/// * `N`: A const usize, representing the number of dimensions in the points.
+///
+/// # Returns
+/// A [`usize`], representing the index of said closest point in `all_points`.
+///
+/// # Panics
+/// this function will panic if the `target_points` is an empty slice.
+#[inline]
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument("Find Closest Point Index", skip_all)
+)]
+pub(crate) fn find_closest_point_index<T, const N: usize>(
+    point: &Point<T, N>,
+    all_points: &[Point<T, N>],
+) -> usize
 where
     T: Bounded + Copy + Default + NumOps + PartialOrd + Scalar,
 {
     assert!(!all_points.is_empty(), "Point cloud must not be empty");
 
     let mut current_distance = T::max_value();
-    let mut current_point = all_points[0]; // Guaranteed to exist
+    let mut current_idx = 0; // Guaranteed to exist
 
-    for target_point in all_points.iter() {
+    for (idx, target_point) in all_points.iter().enumerate() {
         let distance = distance_squared(point, target_point);
         if distance < current_distance {
             current_distance = distance;
-            current_point = *target_point;
+            current_idx = idx;
         }
     }
 
-    current_point
+    current_idx
 }
 
 /// Generates a randomized points cloud within a specified spherical range.
@@ -135,6 +417,22 @@ pub fn generate_point_cloud<T, const N: usize>(
         .collect()
 } // Just calls a different function a number of times, no specific test needed
 
+/// Generates a room corner, made of a floor and two perpendicular walls,
+/// each a grid of `steps` by `steps` points which are `spacing` apart.
+#[cfg(test)]
+pub(crate) fn generate_corner_3d(steps: usize, spacing: f64) -> Vec<nalgebra::Point3<f64>> {
+    (0..steps)
+        .flat_map(|a| (0..steps).map(move |b| (a as f64 * spacing, b as f64 * spacing)))
+        .flat_map(|(a, b)| {
+            [
+                nalgebra::Point3::new(a, b, 0.0),
+                nalgebra::Point3::new(a, 0.0, b + spacing),
+                nalgebra::Point3::new(0.0, a + spacing, b + spacing),
+            ]
+        })
+        .collect()
+}
+
 /// Transform a point cloud, returning a transformed copy.
 /// This function does not mutate the original point cloud.
 ///
@@ -160,14 +458,36 @@ pub fn transform_point_cloud<T, const N: usize, R>(
 ) -> Vec<Point<T, N>>
 where
     T: RealField,
-    R: AbstractRotation<T, N>,
+    R: AbstractRotation<T, N> + MaybeSync,
 {
-    source_points
-        .iter()
+    maybe_par_iter!(source_points)
         .map(|point| isometry_matrix.transform_point(point))
         .collect()
 } // Just calls a different function a number of times, no specific test needed
 
+/// Calculates the integer coordinates of the voxel containing a point.
+///
+/// # Arguments
+/// * `point`: a [`Point`].
+/// * `voxel_size`: a floating point number, specifying the size for each voxel.
+///
+/// # Generics
+/// * `T`: Either an [`f32`] or [`f64`].
+/// * `N`: A const usize, representing the number of dimensions in the point.
+///
+/// # Returns
+/// An array of `N` [`isize`], representing the coordinates of the voxel.
+#[inline]
+pub(crate) fn calculate_voxel_coordinates<T, const N: usize>(
+    point: &Point<T, N>,
+    voxel_size: T,
+) -> [isize; N]
+where
+    T: ComplexField + Copy + AsPrimitive<isize>,
+{
+    array::from_fn(|idx| (point[idx] / voxel_size).floor().as_())
+}
+
 /// Downsample a points cloud, returning a new point cloud, with all points within each voxel combined into their mean.
 ///
 /// # Arguments
@@ -183,7 +503,7 @@ pub fn transform_point_cloud<T, const N: usize, R>(
 ///
 /// # Warnings
 /// * Point cloud order is *never* guaranteed.
-/// * When compiling for no_std, a `BTreeMap` from the `alloc` crate is used in place of a [`HashMap`].
+/// * When compiling for no_std, a `BTreeMap` from the `alloc` crate is used in place of a `HashMap`.
 #[cfg_attr(
     feature = "tracing",
     tracing::instrument("Downsample Point Cloud Using Voxels", skip_all)
@@ -196,25 +516,9 @@ pub fn voxel_downsample_point_cloud<T, const N: usize>(
     T: ComplexField + Copy + AsPrimitive<isize>,
     usize: AsPrimitive<T>,
 {
-    let mut voxel_map: HashMap<[isize; N], Vec<Point<T, N>>> = HashMap::new();
-
-    // Assign points to voxels
-    for point in points {
-        let voxel_coords: [isize; N] =
-            array::from_fn(|idx| (point[idx] / voxel_size).floor().as_());
-        voxel_map.entry(voxel_coords).or_default().push(*point);
-    }
-
     // Compute centroid for each voxel and collect them as the downsampled points
-    voxel_map
-        .into_values()
-        .map(|points_in_voxel| {
-            let num_points = points_in_voxel.len().as_();
-            let sum = points_in_voxel
-                .into_iter()
-                .fold(Point::default(), |acc, p| acc + p.coords);
-            sum / num_points
-        })
+    VoxelGrid::from_points(points, voxel_size)
+        .centroids()
         .collect()
 }
 
@@ -222,7 +526,7 @@ pub fn voxel_downsample_point_cloud<T, const N: usize>(
 mod tests {
     use super::*;
     use crate::Vec;
-    use nalgebra::{Point, Point2, Point3};
+    use nalgebra::{ArrayStorage, Point, Point2, Point3};
 
     #[test]
     fn test_empty_point_cloud_center() {
@@ -304,4 +608,155 @@ fn test_downsample_point_cloud() {
             .iter()
             .any(|element| *element == Point3::new(-5.95, -5.0, -3.95)));
     }
+
+    #[test]
+    fn test_calculate_point_cloud_covariance() {
+        let points: [Point<f64, 2>; 4] = [
+            Point::from([1.0, 0.0]),
+            Point::from([-1.0, 0.0]),
+            Point::from([0.0, 2.0]),
+            Point::from([0.0, -2.0]),
+        ];
+
+        let covariance = calculate_point_cloud_covariance(&points);
+        assert_eq!(
+            covariance,
+            SameSizeMat::from_data(ArrayStorage([[0.5, 0.0], [0.0, 2.0]])),
+            "The calculated covariance matrix does not match the expected value."
+        );
+        assert_eq!(
+            calculate_point_cloud_covariance::<f64, 2>(&[]),
+            SameSizeMat::<f64, 2>::zeros()
+        );
+    }
+
+    #[test]
+    fn test_estimate_normals_2d() {
+        // A straight line along the X axis, viewed from below
+        let points = (0..10)
+            .map(|idx| Point2::new(idx as f64 * 0.1, 1.0))
+            .collect::<Vec<_>>();
+
+        let normals = estimate_normals(
+            &points,
+            NeighbourhoodSearch::KNearest(4),
+            &Point2::new(0.5, -3.0),
+        );
+        assert_eq!(normals.len(), points.len());
+        for point_normal in normals {
+            let point_normal = point_normal.expect("Every point should have a normal");
+            assert!((point_normal.normal.y + 1.0).abs() < 1e-6);
+            assert!(point_normal.curvature.abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn test_estimate_normals_3d() {
+        // A flat grid on the XY plane, viewed from above
+        let points = (0..100)
+            .map(|idx| Point3::new((idx % 10) as f64 * 0.1, (idx / 10) as f64 * 0.1, 0.0))
+            .collect::<Vec<_>>();
+
+        for neighbourhood in [
+            NeighbourhoodSearch::KNearest(8),
+            NeighbourhoodSearch::Radius(0.15),
+        ] {
+            let normals = estimate_normals(&points, neighbourhood, &Point3::new(0.0, 0.0, 10.0));
+            for point_normal in normals {
+                let point_normal = point_normal.expect("Every point should have a normal");
+                assert!((point_normal.normal.z - 1.0).abs() < 1e-6);
+                assert!(point_normal.curvature.abs() < 1e-6);
+            }
+        }
+
+        // The same grid viewed from below should flip the normals
+        let normals = estimate_normals(
+            &points,
+            NeighbourhoodSearch::KNearest(8),
+            &Point3::new(0.0, 0.0, -10.0),
+        );
+        assert!(normals
+            .iter()
+            .all(|point_normal| (point_normal.unwrap().normal.z + 1.0).abs() < 1e-6));
+
+        // A corner has a curved neighbourhood
+        let corner = [
+            Point3::new(0.0, 0.0, 0.0),
+            Point3::new(1.0, 0.0, 0.0),
+            Point3::new(0.0, 1.0, 0.0),
+            Point3::new(0.0, 0.0, 1.0),
+        ];
+        let normals =
+            estimate_normals(&corner, NeighbourhoodSearch::KNearest(4), &Point3::origin());
+        assert!(normals[0].unwrap().curvature > 0.1);
+    }
+
+    #[test]
+    fn test_estimate_normals_sparse_neighbourhood() {
+        let points = [Point3::new(0.0, 0.0, 0.0), Point3::new(5.0, 0.0, 0.0)];
+        let normals =
+            estimate_normals(&points, NeighbourhoodSearch::Radius(1.0), &Point3::origin());
+        assert!(normals.iter().all(Option::is_none));
+
+        assert!(estimate_normals::<f64, 3>(
+            &[],
+            NeighbourhoodSearch::KNearest(8),
+            &Point3::origin()
+        )
+        .is_empty());
+    }
+
+    fn generate_noisy_grid() -> Vec<Point2<f64>> {
+        let mut points = (0..100)
+            .map(|idx| Point2::new((idx % 10) as f64, (idx / 10) as f64))
+            .collect::<Vec<_>>();
+        points.insert(42, Point2::new(50.0, 50.0));
+        points.push(Point2::new(-30.0, 10.0));
+        points.push(Point2::new(-30.5, 10.0));
+        points
+    }
+
+    #[test]
+    fn test_statistical_outlier_removal() {
+        let points = generate_noisy_grid();
+        let (inliers, inlier_mask) = statistical_outlier_removal(&points, 4, 1.0);
+        assert_eq!(inlier_mask.len(), points.len());
+        assert_eq!(inliers.len(), 100);
+        assert!(!inlier_mask[42]);
+        assert!(!inlier_mask[101] && !inlier_mask[102]);
+        assert_eq!(
+            inliers,
+            points
+                .iter()
+                .zip(inlier_mask.iter())
+                .filter_map(|(point, is_inlier)| is_inlier.then_some(*point))
+                .collect::<Vec<_>>()
+        );
+
+        // Without neighbours to consider, every point is an inlier
+        let (inliers, inlier_mask) = statistical_outlier_removal(&points, 0, 1.0);
+        assert_eq!(inliers, points);
+        assert!(inlier_mask.into_iter().all(|is_inlier| is_inlier));
+        assert!(statistical_outlier_removal::<f64, 2>(&[], 4, 1.0)
+            .0
+            .is_empty());
+    }
+
+    #[test]
+    fn test_radius_outlier_removal() {
+        let points = generate_noisy_grid();
+
+        // Grid corners have exactly two neighbours within the radius
+        let (inliers, inlier_mask) = radius_outlier_removal(&points, 1.0, 2);
+        assert_eq!(inliers.len(), 100);
+        assert!(!inlier_mask[42] && !inlier_mask[101] && !inlier_mask[102]);
+
+        // The two distant points are each other's neighbours
+        let (inliers, inlier_mask) = radius_outlier_removal(&points, 1.0, 1);
+        assert_eq!(inliers.len(), 102);
+        assert!(!inlier_mask[42] && inlier_mask[101] && inlier_mask[102]);
+
+        let (inliers, _) = radius_outlier_removal(&points, 1.0, 0);
+        assert_eq!(inliers, points);
+    }
 }
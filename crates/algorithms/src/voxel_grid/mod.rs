@@ -0,0 +1,478 @@
+// SPDX-License-Identifier: MIT
+/*
+ * Copyright (c) [2023 - Present] Emily Matheys <emilymatt96@gmail.com>
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+#[cfg(feature = "serde")]
+use nalgebra::Scalar;
+use nalgebra::{ComplexField, Point, RealField};
+use num_traits::AsPrimitive;
+
+use crate::{
+    array,
+    types::SameSizeMat,
+    utils::point_cloud::{
+        calculate_point_cloud_center, calculate_point_cloud_covariance, calculate_voxel_coordinates,
+    },
+    HashMap, Vec,
+};
+
+/// The statistics of the points inside a single voxel.
+///
+/// # Generics
+/// * `T`: Either an [`f32`] or [`f64`].
+/// * `N`: A const usize, representing the number of dimensions in the points.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct VoxelStatistics<T: RealField, const N: usize> {
+    /// The amount of points inside the voxel.
+    pub num_points: usize,
+    /// The mean of the points inside the voxel.
+    pub centroid: Point<T, N>,
+    /// The covariance matrix of the points inside the voxel.
+    pub covariance: SameSizeMat<T, N>,
+}
+
+/// Iterates over the integer coordinates of a voxel and all voxels adjacent to it, including diagonally adjacent ones.
+///
+/// # Arguments
+/// * `coordinates`: the integer coordinates of the voxel.
+///
+/// # Generics
+/// * `N`: A const usize, representing the number of dimensions in the voxel coordinates.
+///
+/// # Returns
+/// An [`Iterator`] of the `3^N` voxel coordinates in the neighbourhood, starting with the lowest one in every dimension.
+pub fn neighbouring_voxels<const N: usize>(
+    coordinates: &[isize; N],
+) -> impl Iterator<Item = [isize; N]> {
+    let coordinates = *coordinates;
+    (0..3usize.pow(N as u32)).map(move |neighbour_idx| {
+        array::from_fn(|idx| {
+            coordinates[idx] + (neighbour_idx / 3usize.pow(idx as u32) % 3) as isize - 1
+        })
+    })
+}
+
+/// A sparse grid of equally sized voxels, mapping the integer coordinates of each occupied voxel to the points inside it.
+/// Useful for spatial hashing of point clouds, such as for downsampling, cell-based registration or occupancy estimation.
+///
+/// # Generics
+/// * `T`: Either an [`f32`] or [`f64`].
+/// * `N`: A const usize, representing the number of dimensions in the points.
+///
+/// # Warnings
+/// * When compiling for no_std, a `BTreeMap` from the `alloc` crate is used in place of a [`HashMap`].
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "serde",
+    serde(
+        from = "SerializedVoxelGrid<T, N>",
+        into = "SerializedVoxelGrid<T, N>",
+        bound = "T: ComplexField + Copy + AsPrimitive<isize> + serde::Serialize + serde::de::DeserializeOwned"
+    )
+)]
+pub struct VoxelGrid<T, const N: usize>
+where
+    T: ComplexField + Copy + AsPrimitive<isize>,
+{
+    voxel_size: T,
+    voxels: HashMap<[isize; N], Vec<Point<T, N>>>,
+}
+
+/// The serialized form of a [`VoxelGrid`], its voxels are rebuilt from the points when deserializing.
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(bound = "T: Scalar + serde::Serialize + serde::de::DeserializeOwned")]
+struct SerializedVoxelGrid<T: Scalar, const N: usize> {
+    voxel_size: T,
+    points: Vec<Point<T, N>>,
+}
+
+#[cfg(feature = "serde")]
+impl<T, const N: usize> From<VoxelGrid<T, N>> for SerializedVoxelGrid<T, N>
+where
+    T: ComplexField + Copy + AsPrimitive<isize>,
+{
+    fn from(voxel_grid: VoxelGrid<T, N>) -> Self {
+        Self {
+            voxel_size: voxel_grid.voxel_size,
+            points: voxel_grid.voxels.into_values().flatten().collect(),
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<T, const N: usize> From<SerializedVoxelGrid<T, N>> for VoxelGrid<T, N>
+where
+    T: ComplexField + Copy + AsPrimitive<isize>,
+{
+    fn from(serialized: SerializedVoxelGrid<T, N>) -> Self {
+        Self::from_points(&serialized.points, serialized.voxel_size)
+    }
+}
+
+impl<T, const N: usize> VoxelGrid<T, N>
+where
+    T: ComplexField + Copy + AsPrimitive<isize>,
+{
+    /// Creates an empty voxel grid.
+    ///
+    /// # Arguments
+    /// * `voxel_size`: a positive floating point number, specifying the size for each voxel.
+    pub fn new(voxel_size: T) -> Self {
+        Self {
+            voxel_size,
+            voxels: HashMap::new(),
+        }
+    }
+
+    /// Creates a voxel grid, containing all points of a point cloud.
+    ///
+    /// # Arguments
+    /// * `points`: a slice of [`Point`], representing the point cloud.
+    /// * `voxel_size`: a positive floating point number, specifying the size for each voxel.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument("Build Voxel Grid", skip_all, level = "debug")
+    )]
+    pub fn from_points(points: &[Point<T, N>], voxel_size: T) -> Self {
+        let mut voxel_grid = Self::new(voxel_size);
+        points.iter().for_each(|point| voxel_grid.insert(*point));
+        voxel_grid
+    }
+
+    /// Returns the size of each voxel in the grid.
+    pub fn voxel_size(&self) -> T {
+        self.voxel_size
+    }
+
+    /// Returns the amount of occupied voxels in the grid.
+    pub fn len(&self) -> usize {
+        self.voxels.len()
+    }
+
+    /// Returns `true` if the grid contains no points.
+    pub fn is_empty(&self) -> bool {
+        self.voxels.is_empty()
+    }
+
+    /// Returns the total amount of points in the grid.
+    pub fn num_points(&self) -> usize {
+        self.voxels.values().map(Vec::len).sum()
+    }
+
+    /// Calculates the integer coordinates of the voxel which contains a point.
+    ///
+    /// # Arguments
+    /// * `point`: a [`Point`], which does not have to be inside the grid.
+    ///
+    /// # Returns
+    /// An array of `N` [`isize`], representing the coordinates of the voxel.
+    #[inline]
+    pub fn voxel_coordinates(&self, point: &Point<T, N>) -> [isize; N] {
+        calculate_voxel_coordinates(point, self.voxel_size)
+    }
+
+    /// Inserts a point into the voxel containing it.
+    ///
+    /// # Arguments
+    /// * `point`: a [`Point`], to be inserted into the grid.
+    pub fn insert(&mut self, point: Point<T, N>) {
+        self.voxels
+            .entry(self.voxel_coordinates(&point))
+            .or_default()
+            .push(point);
+    }
+
+    /// Looks up the points inside the voxel that contains a point.
+    ///
+    /// # Arguments
+    /// * `point`: a [`Point`], specifying which voxel to look up.
+    ///
+    /// # Returns
+    /// A slice of the [`Point`]s inside the voxel, or [`None`] if the voxel is empty.
+    pub fn get(&self, point: &Point<T, N>) -> Option<&[Point<T, N>]> {
+        self.get_voxel(&self.voxel_coordinates(point))
+    }
+
+    /// Looks up the points inside a voxel.
+    ///
+    /// # Arguments
+    /// * `coordinates`: the integer coordinates of the voxel.
+    ///
+    /// # Returns
+    /// A slice of the [`Point`]s inside the voxel, or [`None`] if the voxel is empty.
+    pub fn get_voxel(&self, coordinates: &[isize; N]) -> Option<&[Point<T, N>]> {
+        self.voxels.get(coordinates).map(Vec::as_slice)
+    }
+
+    /// Iterates over all occupied voxels in the grid.
+    ///
+    /// # Returns
+    /// An [`Iterator`] of the coordinates of each voxel and the [`Point`]s inside it, in no particular order.
+    pub fn iter(&self) -> impl Iterator<Item = (&[isize; N], &[Point<T, N>])> {
+        self.voxels
+            .iter()
+            .map(|(coordinates, points)| (coordinates, points.as_slice()))
+    }
+
+    /// Iterates over the occupied voxels adjacent to a voxel, including the voxel itself.
+    ///
+    /// # Arguments
+    /// * `coordinates`: the integer coordinates of the voxel.
+    ///
+    /// # Returns
+    /// An [`Iterator`] of the coordinates of each occupied neighbouring voxel and the [`Point`]s inside it.
+    pub fn neighbours(
+        &self,
+        coordinates: &[isize; N],
+    ) -> impl Iterator<Item = ([isize; N], &[Point<T, N>])> {
+        neighbouring_voxels(coordinates).filter_map(|neighbour_coordinates| {
+            self.get_voxel(&neighbour_coordinates)
+                .map(|points| (neighbour_coordinates, points))
+        })
+    }
+
+    /// Calculates the mean of the points inside a voxel.
+    ///
+    /// # Arguments
+    /// * `coordinates`: the integer coordinates of the voxel.
+    ///
+    /// # Returns
+    /// The centroid of the voxel as a [`Point`], or [`None`] if the voxel is empty.
+    pub fn centroid(&self, coordinates: &[isize; N]) -> Option<Point<T, N>>
+    where
+        usize: AsPrimitive<T>,
+    {
+        self.get_voxel(coordinates)
+            .map(calculate_point_cloud_center)
+    }
+
+    /// Iterates over the centroids of all occupied voxels in the grid.
+    ///
+    /// # Returns
+    /// An [`Iterator`] of [`Point`], one per voxel, in no particular order.
+    pub fn centroids(&self) -> impl Iterator<Item = Point<T, N>> + '_
+    where
+        usize: AsPrimitive<T>,
+    {
+        self.voxels
+            .values()
+            .map(|points| calculate_point_cloud_center(points))
+    }
+}
+
+impl<T, const N: usize> VoxelGrid<T, N>
+where
+    T: AsPrimitive<isize> + Copy + RealField,
+    usize: AsPrimitive<T>,
+{
+    /// Calculates the statistics of the points inside a voxel.
+    ///
+    /// # Arguments
+    /// * `coordinates`: the integer coordinates of the voxel.
+    ///
+    /// # Returns
+    /// The [`VoxelStatistics`] of the voxel, or [`None`] if the voxel is empty.
+    pub fn statistics(&self, coordinates: &[isize; N]) -> Option<VoxelStatistics<T, N>> {
+        self.get_voxel(coordinates).map(calculate_voxel_statistics)
+    }
+
+    /// Iterates over the statistics of all occupied voxels in the grid.
+    ///
+    /// # Returns
+    /// An [`Iterator`] of the coordinates of each voxel and its [`VoxelStatistics`], in no particular order.
+    pub fn statistics_iter(
+        &self,
+    ) -> impl Iterator<Item = ([isize; N], VoxelStatistics<T, N>)> + '_ {
+        self.voxels
+            .iter()
+            .map(|(coordinates, points)| (*coordinates, calculate_voxel_statistics(points)))
+    }
+
+    /// Finds the closest point to a target, among the points in the voxel containing it and in its neighbouring voxels.
+    ///
+    /// # Arguments
+    /// * `target`: a [`Point`], to search around.
+    ///
+    /// # Returns
+    /// The closest [`Point`] in the neighbourhood, or [`None`] if the neighbourhood is empty.
+    ///
+    /// # Warnings
+    /// * The result is guaranteed to be the true nearest neighbour only if it is within `voxel_size` of the target.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            "Find Nearest Point In Voxel Neighbourhood",
+            skip_all,
+            level = "trace"
+        )
+    )]
+    pub fn nearest(&self, target: &Point<T, N>) -> Option<Point<T, N>> {
+        self.neighbours(&self.voxel_coordinates(target))
+            .flat_map(|(_, points)| points.iter())
+            .map(|point| ((point - target).norm_squared(), point))
+            .min_by(|(distance_a, _), (distance_b, _)| {
+                distance_a
+                    .partial_cmp(distance_b)
+                    .unwrap_or(core::cmp::Ordering::Equal)
+            })
+            .map(|(_, point)| *point)
+    }
+}
+
+#[inline]
+fn calculate_voxel_statistics<T, const N: usize>(points: &[Point<T, N>]) -> VoxelStatistics<T, N>
+where
+    T: Copy + RealField,
+    usize: AsPrimitive<T>,
+{
+    VoxelStatistics {
+        num_points: points.len(),
+        centroid: calculate_point_cloud_center(points),
+        covariance: calculate_point_cloud_covariance(points),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use nalgebra::{Point2, Point3};
+
+    use super::*;
+    use crate::Vec;
+
+    fn generate_grid() -> VoxelGrid<f32, 2> {
+        VoxelGrid::from_points(
+            &[
+                Point2::new(0.1, 0.1),
+                Point2::new(0.3, 0.3),
+                Point2::new(0.9, 0.1),
+                Point2::new(-0.2, 0.4),
+                Point2::new(2.5, 2.5),
+            ],
+            1.0,
+        )
+    }
+
+    #[test]
+    fn test_insert_and_lookup() {
+        let mut voxel_grid = generate_grid();
+        assert_eq!(voxel_grid.len(), 3);
+        assert_eq!(voxel_grid.num_points(), 5);
+        assert_eq!(
+            voxel_grid.voxel_coordinates(&Point2::new(-0.2, 0.4)),
+            [-1, 0]
+        );
+        assert_eq!(
+            voxel_grid.get(&Point2::new(0.5, 0.5)).map(<[_]>::len),
+            Some(3)
+        );
+        assert!(voxel_grid.get(&Point2::new(1.5, 0.5)).is_none());
+
+        voxel_grid.insert(Point2::new(1.5, 0.5));
+        assert_eq!(voxel_grid.len(), 4);
+        assert_eq!(
+            voxel_grid.get_voxel(&[1, 0]),
+            Some([Point2::new(1.5, 0.5)].as_slice())
+        );
+    }
+
+    #[test]
+    fn test_neighbours() {
+        assert_eq!(neighbouring_voxels(&[0, 0, 0]).count(), 27);
+        assert!(neighbouring_voxels(&[4, -2]).all(|coordinates| {
+            (3..=5).contains(&coordinates[0]) && (-3..=-1).contains(&coordinates[1])
+        }));
+
+        let voxel_grid = generate_grid();
+        let mut neighbours = voxel_grid
+            .neighbours(&[0, 0])
+            .map(|(coordinates, _)| coordinates)
+            .collect::<Vec<_>>();
+        neighbours.sort_unstable();
+        assert_eq!(neighbours, [[-1, 0], [0, 0]]);
+        assert_eq!(voxel_grid.neighbours(&[2, 2]).count(), 1);
+        assert_eq!(voxel_grid.neighbours(&[4, 4]).count(), 0);
+    }
+
+    #[test]
+    fn test_statistics() {
+        let voxel_grid = VoxelGrid::from_points(
+            &[
+                Point3::new(0.0, 0.0, 0.5),
+                Point3::new(1.0, 0.0, 0.5),
+                Point3::new(0.0, 1.0, 0.5),
+                Point3::new(1.0, 1.0, 0.5),
+            ],
+            2.0,
+        );
+        assert_eq!(
+            voxel_grid.centroid(&[0, 0, 0]),
+            Some(Point3::new(0.5, 0.5, 0.5))
+        );
+        assert!(voxel_grid.centroid(&[1, 0, 0]).is_none());
+
+        let statistics = voxel_grid.statistics(&[0, 0, 0]).unwrap();
+        assert_eq!(statistics.num_points, 4);
+        assert_eq!(statistics.centroid, Point3::new(0.5, 0.5, 0.5));
+        assert!((statistics.covariance[(0, 0)] - 0.25).abs() < f64::EPSILON);
+        assert!((statistics.covariance[(1, 1)] - 0.25).abs() < f64::EPSILON);
+        assert!(statistics.covariance[(0, 1)].abs() < f64::EPSILON);
+        assert!(statistics.covariance[(2, 2)].abs() < f64::EPSILON);
+        assert_eq!(voxel_grid.statistics_iter().count(), 1);
+    }
+
+    #[test]
+    fn test_nearest() {
+        let voxel_grid = generate_grid();
+        assert_eq!(
+            voxel_grid.nearest(&Point2::new(1.2, 0.1)),
+            Some(Point2::new(0.9, 0.1))
+        );
+        assert_eq!(
+            voxel_grid.nearest(&Point2::new(-0.5, 0.5)),
+            Some(Point2::new(-0.2, 0.4))
+        );
+        assert_eq!(
+            voxel_grid.nearest(&Point2::new(3.2, 3.9)),
+            Some(Point2::new(2.5, 2.5))
+        );
+        // Outside the neighbourhood of any occupied voxel
+        assert!(voxel_grid.nearest(&Point2::new(5.0, 5.0)).is_none());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_round_trip() {
+        let voxel_grid = generate_grid();
+        let serialized = serde_json::to_string(&voxel_grid).unwrap();
+        let deserialized: VoxelGrid<f32, 2> = serde_json::from_str(&serialized).unwrap();
+
+        assert_eq!(deserialized.voxel_size(), voxel_grid.voxel_size());
+        assert_eq!(deserialized.len(), voxel_grid.len());
+        assert_eq!(deserialized.num_points(), voxel_grid.num_points());
+        for (coordinates, points) in voxel_grid.iter() {
+            assert_eq!(deserialized.get_voxel(coordinates), Some(points));
+        }
+    }
+}